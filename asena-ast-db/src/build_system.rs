@@ -2,13 +2,16 @@ use std::{hash::Hash, path::PathBuf};
 
 use dashmap::{DashMap, DashSet};
 
-use crate::{package::Package, ModuleRef};
+use crate::{package::Package, vfs::VfsFile, ModuleRef};
 
 #[derive(Default, Debug)]
 pub struct BuildSystem {
     pub files: DashMap<PathBuf, ModuleRef>,
     pub modules: DashMap<ModuleRef, Package>,
     pub packages: DashSet<Package>,
+
+    /// The files opened in the editor, read by [crate::db::AstDatabase::is_overlaid].
+    pub overlaid: DashSet<VfsFile>,
 }
 
 impl BuildSystem {
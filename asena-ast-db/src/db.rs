@@ -4,7 +4,7 @@ use asena_ast::{AsenaFile, BindingId, GlobalName, QualifiedPath, Variant};
 use asena_leaf::ast::{AstParam, GreenTree, Located, Node};
 use asena_lexer::Lexer;
 use asena_parser::Parser;
-use asena_report::{BoxInternalError, Diagnostic};
+use asena_report::{BoxInternalError, Diagnostic, Reports};
 use asena_span::{Loc, Spanned};
 use itertools::Itertools;
 
@@ -42,6 +42,24 @@ pub trait AstDatabase {
 
     fn global_scope(&self) -> ScopeRef;
 
+    /// If the file is opened in the editor, so its source is read from
+    /// [AstDatabase::overlay_source] instead of the disk. The build system isn't tracked, so it
+    /// has no inputs, and it's only executed again when [crate::vfs::VfsFile::open] or
+    /// [crate::vfs::VfsFile::close] invalidate it. The invalidation is for a single file, so only
+    /// the [AstDatabase::source] of that file is executed again, and it's backdated when the text
+    /// is the same, like when the file is opened with the contents of the disk.
+    fn is_overlaid(&self, vfs_file: VfsFile) -> bool;
+
+    /// The unsaved source of an opened file, it's an input, so changing it only invalidates the
+    /// queries that depend on the file.
+    #[salsa::input]
+    fn overlay_source(&self, vfs_file: VfsFile) -> Arc<String>;
+
+    /// The standard library, that is a dependency of every other package. It's set once by
+    /// [crate::stdlib::load_std], when the database is created.
+    #[salsa::input]
+    fn std_package(&self) -> Package;
+
     #[salsa::interned]
     fn intern_package(&self, package: PackageData) -> Package;
 
@@ -148,13 +166,40 @@ fn items(db: &dyn AstDatabase, vfs_file: VfsFile) -> Arc<HashMap<FunctionId, Arc
     Arc::new(decls)
 }
 
+fn is_overlaid(db: &dyn AstDatabase, vfs_file: VfsFile) -> bool {
+    db.salsa_runtime()
+        .report_synthetic_read(salsa::Durability::LOW);
+    db.build_system().overlaid.contains(&vfs_file)
+}
+
 fn source(db: &dyn AstDatabase, vfs_file: VfsFile) -> Arc<String> {
+    if db.is_overlaid(vfs_file) {
+        return db.overlay_source(vfs_file);
+    }
+
+    // The disk isn't tracked by salsa, the source is read again when the file is changed outside
+    // of the editor, see [VfsFile::reload], or when it's closed. The synthetic reads have a low
+    // durability, so the invalidations don't bump the revision of the high durability inputs,
+    // like the standard library.
+    db.salsa_runtime()
+        .report_synthetic_read(salsa::Durability::LOW);
+
     let vfs_file = db.lookup_intern_vfs_file(vfs_file);
+    let path = vfs_file.id.path.clone();
 
+    // The file can be deleted from the disk while it's still imported by other files.
     let file = vfs_file
         .vfs()
-        .read_file(&vfs_file.name)
-        .expect("Internal error: VFS file not found");
+        .read_file(&vfs_file.name, &path)
+        .unwrap_or_else(|| {
+            let package = db.lookup_intern_package(vfs_file.pkg);
+            package.diagnostic(Diagnostic::of(
+                Loc::new(path.clone(), 0, 0),
+                FileNotFoundError(path),
+            ));
+
+            String::new()
+        });
 
     Arc::new(file)
 }
@@ -163,10 +208,21 @@ fn cst(db: &dyn AstDatabase, vfs_file: VfsFile) -> GreenTree {
     let source = db.source(vfs_file);
     let data = db.lookup_intern_vfs_file(vfs_file);
 
-    let lexer = Lexer::new(data.id.path, &source);
+    let lexer = Lexer::new(data.id.path.clone(), &source);
     let parser = Parser::from(lexer).run(asena_grammar::file);
     let tree = parser.build_tree();
 
+    // Reports the syntax errors with their quickfixes, the errors at the end of the file have no
+    // path, so it's set to the path of the file.
+    let package = db.lookup_intern_package(data.pkg);
+    for mut diagnostic in tree.report().diagnostics.iter().cloned() {
+        if diagnostic.message.span.file.is_none() {
+            diagnostic.message.span.file = Some(data.id.path.clone());
+        }
+
+        package.diagnostic(diagnostic);
+    }
+
     tree.data.into()
 }
 
@@ -210,29 +266,17 @@ fn mk_global_name(db: &dyn AstDatabase, module: FunctionId, decl: AstParam<Decl>
 }
 
 fn mk_vfs_file(db: &dyn AstDatabase, vfs_file: VfsFileData) -> VfsFile {
-    let scope = db.global_scope();
-    let mut scope = scope.write().unwrap();
-
-    let path = vfs_file.id.path.clone();
-    let name = FunctionId::new(&vfs_file.name);
-    let pkg = vfs_file.pkg;
     let id = db.intern_vfs_file(vfs_file);
-    let module = ModuleRef::Found(id);
-
-    db.build_system().add_file(path.clone(), module.clone());
-    db.build_system().add_module(module.clone(), pkg);
-
-    scope.modules.insert(name.to_string(), module.clone());
-    scope.paths.insert(path, module);
-    scope.import(db, id, Some(name));
-
-    let pkg_data = db.lookup_intern_package(pkg);
-    pkg_data.files.insert(id);
-
+    id.register(db);
     id
 }
 
 fn module_ref(db: &dyn AstDatabase, module: Spanned<FunctionId>) -> ModuleRef {
+    // The modules of the global scope aren't tracked, they change when the files are created or
+    // deleted, and when the packages are loaded again, so it's executed again in every revision,
+    // and the files that import it are only checked again if the module is found or lost.
+    db.salsa_runtime().report_untracked_read();
+
     let global_scope = db.global_scope();
     let global_scope = global_scope.read().unwrap();
 
@@ -3,15 +3,24 @@ use std::path::PathBuf;
 use asena_report::{DiagnosticKind, InternalError};
 use thiserror::Error;
 
+use crate::manifest::ManifestError;
+use crate::resolution::ResolveError;
 use crate::*;
 
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[repr(u8)]
 pub enum BuildError {
     #[error("module not found: `{0}`")]
     ModuleNotFoundError(FunctionId),
 
     #[error("file not found: `{0}`")]
     FileNotFoundError(PathBuf),
+
+    #[error("{0}")]
+    InvalidManifestError(ManifestError),
+
+    #[error("{0}")]
+    DependencyError(ResolveError),
 }
 
 impl BuildError {
@@ -25,10 +34,11 @@ impl BuildError {
 
 impl InternalError for BuildError {
     fn code(&self) -> u16 {
-        self.discriminant() as u16
+        // The building errors are `E02xx`, they are listed in `docs/diagnostics.md`.
+        200 + self.discriminant() as u16
     }
 
     fn kind(&self) -> DiagnosticKind {
-        DiagnosticKind::Error
+        DiagnosticKind::BuildError
     }
 }
@@ -19,9 +19,15 @@ pub mod commands;
 pub mod db;
 pub mod def;
 pub mod error;
+pub mod manifest;
 pub mod package;
 pub mod report;
+pub mod resolution;
 pub mod scope;
+pub mod stdlib;
+pub mod structure;
+pub mod symbols;
+pub mod testing;
 pub mod vfs;
 
 pub use error::BuildError::*;
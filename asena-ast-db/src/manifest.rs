@@ -0,0 +1,476 @@
+//! The manifest of a package, it's the `asena.yml` file at the root of the package, like:
+//!
+//! ```yaml
+//! name: example
+//! version: 0.1.0
+//! entry: Main
+//! source:
+//!   - src
+//! dependencies:
+//!   prelude:
+//!     path: ../prelude
+//!   json: 1.2.0
+//! backend:
+//!   optimize: true
+//!   debug: false
+//! ```
+//!
+//! Only this subset of YAML is read. The unknown keys are ignored, but the known ones are
+//! validated, and their errors are reported as build diagnostics at their lines.
+
+use std::path::{Path, PathBuf};
+
+use asena_report::Diagnostic;
+use asena_span::Loc;
+use thiserror::Error;
+
+use crate::error::BuildError;
+use crate::resolution::version::{Version, VersionReq};
+use crate::stdlib::STD_PACKAGE;
+
+/// The name of the manifest file, at the root of the package.
+pub const MANIFEST_FILE: &str = "asena.yml";
+
+/// The module with the entry point of the package, if the manifest doesn't have one.
+pub const DEFAULT_ENTRY: &str = "Main";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    pub name: String,
+    pub version: String,
+
+    /// The directories with the modules of the package, relative to its root. The modules are
+    /// named by their paths relative to them.
+    pub source_dirs: Vec<PathBuf>,
+
+    /// The module with the entry point of the executable of the package.
+    pub entry: String,
+    pub dependencies: Vec<Dependency>,
+    pub backend: BackendOptions,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    pub name: String,
+    pub source: DependencySource,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencySource {
+    /// A package in a directory, relative to the root of the package that depends on it.
+    Path(PathBuf),
+
+    /// A requirement of the versions of the registry, like `1.2.0`, see [`VersionReq`].
+    Version(String),
+}
+
+/// The options of the code generation, they are the defaults of the build commands.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BackendOptions {
+    pub optimize: bool,
+    pub debug: bool,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ManifestError {
+    #[error("could not read the manifest `{0}`: {1}")]
+    ReadError(PathBuf, String),
+
+    #[error("invalid manifest at line {line}: {message}")]
+    SyntaxError { line: usize, message: String },
+
+    #[error("invalid `{key}` at line {line}: {message}")]
+    InvalidValue {
+        line: usize,
+        key: String,
+        message: String,
+    },
+
+    #[error("the package can't be named `{0}`, it's reserved for the standard library")]
+    ReservedName(String),
+}
+
+/// The sections of the manifest, that are the keys with nested entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Source,
+    Dependencies,
+    Backend,
+    Unknown,
+}
+
+/// A dependency being parsed, it's validated when all its entries are read.
+struct PendingDependency {
+    line: usize,
+    name: String,
+    path: Option<PathBuf>,
+    version: Option<String>,
+}
+
+impl Manifest {
+    /// The manifest of a package without `asena.yml`, with the modules at its root.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: "0.0.0".into(),
+            source_dirs: vec![PathBuf::from(".")],
+            entry: DEFAULT_ENTRY.into(),
+            dependencies: vec![],
+            backend: BackendOptions::default(),
+        }
+    }
+
+    /// Reads the manifest of the package at the `root` directory. If there is no manifest, or
+    /// it has no name, the package is named after the directory.
+    pub fn read(root: &Path) -> Result<Self, ManifestError> {
+        let default_name = root
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("Local");
+
+        let path = root.join(MANIFEST_FILE);
+        if !path.exists() {
+            if default_name == STD_PACKAGE {
+                return Err(ManifestError::ReservedName(default_name.into()));
+            }
+
+            return Ok(Self::new(default_name));
+        }
+
+        let text = std::fs::read_to_string(&path)
+            .map_err(|error| ManifestError::ReadError(path.clone(), error.to_string()))?;
+
+        Self::parse(&text, default_name)
+    }
+
+    /// The root directory of the package of the path, that is the closest directory with a
+    /// manifest. If there is none, it's the directory of the path.
+    pub fn find_root(path: &Path) -> PathBuf {
+        let dir = if path.is_dir() {
+            path
+        } else {
+            path.parent().unwrap_or(path)
+        };
+
+        dir.ancestors()
+            .find(|dir| dir.join(MANIFEST_FILE).exists())
+            .unwrap_or(dir)
+            .to_path_buf()
+    }
+
+    /// Parses and validates the manifest, the unknown keys are ignored.
+    pub fn parse(text: &str, default_name: &str) -> Result<Self, ManifestError> {
+        let mut manifest = Self::new(default_name);
+        let mut source_dirs: Option<Vec<PathBuf>> = None;
+        let mut section = Section::Unknown;
+        let mut dependency: Option<PendingDependency> = None;
+        let mut dependency_indent = None;
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.split(" #").next().unwrap_or_default().trim_end();
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+
+            let syntax_error = |message: &str| ManifestError::SyntaxError {
+                line: line_number,
+                message: message.into(),
+            };
+            let invalid = |key: &str, message: String| ManifestError::InvalidValue {
+                line: line_number,
+                key: key.into(),
+                message,
+            };
+
+            if line.starts_with('\t') {
+                return Err(syntax_error("tabs can't be used for indentation"));
+            }
+
+            let indent = line.len() - line.trim_start().len();
+            let line = line.trim_start();
+
+            // The nested entries belong to the last key without indentation.
+            if indent > 0 {
+                match section {
+                    Section::Source => {
+                        let Some(item) = line.strip_prefix('-') else {
+                            return Err(syntax_error("expected a `- directory` item"));
+                        };
+
+                        let dir = unquote(item.trim());
+                        if dir.is_empty() {
+                            return Err(invalid("source", "expected a directory".into()));
+                        }
+                        source_dirs.get_or_insert_with(Vec::new).push(dir.into());
+                    }
+                    Section::Dependencies => {
+                        let (key, value) = entry(line).ok_or_else(|| {
+                            syntax_error("expected a `name: version` or `name:` entry")
+                        })?;
+
+                        let indent_of_names = *dependency_indent.get_or_insert(indent);
+                        if indent < indent_of_names {
+                            return Err(syntax_error("inconsistent indentation"));
+                        }
+
+                        // The entries indented deeper are the fields of the last dependency.
+                        if indent > indent_of_names {
+                            let Some(pending) = dependency.as_mut() else {
+                                return Err(syntax_error("expected a dependency name"));
+                            };
+
+                            match key {
+                                "path" if !value.is_empty() => pending.path = Some(value.into()),
+                                "version" if !value.is_empty() => {
+                                    pending.version = Some(value.into())
+                                }
+                                "path" | "version" => {
+                                    return Err(invalid(key, "expected a value".into()));
+                                }
+                                _ => {}
+                            }
+                            continue;
+                        }
+
+                        if let Some(pending) = dependency.take() {
+                            manifest.dependencies.push(pending.finish(&manifest)?);
+                        }
+
+                        dependency = Some(PendingDependency {
+                            line: line_number,
+                            name: key.into(),
+                            path: None,
+                            version: (!value.is_empty()).then(|| value.into()),
+                        });
+                    }
+                    Section::Backend => {
+                        let (key, value) = entry(line)
+                            .ok_or_else(|| syntax_error("expected a `key: value` entry"))?;
+
+                        let value = match value {
+                            "true" => true,
+                            "false" => false,
+                            _ => return Err(invalid(key, "expected `true` or `false`".into())),
+                        };
+
+                        match key {
+                            "optimize" => manifest.backend.optimize = value,
+                            "debug" => manifest.backend.debug = value,
+                            _ => {}
+                        }
+                    }
+                    Section::Unknown => {}
+                }
+                continue;
+            }
+
+            let (key, value) =
+                entry(line).ok_or_else(|| syntax_error("expected a `key: value` entry"))?;
+
+            if let Some(pending) = dependency.take() {
+                manifest.dependencies.push(pending.finish(&manifest)?);
+            }
+
+            section = Section::Unknown;
+            match key {
+                "name" if !is_package_name(value) => {
+                    let message = format!(
+                        "`{value}` is not a valid package name, it must start with a letter, \
+                         followed by letters, digits, `-` or `_`"
+                    );
+                    return Err(invalid(key, message));
+                }
+                "name" if value == STD_PACKAGE => {
+                    let message = format!("`{value}` is reserved for the standard library");
+                    return Err(invalid(key, message));
+                }
+                "name" => manifest.name = value.to_string(),
+                "version" if value.parse::<Version>().is_err() => {
+                    let message = format!("`{value}` is not a `major.minor.patch` version");
+                    return Err(invalid(key, message));
+                }
+                "version" => manifest.version = value.to_string(),
+                "entry" if !is_module_name(value) => {
+                    let message = format!("`{value}` is not a valid module name");
+                    return Err(invalid(key, message));
+                }
+                "entry" => manifest.entry = value.to_string(),
+                "source" if value.is_empty() => section = Section::Source,
+                "source" => source_dirs = Some(vec![PathBuf::from(value)]),
+                "dependencies" | "backend" if !value.is_empty() => {
+                    return Err(invalid(
+                        key,
+                        "expected the entries on the next lines".into(),
+                    ));
+                }
+                "dependencies" => {
+                    section = Section::Dependencies;
+                    dependency_indent = None;
+                }
+                "backend" => section = Section::Backend,
+                _ => {}
+            }
+        }
+
+        if let Some(pending) = dependency.take() {
+            manifest.dependencies.push(pending.finish(&manifest)?);
+        }
+
+        if let Some(source_dirs) = source_dirs {
+            manifest.source_dirs = source_dirs;
+        }
+
+        // The package is named after its directory, when the manifest has no name.
+        if manifest.name == STD_PACKAGE {
+            return Err(ManifestError::ReservedName(manifest.name));
+        }
+
+        Ok(manifest)
+    }
+
+    /// The manifest written by `asena new`, and `asena init`.
+    pub fn render(&self) -> String {
+        let mut text = format!(
+            "name: {}\nversion: {}\nentry: {}\nsource:\n",
+            self.name, self.version, self.entry
+        );
+
+        for source_dir in &self.source_dirs {
+            text.push_str(&format!("  - {}\n", source_dir.display()));
+        }
+
+        text
+    }
+}
+
+impl PendingDependency {
+    fn finish(self, manifest: &Manifest) -> Result<Dependency, ManifestError> {
+        let invalid = |message: String| ManifestError::InvalidValue {
+            line: self.line,
+            key: "dependencies".into(),
+            message,
+        };
+
+        if !is_package_name(&self.name) {
+            return Err(invalid(format!(
+                "`{}` is not a valid package name",
+                self.name
+            )));
+        }
+
+        if self.name == STD_PACKAGE {
+            return Err(invalid(format!(
+                "`{}` is a dependency of every package, it can't be declared",
+                self.name
+            )));
+        }
+
+        if manifest
+            .dependencies
+            .iter()
+            .any(|dependency| dependency.name == self.name)
+        {
+            return Err(invalid(format!("`{}` is declared twice", self.name)));
+        }
+
+        let source = match (self.path, self.version) {
+            (Some(path), None) => DependencySource::Path(path),
+            (None, Some(version)) if version.parse::<VersionReq>().is_ok() => {
+                DependencySource::Version(version)
+            }
+            (None, Some(version)) => {
+                return Err(invalid(format!(
+                    "`{version}` is not a valid version requirement of `{}`",
+                    self.name
+                )))
+            }
+            (Some(_), Some(_)) => {
+                return Err(invalid(format!(
+                    "`{}` must have either a `path` or a `version`, not both",
+                    self.name
+                )))
+            }
+            (None, None) => {
+                return Err(invalid(format!(
+                    "`{}` must have a `path` or a `version`",
+                    self.name
+                )))
+            }
+        };
+
+        Ok(Dependency {
+            name: self.name,
+            source,
+        })
+    }
+}
+
+impl ManifestError {
+    /// The line of the error, starting at 1, if it's in the text of the manifest.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Self::ReadError(..) | Self::ReservedName(..) => None,
+            Self::SyntaxError { line, .. } | Self::InvalidValue { line, .. } => Some(*line),
+        }
+    }
+
+    /// The error as a diagnostic of the manifest at the path, located at the line of the error
+    /// in its text.
+    pub fn diagnostic(&self, path: &Path, text: &str) -> Diagnostic<BuildError> {
+        let (start, end) = self
+            .line()
+            .and_then(|line| {
+                let start = text
+                    .split_inclusive('\n')
+                    .take(line - 1)
+                    .map(str::len)
+                    .sum::<usize>();
+                let line = text[start..].lines().next()?;
+
+                Some((start, start + line.len()))
+            })
+            .unwrap_or_default();
+
+        let loc = Loc::new(path.to_path_buf(), start, end);
+
+        Diagnostic::of(loc, BuildError::InvalidManifestError(self.clone()))
+    }
+}
+
+/// If the name is a valid package name, like `asena-json`.
+pub fn is_package_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// If the name is a valid module name, like `Data.List`.
+fn is_module_name(name: &str) -> bool {
+    name.split('.').all(|segment| {
+        let mut chars = segment.chars();
+
+        chars.next().is_some_and(|c| c.is_alphabetic())
+            && chars.all(|c| c.is_alphanumeric() || c == '_')
+    })
+}
+
+/// Splits a `key: value` entry, the value is unquoted, and may be empty.
+fn entry(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once(':')?;
+
+    Some((key.trim(), unquote(value.trim())))
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .or_else(|| {
+            value
+                .strip_prefix('\'')
+                .and_then(|value| value.strip_suffix('\''))
+        })
+        .unwrap_or(value)
+}
@@ -28,11 +28,40 @@ pub struct PackageData {
     pub errors: Arc<RwLock<Vec<Diagnostic<BoxInternalError>>>>,
     pub vfs: Arc<FileSystem>,
     pub files: Arc<DashSet<VfsFile>>,
+
+    /// The direct and transitive dependencies of the package, with the standard library first.
     pub dependencies: im::Vector<Package>,
 }
 
 impl Package {
+    /// Creates a package, that only depends on the standard library.
     pub fn new(db: &dyn AstDatabase, name: &str, version: &str, vfs: Arc<FileSystem>) -> Self {
+        Self::with_dependencies(db, name, version, vfs, Vector::new())
+    }
+
+    /// Creates a package that depends on the packages, and on the standard library, the
+    /// declarations of the last dependencies shadow the ones of the first.
+    pub fn with_dependencies(
+        db: &dyn AstDatabase,
+        name: &str,
+        version: &str,
+        vfs: Arc<FileSystem>,
+        mut dependencies: Vector<Package>,
+    ) -> Self {
+        dependencies.push_front(db.std_package());
+
+        Self::without_std(db, name, version, vfs, dependencies)
+    }
+
+    /// Creates a package that only depends on the packages, it's used for the standard library
+    /// itself.
+    pub(crate) fn without_std(
+        db: &dyn AstDatabase,
+        name: &str,
+        version: &str,
+        vfs: Arc<FileSystem>,
+        dependencies: Vector<Package>,
+    ) -> Self {
         db.build_system()
             .add_package(db.intern_package(PackageData {
                 name: name.to_string(),
@@ -40,7 +69,7 @@ impl Package {
                 vfs,
                 files: Default::default(),
                 errors: Arc::new(RwLock::new(Default::default())),
-                dependencies: Vector::new(),
+                dependencies,
             }))
     }
 
@@ -1,7 +1,7 @@
-use std::hash::Hash;
+use std::{hash::Hash, path::Path};
 
-use ariadne::{Color, Config, LabelAttach};
-use asena_report::{BoxInternalError, Diagnostic, DiagnosticKind};
+use ariadne::{Color, ColorGenerator, Config, LabelAttach, Report, ReportKind, Source};
+use asena_report::{BoxInternalError, Diagnostic, DiagnosticKind, InternalError};
 use im::HashSet;
 use itertools::Itertools;
 
@@ -41,8 +41,6 @@ impl Ord for OrdDiagnostic {
 
 impl PackageData {
     pub fn print_diagnostics(&self, db: &dyn AstDatabase) {
-        use ariadne::{ColorGenerator, Report, ReportKind, Source};
-
         let errors = self.errors.read().unwrap();
 
         let groups = errors
@@ -56,41 +54,59 @@ impl PackageData {
             let vfs_file = db.vfs_file(module_ref);
             let text = db.source(vfs_file);
 
-            let errors = diagnostics
-                .cloned()
-                .enumerate()
-                .map(|(order, diagnostic)| OrdDiagnostic { order, diagnostic })
-                .collect::<HashSet<OrdDiagnostic>>();
-
-            let labels = errors
-                .iter()
-                .cloned()
-                .sorted_by(|d, n| d.order.cmp(&n.order))
-                .map(|d| Self::create_new_label(d.diagnostic, &mut colors));
-
-            let errors = errors.len();
-
-            Report::build(ReportKind::Error, (), 0)
-                .with_code("EFFF")
-                .with_message(format!("There was {errors} errors with the file: {file:?}"))
-                .with_labels(labels)
-                .with_config(
-                    Config::default()
-                        .with_tab_width(2)
-                        .with_cross_gap(true)
-                        .with_label_attach(LabelAttach::Start)
-                        .with_char_set(ariadne::CharSet::Ascii)
-                        .with_underlines(false),
-                )
-                .finish()
-                .eprint(Source::from(text.as_ref()))
-                .unwrap();
+            Self::print_report(&file, &text, diagnostics.cloned(), &mut colors);
         }
     }
 
+    /// Prints the diagnostics of a file that isn't a module, like the manifest of the package.
+    pub fn print_file_diagnostics<E>(file: &Path, text: &str, diagnostics: Vec<Diagnostic<E>>)
+    where
+        E: Clone + Send + Sync + InternalError + 'static,
+    {
+        let diagnostics = diagnostics.into_iter().map(Diagnostic::boxed);
+
+        Self::print_report(file, text, diagnostics, &mut ColorGenerator::new());
+    }
+
+    fn print_report(
+        file: &Path,
+        text: &str,
+        diagnostics: impl Iterator<Item = Diagnostic<BoxInternalError>>,
+        colors: &mut ColorGenerator,
+    ) {
+        let errors = diagnostics
+            .enumerate()
+            .map(|(order, diagnostic)| OrdDiagnostic { order, diagnostic })
+            .collect::<HashSet<OrdDiagnostic>>();
+
+        let labels = errors
+            .iter()
+            .cloned()
+            .sorted_by(|d, n| d.order.cmp(&n.order))
+            .map(|d| Self::create_new_label(d.diagnostic, colors));
+
+        let errors = errors.len();
+
+        Report::build(ReportKind::Error, (), 0)
+            .with_code("EFFF")
+            .with_message(format!("There was {errors} errors with the file: {file:?}"))
+            .with_labels(labels)
+            .with_config(
+                Config::default()
+                    .with_tab_width(2)
+                    .with_cross_gap(true)
+                    .with_label_attach(LabelAttach::Start)
+                    .with_char_set(ariadne::CharSet::Ascii)
+                    .with_underlines(false),
+            )
+            .finish()
+            .eprint(Source::from(text))
+            .unwrap();
+    }
+
     fn create_new_label(
         diagnostic: Diagnostic<BoxInternalError>,
-        colors: &mut ariadne::ColorGenerator,
+        colors: &mut ColorGenerator,
     ) -> ariadne::Label {
         let range = diagnostic
             .message
@@ -100,12 +116,15 @@ impl PackageData {
             .unwrap_or_default();
         let message = diagnostic.message.value.to_string();
         let color = match diagnostic.kind {
-            DiagnosticKind::Warning | DiagnosticKind::Deprecated => Color::Yellow,
+            DiagnosticKind::Warning | DiagnosticKind::Deprecated | DiagnosticKind::Unnecessary => {
+                Color::Yellow
+            }
             DiagnosticKind::Info => Color::Blue,
             DiagnosticKind::HardError
             | DiagnosticKind::Error
             | DiagnosticKind::InternalError
-            | DiagnosticKind::LoweringError => Color::Red,
+            | DiagnosticKind::LoweringError
+            | DiagnosticKind::BuildError => Color::Red,
             _ => colors.next(),
         };
 
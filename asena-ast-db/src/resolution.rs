@@ -0,0 +1,571 @@
+//! Resolves the dependencies of a package, from its manifest. The dependencies are directories
+//! with other packages, relative to the package that depends on them, or versions of the packages
+//! of a local registry. The versions chosen are written in the lockfile of the package, and each
+//! dependency is loaded as its own package, with its own file system.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use asena_report::Diagnostic;
+use asena_span::Loc;
+use im::Vector;
+use thiserror::Error;
+
+use crate::{
+    db::AstDatabase,
+    error::BuildError,
+    manifest::{Dependency, DependencySource, Manifest, ManifestError, MANIFEST_FILE},
+    package::Package,
+    vfs::{discover_modules, FileSystem, VfsFileData, VfsPath},
+};
+
+use self::{
+    lockfile::{LockedPackage, Lockfile, LOCK_FILE},
+    version::{Version, VersionReq},
+};
+
+pub mod lockfile;
+pub mod version;
+
+/// The environment variable with the directory of the registry.
+pub const REGISTRY_VAR: &str = "ASENA_REGISTRY";
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ResolveError {
+    #[error("{}: {1}", .0.display())]
+    ManifestError(PathBuf, ManifestError),
+
+    #[error("could not find the package `{0}` at {}", .1.display())]
+    PackageNotFound(String, PathBuf),
+
+    #[error("no version of `{0}` in the registry matches `{1}`")]
+    VersionNotFound(String, String),
+
+    #[error("the package at {} is named `{found}`, but it's required as `{name}`", .path.display())]
+    NameMismatch {
+        name: String,
+        found: String,
+        path: PathBuf,
+    },
+
+    #[error("the package `{0}` is required both from {} and from {}", .1.display(), .2.display())]
+    VersionConflict(String, PathBuf, PathBuf),
+
+    #[error("dependency cycle: {}", .0.join(" -> "))]
+    DependencyCycle(Vec<String>),
+
+    #[error("invalid lockfile `{}`: {1}", .0.display())]
+    LockfileError(PathBuf, String),
+}
+
+/// A registry of packages in a local directory, without a server, with a directory for each
+/// version of each package, like `json/1.2.3`, that has the manifest and the modules of the
+/// package. It can be checked in, or cached, so the CI builds offline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Registry {
+    pub dir: PathBuf,
+}
+
+/// A dependency chosen by the resolution.
+#[derive(Debug, Clone)]
+pub struct ResolvedPackage {
+    pub manifest: Manifest,
+    pub version: Version,
+
+    /// The root directory of the package.
+    pub root: PathBuf,
+
+    /// The directory of a path dependency, relative to the root of the resolved package, as it's
+    /// written in the lockfile.
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct Resolution {
+    /// The direct and transitive dependencies, each one after its own dependencies.
+    pub packages: Vec<ResolvedPackage>,
+}
+
+impl Registry {
+    /// The registry at the `ASENA_REGISTRY` directory, or at `~/.asena/registry`.
+    pub fn from_env() -> Self {
+        let dir = std::env::var_os(REGISTRY_VAR)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| {
+                let home = std::env::var_os("HOME")
+                    .or_else(|| std::env::var_os("USERPROFILE"))
+                    .map(PathBuf::from)
+                    .unwrap_or_default();
+
+                home.join(".asena").join("registry")
+            });
+
+        Self { dir }
+    }
+
+    /// The versions of the package in the registry, from the oldest to the newest.
+    pub fn versions(&self, name: &str) -> Vec<Version> {
+        let Ok(entries) = std::fs::read_dir(self.dir.join(name)) else {
+            return vec![];
+        };
+
+        let mut versions = entries
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+            .collect::<Vec<Version>>();
+        versions.sort();
+        versions
+    }
+
+    pub fn package_dir(&self, name: &str, version: &Version) -> PathBuf {
+        self.dir.join(name).join(version.to_string())
+    }
+}
+
+impl Resolution {
+    /// The lockfile with the chosen versions.
+    pub fn lockfile(&self) -> Lockfile {
+        let mut packages = self
+            .packages
+            .iter()
+            .map(|package| LockedPackage {
+                name: package.manifest.name.clone(),
+                version: package.version,
+                path: package.path.clone(),
+                dependencies: package
+                    .manifest
+                    .dependencies
+                    .iter()
+                    .map(|dependency| dependency.name.clone())
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Lockfile { packages }
+    }
+
+    /// Creates the packages of the dependencies, each one with a file system rooted at its
+    /// directory, and with its modules. Returns them, as they are the dependencies of the
+    /// resolved package.
+    pub fn load(&self, db: &dyn AstDatabase) -> Vector<Package> {
+        // The packages depend on their transitive dependencies too, as their declarations are
+        // discovered by the backends, with the ones of the package.
+        let mut loaded: HashMap<&str, (Package, Vector<Package>)> = HashMap::new();
+
+        for resolved in &self.packages {
+            let mut dependencies = Vector::new();
+            for dependency in &resolved.manifest.dependencies {
+                let Some((pkg, transitive)) = loaded.get(dependency.name.as_str()) else {
+                    continue;
+                };
+
+                for pkg in transitive.iter().chain([pkg]) {
+                    if !dependencies.contains(pkg) {
+                        dependencies.push_back(*pkg);
+                    }
+                }
+            }
+
+            let vfs = FileSystem {
+                base_dir: Some(resolved.root.clone()),
+                ..Default::default()
+            };
+            let name = &resolved.manifest.name;
+            let version = resolved.version.to_string();
+            let pkg =
+                Package::with_dependencies(db, name, &version, Arc::new(vfs), dependencies.clone());
+
+            for source_dir in &resolved.manifest.source_dirs {
+                for (module, path) in discover_modules(&resolved.root.join(source_dir)) {
+                    VfsFileData::new(db, &module, VfsPath { path }, pkg);
+                }
+            }
+
+            loaded.insert(name.as_str(), (pkg, dependencies));
+        }
+
+        self.packages
+            .iter()
+            .filter_map(|resolved| Some(loaded.get(resolved.manifest.name.as_str())?.0))
+            .collect()
+    }
+}
+
+impl ResolveError {
+    /// The name of the dependency that couldn't be resolved.
+    pub fn dependency(&self) -> Option<&str> {
+        match self {
+            Self::PackageNotFound(name, _)
+            | Self::VersionNotFound(name, _)
+            | Self::NameMismatch { name, .. }
+            | Self::VersionConflict(name, ..) => Some(name),
+            Self::DependencyCycle(names) => names.last().map(String::as_str),
+            Self::ManifestError(..) | Self::LockfileError(..) => None,
+        }
+    }
+
+    /// The error as a diagnostic of the manifest at the path, located at the entry of the
+    /// dependency, if the manifest has it.
+    pub fn diagnostic(&self, path: &Path, text: &str) -> Diagnostic<BuildError> {
+        let mut start = 0;
+        let mut range = (0, 0);
+        for line in text.split_inclusive('\n') {
+            let entry = line.trim_start();
+            let is_dependency = line.starts_with(char::is_whitespace)
+                && self.dependency().is_some_and(|name| {
+                    entry
+                        .strip_prefix(name)
+                        .is_some_and(|rest| rest.trim_start().starts_with(':'))
+                });
+
+            if is_dependency {
+                range = (start, start + line.trim_end().len());
+                break;
+            }
+
+            start += line.len();
+        }
+
+        let loc = Loc::new(path.to_path_buf(), range.0, range.1);
+
+        Diagnostic::of(loc, BuildError::DependencyError(self.clone()))
+    }
+}
+
+/// Resolves the dependencies of the package at the `root` directory, with the registry of the
+/// environment, and writes its lockfile. The versions of the lockfile are kept, while they match
+/// the requirements.
+pub fn resolve_package(root: &Path, manifest: &Manifest) -> Result<Resolution, ResolveError> {
+    let lockfile = Lockfile::read(root)?;
+    let resolution = resolve(root, manifest, &Registry::from_env(), &lockfile)?;
+
+    // The packages without dependencies don't need a lockfile.
+    let lockfile = resolution.lockfile();
+    if !lockfile.packages.is_empty() || root.join(LOCK_FILE).exists() {
+        lockfile.write(root)?;
+    }
+
+    Ok(resolution)
+}
+
+/// Resolves the dependencies of the package at the `root` directory, and their dependencies.
+/// A package is only resolved once, so all the packages that depend on it must agree on its
+/// version.
+pub fn resolve(
+    root: &Path,
+    manifest: &Manifest,
+    registry: &Registry,
+    lockfile: &Lockfile,
+) -> Result<Resolution, ResolveError> {
+    let mut resolver = Resolver {
+        registry,
+        lockfile,
+        resolution: Resolution::default(),
+        stack: vec![manifest.name.clone()],
+    };
+
+    for dependency in &manifest.dependencies {
+        resolver.visit(root, Some(Path::new("")), dependency)?;
+    }
+
+    Ok(resolver.resolution)
+}
+
+struct Resolver<'a> {
+    registry: &'a Registry,
+    lockfile: &'a Lockfile,
+    resolution: Resolution,
+
+    /// The names of the packages being resolved, from the root package, to find the cycles.
+    stack: Vec<String>,
+}
+
+impl Resolver<'_> {
+    /// Resolves the dependency of the package at the `parent` directory, and its dependencies.
+    /// The `relative` path is the directory of the parent, relative to the root package, if it
+    /// isn't in the registry.
+    fn visit(
+        &mut self,
+        parent: &Path,
+        relative: Option<&Path>,
+        dependency: &Dependency,
+    ) -> Result<(), ResolveError> {
+        let name = &dependency.name;
+        if let Some(index) = self.stack.iter().position(|package| package == name) {
+            let mut cycle = self.stack[index..].to_vec();
+            cycle.push(name.clone());
+            return Err(ResolveError::DependencyCycle(cycle));
+        }
+
+        let (root, path) = match &dependency.source {
+            DependencySource::Path(path) => (
+                parent.join(path),
+                relative.map(|relative| relative.join(path)),
+            ),
+            DependencySource::Version(requirement) => {
+                let version = self.select(name, requirement)?;
+                (self.registry.package_dir(name, &version), None)
+            }
+        };
+
+        let resolved = self
+            .resolution
+            .packages
+            .iter()
+            .find(|package| &package.manifest.name == name);
+        if let Some(resolved) = resolved {
+            if !is_same_dir(&resolved.root, &root) {
+                let first = resolved.root.clone();
+                return Err(ResolveError::VersionConflict(name.clone(), first, root));
+            }
+
+            return Ok(());
+        }
+
+        let manifest_path = root.join(MANIFEST_FILE);
+        if !manifest_path.exists() {
+            return Err(ResolveError::PackageNotFound(name.clone(), root));
+        }
+
+        let manifest = Manifest::read(&root)
+            .map_err(|error| ResolveError::ManifestError(manifest_path, error))?;
+        if &manifest.name != name {
+            return Err(ResolveError::NameMismatch {
+                name: name.clone(),
+                found: manifest.name,
+                path: root,
+            });
+        }
+
+        self.stack.push(name.clone());
+        for dependency in &manifest.dependencies {
+            self.visit(&root, path.as_deref(), dependency)?;
+        }
+        self.stack.pop();
+
+        self.resolution.packages.push(ResolvedPackage {
+            version: manifest.version.parse().unwrap_or_default(),
+            manifest,
+            root,
+            path,
+        });
+
+        Ok(())
+    }
+
+    /// The version of the registry chosen for the requirement: the locked one, if it still
+    /// matches, or the newest one that matches.
+    fn select(&self, name: &str, requirement: &str) -> Result<Version, ResolveError> {
+        let not_found = || ResolveError::VersionNotFound(name.into(), requirement.into());
+        let requirement = requirement.parse::<VersionReq>().map_err(|_| not_found())?;
+        let versions = self.registry.versions(name);
+
+        let locked = self
+            .lockfile
+            .get(name)
+            .filter(|locked| locked.path.is_none())
+            .map(|locked| locked.version)
+            .filter(|version| requirement.matches(version) && versions.contains(version));
+
+        match locked {
+            Some(version) => Ok(version),
+            None => versions
+                .into_iter()
+                .rev()
+                .find(|version| requirement.matches(version))
+                .ok_or_else(not_found),
+        }
+    }
+}
+
+fn is_same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A temporary directory with the files, it's removed when dropped.
+    struct Fixture(PathBuf);
+
+    impl Fixture {
+        fn new(name: &str, files: &[(String, String)]) -> Self {
+            let dir = std::env::temp_dir().join(format!("asena-{name}-{}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+
+            for (path, text) in files {
+                let path = dir.join(path);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(path, text).unwrap();
+            }
+
+            Self(dir)
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// A registry with the versions of the `json` package.
+    fn registry(name: &str, versions: &[&str]) -> (Fixture, Registry) {
+        let files = versions
+            .iter()
+            .map(|version| {
+                let path = format!("json/{version}/{MANIFEST_FILE}");
+                (path, format!("name: json\nversion: {version}\n"))
+            })
+            .collect::<Vec<_>>();
+
+        let fixture = Fixture::new(name, &files);
+        let registry = Registry {
+            dir: fixture.0.clone(),
+        };
+
+        (fixture, registry)
+    }
+
+    /// Resolves a package that depends on `json`, with the requirement.
+    fn resolve_json(
+        registry: &Registry,
+        requirement: &str,
+        lockfile: &Lockfile,
+    ) -> Result<Version, ResolveError> {
+        let text = format!("name: app\ndependencies:\n  json: {requirement}\n");
+        let manifest = Manifest::parse(&text, "app").unwrap();
+        let resolution = resolve(&registry.dir, &manifest, registry, lockfile)?;
+
+        Ok(resolution.packages[0].version)
+    }
+
+    fn locked_json(version: Version) -> Lockfile {
+        Lockfile {
+            packages: vec![LockedPackage {
+                name: "json".into(),
+                version,
+                path: None,
+                dependencies: vec![],
+            }],
+        }
+    }
+
+    #[test]
+    fn it_selects_the_newest_matching_version() {
+        let versions = ["0.9.0", "1.0.0", "1.2.0", "1.10.0", "2.0.0"];
+        let (_fixture, registry) = registry("newest", &versions);
+        let lockfile = Lockfile::default();
+
+        assert_eq!(
+            resolve_json(&registry, "^1.0", &lockfile),
+            Ok(Version::new(1, 10, 0))
+        );
+        assert_eq!(
+            resolve_json(&registry, "^0.9", &lockfile),
+            Ok(Version::new(0, 9, 0))
+        );
+        assert_eq!(
+            resolve_json(&registry, "^3", &lockfile),
+            Err(ResolveError::VersionNotFound("json".into(), "^3".into()))
+        );
+    }
+
+    #[test]
+    fn it_keeps_the_locked_versions_that_match() {
+        let (_fixture, registry) = registry("locked", &["1.0.0", "1.2.0", "1.10.0"]);
+        let lockfile = locked_json(Version::new(1, 2, 0));
+
+        assert_eq!(
+            resolve_json(&registry, "^1.0", &lockfile),
+            Ok(Version::new(1, 2, 0))
+        );
+    }
+
+    #[test]
+    fn it_replaces_the_locked_versions_that_dont_match() {
+        let (_fixture, registry) = registry("outdated", &["1.2.0", "2.0.0", "2.1.0"]);
+
+        // The requirement changed since the lockfile was written.
+        let lockfile = locked_json(Version::new(1, 2, 0));
+        assert_eq!(
+            resolve_json(&registry, "^2.0", &lockfile),
+            Ok(Version::new(2, 1, 0))
+        );
+
+        // The locked version was removed from the registry.
+        let lockfile = locked_json(Version::new(2, 0, 5));
+        assert_eq!(
+            resolve_json(&registry, "^2.0", &lockfile),
+            Ok(Version::new(2, 1, 0))
+        );
+    }
+
+    #[test]
+    fn it_writes_the_chosen_versions_in_the_lockfile() {
+        let (fixture, registry) = registry("lockfile", &["1.2.0"]);
+        let text = "name: app\ndependencies:\n  json: ^1.0\n  util:\n    path: util\n";
+        let manifest = Manifest::parse(text, "app").unwrap();
+
+        let util = fixture.0.join("util");
+        std::fs::create_dir_all(&util).unwrap();
+        let util_manifest = "name: util\nversion: 0.1.0\ndependencies:\n  json: ^1.2\n";
+        std::fs::write(util.join(MANIFEST_FILE), util_manifest).unwrap();
+
+        let resolution = resolve(&fixture.0, &manifest, &registry, &Lockfile::default()).unwrap();
+        let lockfile = resolution.lockfile();
+
+        assert_eq!(
+            lockfile.packages,
+            [
+                LockedPackage {
+                    name: "json".into(),
+                    version: Version::new(1, 2, 0),
+                    path: None,
+                    dependencies: vec![],
+                },
+                LockedPackage {
+                    name: "util".into(),
+                    version: Version::new(0, 1, 0),
+                    path: Some(PathBuf::from("util")),
+                    dependencies: vec!["json".into()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reports_the_dependency_cycles() {
+        let files = [
+            ("a", "name: a\ndependencies:\n  b:\n    path: ../b\n"),
+            ("b", "name: b\ndependencies:\n  a:\n    path: ../a\n"),
+        ]
+        .map(|(name, text)| (format!("{name}/{MANIFEST_FILE}"), text.to_string()));
+        let fixture = Fixture::new("cycle", &files);
+
+        let text = "name: app\ndependencies:\n  a:\n    path: a\n";
+        let manifest = Manifest::parse(text, "app").unwrap();
+        let error = resolve(
+            &fixture.0,
+            &manifest,
+            &Registry::from_env(),
+            &Lockfile::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error,
+            ResolveError::DependencyCycle(vec!["a".into(), "b".into(), "a".into()])
+        );
+        assert_eq!(error.to_string(), "dependency cycle: a -> b -> a");
+        assert_eq!(error.dependency(), Some("a"));
+    }
+}
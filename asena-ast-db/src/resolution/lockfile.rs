@@ -0,0 +1,206 @@
+//! The lockfile of a package, it's the `asena.lock` file next to the manifest, with the versions
+//! of all the dependencies chosen by the last resolution, like:
+//!
+//! ```yaml
+//! json:
+//!   version: 1.2.3
+//!   dependencies: [prelude]
+//! prelude:
+//!   version: 0.1.0
+//!   path: ../prelude
+//!   dependencies: []
+//! ```
+//!
+//! The next resolutions keep the locked versions, while they match the requirements of the
+//! manifests, so the builds are reproducible.
+
+use std::path::{Path, PathBuf};
+
+use super::{version::Version, ResolveError};
+
+/// The name of the lockfile, at the root of the package.
+pub const LOCK_FILE: &str = "asena.lock";
+
+const HEADER: &str = "# This file is generated by asena, it shouldn't be edited by hand.\n";
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Lockfile {
+    /// The packages, sorted by name.
+    pub packages: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: Version,
+
+    /// The directory of a path dependency, relative to the root of the package. The packages
+    /// of the registry have no path.
+    pub path: Option<PathBuf>,
+
+    /// The names of the direct dependencies of the package.
+    pub dependencies: Vec<String>,
+}
+
+impl Lockfile {
+    /// Reads the lockfile of the package at the `root` directory, it's empty if there is none.
+    pub fn read(root: &Path) -> Result<Self, ResolveError> {
+        let path = root.join(LOCK_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let text = std::fs::read_to_string(&path)
+            .map_err(|error| ResolveError::LockfileError(path.clone(), error.to_string()))?;
+
+        Self::parse(&text).map_err(|message| ResolveError::LockfileError(path, message))
+    }
+
+    /// Writes the lockfile of the package at the `root` directory, if it changed.
+    pub fn write(&self, root: &Path) -> Result<(), ResolveError> {
+        let path = root.join(LOCK_FILE);
+        let text = self.render();
+        if std::fs::read_to_string(&path).is_ok_and(|current| current == text) {
+            return Ok(());
+        }
+
+        std::fs::write(&path, text)
+            .map_err(|error| ResolveError::LockfileError(path, error.to_string()))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LockedPackage> {
+        self.packages.iter().find(|package| package.name == name)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut packages: Vec<LockedPackage> = vec![];
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once(':') else {
+                return Err(format!(
+                    "expected a `key: value` entry at line {line_number}"
+                ));
+            };
+            let value = value.trim();
+
+            // The packages aren't indented, and their fields are.
+            if !line.starts_with(char::is_whitespace) {
+                packages.push(LockedPackage {
+                    name: key.trim().to_string(),
+                    version: Version::default(),
+                    path: None,
+                    dependencies: vec![],
+                });
+                continue;
+            }
+
+            let Some(package) = packages.last_mut() else {
+                return Err(format!("expected a package at line {line_number}"));
+            };
+
+            match key.trim() {
+                "version" => {
+                    package.version = value
+                        .parse()
+                        .map_err(|_| format!("invalid version at line {line_number}"))?;
+                }
+                "path" => package.path = Some(PathBuf::from(value)),
+                "dependencies" => {
+                    let Some(names) = value
+                        .strip_prefix('[')
+                        .and_then(|value| value.strip_suffix(']'))
+                    else {
+                        return Err(format!(
+                            "expected a `[name, ...]` list at line {line_number}"
+                        ));
+                    };
+
+                    package.dependencies = names
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|name| !name.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Self { packages })
+    }
+
+    pub fn render(&self) -> String {
+        let mut text = HEADER.to_string();
+
+        for package in &self.packages {
+            text.push_str(&format!("{}:\n", package.name));
+            text.push_str(&format!("  version: {}\n", package.version));
+            if let Some(path) = &package.path {
+                text.push_str(&format!("  path: {}\n", path.display()));
+            }
+            text.push_str(&format!(
+                "  dependencies: [{}]\n",
+                package.dependencies.join(", ")
+            ));
+        }
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_the_rendered_lockfile() {
+        let lockfile = Lockfile {
+            packages: vec![
+                LockedPackage {
+                    name: "json".into(),
+                    version: Version::new(1, 2, 3),
+                    path: None,
+                    dependencies: vec!["prelude".into()],
+                },
+                LockedPackage {
+                    name: "prelude".into(),
+                    version: Version::new(0, 1, 0),
+                    path: Some(PathBuf::from("../prelude")),
+                    dependencies: vec![],
+                },
+            ],
+        };
+
+        let text = lockfile.render();
+        assert!(text.starts_with(HEADER));
+        assert_eq!(Lockfile::parse(&text), Ok(lockfile));
+    }
+
+    #[test]
+    fn it_sorts_the_packages_by_name() {
+        let text = "prelude:\n  version: 0.1.0\njson:\n  version: 1.2.3\n";
+        let lockfile = Lockfile::parse(text).unwrap();
+
+        assert_eq!(lockfile.packages[0].name, "json");
+        assert_eq!(
+            lockfile.get("prelude").unwrap().version,
+            Version::new(0, 1, 0)
+        );
+        assert_eq!(lockfile.get("yaml"), None);
+    }
+
+    #[test]
+    fn it_rejects_the_invalid_lockfiles() {
+        assert!(Lockfile::parse("json:\n  version: 1.2\n").is_err());
+        assert!(Lockfile::parse("  version: 1.2.3\n").is_err());
+        assert!(Lockfile::parse("json:\n  dependencies: prelude\n").is_err());
+        assert!(Lockfile::parse("json\n").is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use std::{fmt::Display, str::FromStr};
+
+/// The version of a package, like `1.2.0`.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+/// A requirement of the versions compatible with a version, like `^1.2`. The versions are
+/// compatible if they don't change its leftmost non-zero number, so `^1.2` matches `1.4.0`, but
+/// not `2.0.0`, and `^0.2.1` matches `0.2.5`, but not `0.3.0`. The `^` is optional.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VersionReq {
+    pub version: Version,
+
+    /// The number of parts written in the requirement, the missing ones are zero, and any of
+    /// their values is compatible, like `^0.0` matches `0.0.4`.
+    pub parts: usize,
+}
+
+impl Version {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl VersionReq {
+    /// If the version is compatible with the requirement.
+    pub fn matches(&self, version: &Version) -> bool {
+        let required = &self.version;
+        if version < required {
+            return false;
+        }
+
+        if required.major > 0 || self.parts == 1 {
+            version.major == required.major
+        } else if required.minor > 0 || self.parts == 2 {
+            version.major == 0 && version.minor == required.minor
+        } else {
+            version == required
+        }
+    }
+}
+
+impl FromStr for Version {
+    type Err = ();
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match parse_parts(text).ok_or(())? {
+            (version, 3) => Ok(version),
+            _ => Err(()),
+        }
+    }
+}
+
+impl FromStr for VersionReq {
+    type Err = ();
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.strip_prefix('^').unwrap_or(text);
+        let (version, parts) = parse_parts(text).ok_or(())?;
+
+        Ok(Self { version, parts })
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl Display for VersionReq {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Version {
+            major,
+            minor,
+            patch,
+        } = self.version;
+
+        match self.parts {
+            1 => write!(f, "^{major}"),
+            2 => write!(f, "^{major}.{minor}"),
+            _ => write!(f, "^{major}.{minor}.{patch}"),
+        }
+    }
+}
+
+/// Parses up to three numbers separated by dots, returns the version, and how many numbers were
+/// written.
+fn parse_parts(text: &str) -> Option<(Version, usize)> {
+    let mut numbers = [0; 3];
+    let mut parts = 0;
+
+    for part in text.split('.') {
+        if parts == 3 || part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+
+        numbers[parts] = part.parse().ok()?;
+        parts += 1;
+    }
+
+    let [major, minor, patch] = numbers;
+
+    Some((Version::new(major, minor, patch), parts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(requirement: &str, version: &str) -> bool {
+        let requirement = requirement.parse::<VersionReq>().unwrap();
+        requirement.matches(&version.parse().unwrap())
+    }
+
+    #[test]
+    fn it_matches_the_compatible_versions() {
+        assert!(matches("^1.2", "1.2.0"));
+        assert!(matches("^1.2", "1.4.7"));
+        assert!(!matches("^1.2", "1.1.9"));
+        assert!(!matches("^1.2", "2.0.0"));
+        assert!(matches("^1", "1.9.9"));
+        assert!(!matches("^1", "2.0.0"));
+        assert!(matches("1.2", "1.3.0"));
+    }
+
+    #[test]
+    fn it_matches_the_minor_versions_before_the_first_major_version() {
+        assert!(matches("^0.2.1", "0.2.5"));
+        assert!(!matches("^0.2.1", "0.2.0"));
+        assert!(!matches("^0.2.1", "0.3.0"));
+        assert!(matches("^0.0.3", "0.0.3"));
+        assert!(!matches("^0.0.3", "0.0.4"));
+        assert!(matches("^0.0", "0.0.4"));
+        assert!(!matches("^0.0", "0.1.0"));
+    }
+
+    #[test]
+    fn it_parses_the_versions_and_the_requirements() {
+        assert_eq!("1.2.3".parse(), Ok(Version::new(1, 2, 3)));
+        assert_eq!("1.2".parse::<Version>(), Err(()));
+        assert_eq!("1.2.3.4".parse::<Version>(), Err(()));
+        assert_eq!("1..3".parse::<Version>(), Err(()));
+        assert_eq!("v1.2.3".parse::<Version>(), Err(()));
+
+        let requirement = "^1.2".parse::<VersionReq>().unwrap();
+        assert_eq!(requirement.version, Version::new(1, 2, 0));
+        assert_eq!(requirement.parts, 2);
+        assert_eq!(requirement.to_string(), "^1.2");
+        assert_eq!("0.3".parse::<VersionReq>().unwrap().to_string(), "^0.3");
+    }
+
+    #[test]
+    fn it_orders_the_versions_by_their_numbers() {
+        let mut versions = ["1.10.0", "1.9.0", "0.12.1", "1.9.10"]
+            .map(|version| version.parse::<Version>().unwrap());
+        versions.sort();
+
+        assert_eq!(
+            versions.map(|version| version.to_string()),
+            ["0.12.1", "1.9.0", "1.9.10", "1.10.0"]
+        );
+    }
+}
@@ -174,6 +174,19 @@ impl ScopeData {
         }
     }
 
+    /// Removes the module, and the declarations of its file, that were imported qualified by the
+    /// module name.
+    pub fn remove_module(&mut self, db: &dyn AstDatabase, name: &str, path: &PathBuf) {
+        let is_from_file =
+            |def: &DefWithId| db.lookup_intern_def(*def).defined_at.file.as_ref() == Some(path);
+
+        self.modules.remove(name);
+        self.paths.remove(path);
+        self.types.retain(|_, def| !is_from_file(def));
+        self.constructors.retain(|_, def| !is_from_file(def));
+        self.functions.retain(|_, def| !is_from_file(def));
+    }
+
     pub fn import<'a, P>(&mut self, db: &dyn AstDatabase, file: VfsFile, prefix: P)
     where
         P: Into<Option<FunctionId>> + Clone + 'a,
@@ -0,0 +1,134 @@
+//! The standard library, it's bundled in the toolchain, and it's a dependency of every local
+//! package. Its modules are embedded in the binary, so it does not depend on the installation.
+
+use std::{path::PathBuf, sync::Arc};
+
+use im::Vector;
+use salsa::Durability;
+
+use crate::{
+    db::AstDatabase,
+    package::Package,
+    vfs::{FileSystem, VfsFile, VfsFileData, VfsPath},
+};
+
+/// The name of the standard library package.
+pub const STD_PACKAGE: &str = "std";
+
+/// The module that is implicitly imported in every file, and declares the operators.
+pub const PRELUDE_MODULE: &str = "Prelude";
+
+/// The modules of the standard library, with their sources, in the order they are loaded.
+pub const STD_MODULES: &[(&str, &str)] = &[
+    ("Prelude", include_str!("../std/Prelude.ase")),
+    ("Bool", include_str!("../std/Bool.ase")),
+    ("Nat", include_str!("../std/Nat.ase")),
+    ("Maybe", include_str!("../std/Maybe.ase")),
+    ("List", include_str!("../std/List.ase")),
+    ("IO", include_str!("../std/IO.ase")),
+];
+
+/// Creates the standard library package, and sets it as the [AstDatabase::std_package] input,
+/// it must be called once, when the database is created. The declarations of its modules are
+/// imported into the global scope, so they can be used without qualification, like `println`, or
+/// qualified by the module, like `IO.println`.
+pub fn load_std(db: &mut dyn AstDatabase) -> Package {
+    let sources = STD_MODULES
+        .iter()
+        .map(|(name, source)| (name.to_string(), Arc::new(source.to_string())))
+        .collect();
+
+    let vfs = FileSystem {
+        base_dir: None,
+        sources,
+    };
+    let version = env!("CARGO_PKG_VERSION");
+    let pkg = Package::without_std(db, STD_PACKAGE, version, Arc::new(vfs), Vector::new());
+    db.set_std_package_with_durability(pkg, Durability::HIGH);
+
+    let db = &*db;
+    for (name, _) in STD_MODULES {
+        let path = VfsPath {
+            path: PathBuf::from(format!("<{STD_PACKAGE}>/{name}.ase")),
+        };
+        let file = VfsFileData::new(db, name, path, pkg);
+
+        db.global_scope().write().unwrap().import(db, file, None);
+    }
+
+    pkg
+}
+
+/// Finds the file of a module of the standard library, by its name.
+pub fn std_module(db: &dyn AstDatabase, name: &str) -> Option<VfsFile> {
+    db.std_package()
+        .files(db)
+        .iter()
+        .map(|file| *file)
+        .find(|file| db.lookup_intern_vfs_file(*file).name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::AstDatabaseStorage,
+        manifest::{Manifest, ManifestError},
+    };
+
+    #[salsa::database(AstDatabaseStorage)]
+    struct TestDatabase {
+        storage: salsa::Storage<TestDatabase>,
+    }
+
+    impl salsa::Database for TestDatabase {}
+
+    impl Default for TestDatabase {
+        /// Creates an empty database, with the standard library loaded.
+        fn default() -> Self {
+            let mut db = Self {
+                storage: Default::default(),
+            };
+            load_std(&mut db);
+            db
+        }
+    }
+
+    #[test]
+    fn std_is_loaded_once_and_its_name_is_reserved() {
+        let db = TestDatabase::default();
+        let std = db.std_package();
+        let local_pkg = Package::new(&db, "Local", "0.0.0", Arc::new(Default::default()));
+
+        assert!(db.lookup_intern_package(std).dependencies.is_empty());
+        assert_eq!(
+            db.lookup_intern_package(local_pkg).dependencies,
+            im::vector![std]
+        );
+
+        let dependency = "name: local\ndependencies:\n  std:\n    version: ^0.1\n";
+        assert!(Manifest::parse("name: std\n", "local").is_err());
+        assert!(Manifest::parse(dependency, "local").is_err());
+        assert_eq!(
+            Manifest::parse("version: 0.1.0\n", STD_PACKAGE),
+            Err(ManifestError::ReservedName(STD_PACKAGE.into()))
+        );
+    }
+
+    #[test]
+    fn std_modules_are_read_without_diagnostics() {
+        let db = TestDatabase::default();
+
+        for (name, _) in STD_MODULES {
+            let file = std_module(&db, name).unwrap();
+            db.ast(file);
+
+            let messages = db
+                .diagnostics(file)
+                .iter()
+                .map(|diagnostic| diagnostic.message.to_string())
+                .collect::<Vec<_>>();
+            assert!(messages.is_empty(), "{name}: {messages:#?}");
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! The structure of the files, used by the editor to fold the blocks and the comments, and to
+//! expand the selection to the nodes that enclose the cursor.
+
+use std::ops::Range;
+
+use asena_leaf::node::{Child, Tree};
+use asena_leaf::token::{kind::TokenKind, token_set::HasTokens};
+use asena_span::{Spanned, TextRange};
+
+use crate::{db::AstDatabase, vfs::VfsFile};
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum AstFoldingKind {
+    /// The contents of a block between braces, like the body of a declaration, the cases of a
+    /// `match`, or the statements of a DSL block.
+    Block,
+
+    /// The consecutive lines of comments.
+    Comment,
+}
+
+/// A range of the file that can be folded, in byte offsets.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct AstFoldingRange {
+    pub range: Range<usize>,
+    pub kind: AstFoldingKind,
+}
+
+/// The blocks and the runs of comments of the file, sorted by their start. The blocks are folded
+/// from the opening brace to the end of the last node inside of them, so the closing brace stays
+/// visible.
+pub fn folding_ranges(db: &dyn AstDatabase, file: VfsFile) -> Vec<AstFoldingRange> {
+    let tree = db.cst(file).into_data().or_empty();
+
+    let mut ranges = vec![];
+    block_ranges(&tree.value, &mut ranges);
+
+    for token in tree.tokens() {
+        let text = &token.value.full_text;
+        let start = token.span.range.start();
+        let end = token.span.range.end();
+
+        let trivia_start = start.saturating_sub(text.before_whitespace.len());
+        comment_ranges(&text.before_whitespace, trivia_start, &mut ranges);
+
+        // The trailing trivia starts at the end of the line of the token, only the trailing
+        // trivia of the last token has whole lines.
+        if let Some((line, rest)) = text.after_whitespace.split_once('\n') {
+            comment_ranges(rest, end + line.len() + 1, &mut ranges);
+        }
+    }
+
+    ranges.sort_by_key(|folding| folding.range.start);
+    ranges
+}
+
+/// The ranges of the token at the offset, and of the nodes that enclose it, from the innermost
+/// to the outermost, that is the whole file. The ranges are the ones of the code of the nodes,
+/// without the comments around them.
+pub fn selection_ranges(db: &dyn AstDatabase, file: VfsFile, offset: usize) -> Vec<Range<usize>> {
+    let tree = db.cst(file).into_data().or_empty();
+
+    let mut ranges = tree_range(&tree.value).into_iter().collect::<Vec<_>>();
+    let mut children = &tree.value.children;
+
+    // Walks down from the file to the token, the first child wins when the offset is between two
+    // of them, like in `a|+ b`.
+    while let Some((child, range)) = children.iter().find_map(|child| {
+        let range = code_range(child)?;
+        (range.start <= offset && offset <= range.end).then_some((child, range))
+    }) {
+        if ranges.last() != Some(&range) {
+            ranges.push(range);
+        }
+
+        match child.value {
+            Child::Tree(ref tree) => children = &tree.children,
+            Child::Token(_) => break,
+        }
+    }
+
+    ranges.reverse();
+    ranges
+}
+
+/// The range from the first to the last token of the child, if it has tokens.
+fn code_range(child: &Spanned<Child>) -> Option<Range<usize>> {
+    match child.value {
+        Child::Token(ref token) if token.kind == TokenKind::Eof => None,
+        Child::Token(_) => match child.span.range {
+            TextRange::Actual(ref range) => Some(range.clone()),
+            TextRange::Virtual => None,
+        },
+        Child::Tree(ref tree) => tree_range(tree),
+    }
+}
+
+fn tree_range(tree: &Tree) -> Option<Range<usize>> {
+    let start = tree.children.iter().find_map(code_range)?.start;
+    let end = tree.children.iter().rev().find_map(code_range)?.end;
+
+    Some(start..end)
+}
+
+/// The blocks between the braces that are children of the tree, and of its children.
+fn block_ranges(tree: &Tree, ranges: &mut Vec<AstFoldingRange>) {
+    let children = &tree.children;
+    let open = children
+        .iter()
+        .position(|child| is_token(child, TokenKind::LeftBrace));
+    let close = children
+        .iter()
+        .rposition(|child| is_token(child, TokenKind::RightBrace));
+    if let (Some(open), Some(close)) = (open, close) {
+        if open + 1 < close {
+            ranges.push(AstFoldingRange {
+                range: children[open].span.range.start()..children[close - 1].span.range.end(),
+                kind: AstFoldingKind::Block,
+            });
+        }
+    }
+
+    for child in children {
+        if let Child::Tree(ref tree) = child.value {
+            block_ranges(tree, ranges);
+        }
+    }
+}
+
+fn is_token(child: &Spanned<Child>, kind: TokenKind) -> bool {
+    matches!(child.value, Child::Token(ref token) if token.kind == kind)
+}
+
+/// The runs of two or more lines of comments in the trivia, that starts at the `offset` of the
+/// file. The blank lines, and the skipped characters end the runs.
+fn comment_ranges(trivia: &str, offset: usize, ranges: &mut Vec<AstFoldingRange>) {
+    let mut run: Option<(Range<usize>, usize)> = None;
+    let mut push = |run: Option<(Range<usize>, usize)>| {
+        if let Some((range, lines)) = run {
+            if lines > 1 {
+                ranges.push(AstFoldingRange {
+                    range,
+                    kind: AstFoldingKind::Comment,
+                });
+            }
+        }
+    };
+
+    let mut start = offset;
+    for line in trivia.split('\n') {
+        let code = line.trim();
+        if code.starts_with("//") {
+            let indent = line.len() - line.trim_start().len();
+            let range = start + indent..start + indent + code.len();
+
+            run = match run {
+                Some((run, lines)) => Some((run.start..range.end, lines + 1)),
+                None => Some((range, 1)),
+            };
+        } else {
+            push(run.take());
+        }
+
+        start += line.len() + 1;
+    }
+
+    push(run);
+}
@@ -0,0 +1,229 @@
+//! The outline of the files, and the search of the declarations by name, used by the editor, and
+//! by the `asena search` command.
+
+use std::collections::HashSet;
+
+use asena_ast::{BindingId, Decl, GlobalName};
+use asena_leaf::ast::Located;
+use asena_span::Loc;
+
+use crate::{db::AstDatabase, vfs::VfsFile, ModuleRef};
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum AstSymbolKind {
+    Enum,
+    Variant,
+    Class,
+    Field,
+    Trait,
+    Instance,
+    Method,
+    Function,
+}
+
+/// A declaration of the file, with the declarations inside of it, like the variants of an enum.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct AstSymbol {
+    pub name: String,
+    pub kind: AstSymbolKind,
+
+    /// The location of the whole declaration.
+    pub loc: Loc,
+
+    /// The location of the name of the declaration.
+    pub selection: Loc,
+    pub children: Vec<AstSymbol>,
+}
+
+/// A symbol matching the query of a search, with the name of the symbol that contains it.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct AstSymbolMatch {
+    pub symbol: AstSymbol,
+    pub container: Option<String>,
+    pub score: isize,
+}
+
+impl AstSymbol {
+    fn new(name: BindingId, kind: AstSymbolKind, loc: Loc) -> Self {
+        Self {
+            name: name.to_fn_id().to_string(),
+            kind,
+            loc,
+            selection: name.location().into_owned(),
+            children: vec![],
+        }
+    }
+
+    fn with_children(mut self, children: Vec<AstSymbol>) -> Self {
+        self.children = children;
+        self
+    }
+}
+
+/// The symbols of the file, in the order of the declarations. The signature and the equations of
+/// a binding group are merged into a single symbol.
+pub fn document_symbols(db: &dyn AstDatabase, file: VfsFile) -> Vec<AstSymbol> {
+    use AstSymbolKind::*;
+
+    let source = db.source(file);
+    let mut symbols: Vec<AstSymbol> = vec![];
+
+    for decl in db.ast(file).declarations() {
+        let loc = decl.location().into_owned();
+        let symbol = match decl {
+            Decl::Signature(ref decl) => AstSymbol::new(decl.name(), Function, loc),
+            Decl::Assign(ref decl) => AstSymbol::new(decl.name(), Function, loc),
+            Decl::Enum(ref decl) => {
+                let variants = decl.variants().into_iter().map(|variant| {
+                    AstSymbol::new(variant.name(), Variant, variant.location().into_owned())
+                });
+                let methods = decl.methods().into_iter().map(|method| {
+                    AstSymbol::new(method.name(), Method, method.location().into_owned())
+                });
+
+                AstSymbol::new(decl.name(), Enum, loc)
+                    .with_children(variants.chain(methods).collect())
+            }
+            Decl::Class(ref decl) => {
+                let fields = decl.fields().into_iter().map(|field| {
+                    AstSymbol::new(field.name(), Field, field.location().into_owned())
+                });
+                let methods = decl.methods().into_iter().map(|method| {
+                    AstSymbol::new(method.name(), Method, method.location().into_owned())
+                });
+
+                AstSymbol::new(decl.name(), Class, loc)
+                    .with_children(fields.chain(methods).collect())
+            }
+            Decl::Trait(ref decl) => {
+                let fields = decl.fields().into_iter().map(|field| {
+                    AstSymbol::new(field.name(), Method, field.location().into_owned())
+                });
+                let methods = decl.default_methods().into_iter().map(|method| {
+                    AstSymbol::new(method.name(), Method, method.location().into_owned())
+                });
+
+                AstSymbol::new(decl.name(), Trait, loc)
+                    .with_children(fields.chain(methods).collect())
+            }
+            Decl::Instance(ref decl) => {
+                // The instances have no name, so they are named by their type, like it's written
+                // in the source.
+                let selection = decl.gadt_type().location().into_owned();
+                let text = source
+                    .get(selection.range.start()..selection.range.end())
+                    .unwrap_or_default();
+                let methods = decl.methods().into_iter().map(|method| {
+                    AstSymbol::new(method.name(), Method, method.location().into_owned())
+                });
+
+                AstSymbol {
+                    name: format!(
+                        "instance {}",
+                        text.split_whitespace().collect::<Vec<_>>().join(" ")
+                    ),
+                    kind: Instance,
+                    loc,
+                    selection,
+                    children: methods.collect(),
+                }
+            }
+            Decl::Use(_) | Decl::Command(_) | Decl::Error => continue,
+        };
+
+        let group = symbols.iter_mut().find(|group| {
+            group.kind == Function && symbol.kind == Function && group.name == symbol.name
+        });
+
+        match group {
+            Some(group) => {
+                let start = group.loc.range.start().min(symbol.loc.range.start());
+                let end = group.loc.range.end().max(symbol.loc.range.end());
+                group.loc = Loc::new(group.loc.file.clone(), start, end);
+            }
+            None => symbols.push(symbol),
+        }
+    }
+
+    symbols
+}
+
+/// The symbols of all the loaded files, of all the packages, that matches the `query`, sorted by
+/// how well they match.
+pub fn workspace_symbols(db: &dyn AstDatabase, query: &str) -> Vec<AstSymbolMatch> {
+    let global_scope = db.global_scope();
+    let files = global_scope
+        .read()
+        .unwrap()
+        .modules
+        .values()
+        .filter_map(|module| match module {
+            ModuleRef::Found(file) => Some(*file),
+            ModuleRef::NotFound => None,
+        })
+        .collect::<HashSet<_>>();
+
+    let mut matches = vec![];
+    for file in files {
+        let mut symbols = document_symbols(db, file)
+            .into_iter()
+            .map(|symbol| (None, symbol))
+            .collect::<Vec<_>>();
+
+        while let Some((container, mut symbol)) = symbols.pop() {
+            let children = std::mem::take(&mut symbol.children);
+            symbols.extend(
+                children
+                    .into_iter()
+                    .map(|child| (Some(symbol.name.clone()), child)),
+            );
+
+            if let Some(score) = fuzzy_score(query, &symbol.name) {
+                matches.push(AstSymbolMatch {
+                    symbol,
+                    container,
+                    score,
+                });
+            }
+        }
+    }
+
+    matches.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.symbol.name.cmp(&b.symbol.name))
+    });
+    matches
+}
+
+/// Scores how well the `name` matches the `query`, if all the characters of the query appears in
+/// the name, in the same order, ignoring the case. The matches at the start of the words, and the
+/// consecutive ones are scored higher.
+pub fn fuzzy_score(query: &str, name: &str) -> Option<isize> {
+    let name = name.chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut index = 0;
+    let mut previous = None;
+
+    for c in query.chars().filter(|c| !c.is_whitespace()) {
+        let found = (index..name.len()).find(|&i| name[i].to_lowercase().eq(c.to_lowercase()))?;
+
+        let is_word_start = found == 0
+            || matches!(name[found - 1], '.' | '_')
+            || (name[found].is_uppercase() && name[found - 1].is_lowercase());
+
+        score += 1;
+        if is_word_start {
+            score += 8;
+        }
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += 4;
+        }
+        score -= (found - index) as isize;
+
+        previous = Some(found);
+        index = found + 1;
+    }
+
+    Some(score)
+}
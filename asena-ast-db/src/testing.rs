@@ -0,0 +1,36 @@
+//! The fixtures shared by the tests of the compiler crates, they are not part of the toolchain.
+
+use std::{path::PathBuf, sync::Arc};
+
+use crate::{
+    db::AstDatabase,
+    package::Package,
+    stdlib::load_std,
+    vfs::{FileSystem, VfsFile, VfsFileData, VfsPath},
+};
+
+/// The name of the package created by [load_main].
+pub const LOCAL_PACKAGE: &str = "Local";
+
+/// The path of the module created by [load_main].
+pub const MAIN_PATH: &str = "<Local>/Main.ase";
+
+/// Loads the standard library, and a package with `source` as its `Main` module, that is
+/// imported into the global scope.
+pub fn load_main(db: &mut dyn AstDatabase, source: &str) -> (Package, VfsFile) {
+    load_std(db);
+
+    let vfs = FileSystem {
+        base_dir: None,
+        sources: im::hashmap! {"Main".to_string() => Arc::new(source.to_string())},
+    };
+    let db = &*db;
+    let pkg = Package::new(db, LOCAL_PACKAGE, "0.0.0", Arc::new(vfs));
+    let path = VfsPath {
+        path: PathBuf::from(MAIN_PATH),
+    };
+    let file = VfsFileData::new(db, "Main", path, pkg);
+    db.global_scope().write().unwrap().import(db, file, None);
+
+    (pkg, file)
+}
@@ -1,15 +1,26 @@
 use std::{
     fmt::Debug,
     hash::Hash,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, RwLock},
 };
 
-use crate::{db::AstDatabase, package::Package, scope::ScopeData};
+use asena_ast::FunctionId;
+
+use crate::{
+    db::{AstDatabase, IsOverlaidQuery, SourceQuery},
+    package::Package,
+    scope::ScopeData,
+    ModuleRef,
+};
 
 #[derive(Debug, Default)]
 pub struct FileSystem {
     pub base_dir: Option<PathBuf>,
+
+    /// The sources held in memory, by module name, they are read before the disk. It's used by
+    /// the packages bundled in the toolchain, like the standard library.
+    pub sources: im::HashMap<String, Arc<String>>,
 }
 
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, PartialOrd, Ord)]
@@ -65,19 +76,151 @@ pub struct VfsPath {
 }
 
 impl FileSystem {
-    pub fn read_file(&self, path: &str) -> Option<String> {
-        let result = match self.base_dir {
-            Some(ref base_dir) => {
-                std::fs::read_to_string(base_dir.join(path).with_extension("ase"))
-            }
-            None => std::fs::read_to_string(format!("{path}.ase")),
+    /// Reads the source of the module, the sources held in memory are read before the disk.
+    pub fn read_file(&self, name: &str, path: &Path) -> Option<String> {
+        if let Some(source) = self.sources.get(name) {
+            return Some(source.to_string());
+        }
+
+        std::fs::read_to_string(path).ok()
+    }
+}
+
+/// The name of the module of the file, by its path relative to the source directory, like
+/// `Data.List` for `Data/List.ase`.
+pub fn module_name(source_dir: &Path, path: &Path) -> Option<String> {
+    if path.extension()? != "ase" {
+        return None;
+    }
+
+    let relative = path.strip_prefix(source_dir).ok()?.with_extension("");
+    let segments = relative
+        .components()
+        .map(|component| component.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(segments.join("."))
+}
+
+/// The `.ase` files of the source directory, and of its subdirectories, with their module names,
+/// sorted by name. The hidden files, and the `target` directories are skipped.
+pub fn discover_modules(source_dir: &Path) -> Vec<(String, PathBuf)> {
+    let mut modules = vec![];
+    let mut dirs = vec![source_dir.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
         };
 
-        result
-            .unwrap_or_else(|_| {
-                panic!("Failed to read file: {}", path);
-            })
-            .into()
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let skip = match path.file_name().and_then(|name| name.to_str()) {
+                Some(name) => name.starts_with('.') || name == "target",
+                None => true,
+            };
+
+            if skip {
+                continue;
+            } else if path.is_dir() {
+                dirs.push(path);
+            } else if let Some(name) = module_name(source_dir, &path) {
+                modules.push((name, path));
+            }
+        }
+    }
+
+    modules.sort();
+    modules
+}
+
+impl VfsFile {
+    /// Opens the file in the overlay, its source becomes the unsaved `text` instead of the
+    /// contents on the disk, until it's closed.
+    ///
+    /// The diagnostics are only cleared if the text changes: salsa backdates the source when it's
+    /// the same, so the queries that report the diagnostics aren't executed again.
+    pub fn open(self, db: &mut dyn AstDatabase, text: Arc<String>) {
+        if db.source(self) != text {
+            self.clear_diagnostics(db);
+        }
+        db.set_overlay_source(self, text);
+
+        if db.build_system().overlaid.insert(self) {
+            IsOverlaidQuery.in_db_mut(db).invalidate(&self);
+        }
+    }
+
+    /// Closes the file in the overlay, so its source is read from the disk again.
+    pub fn close(self, db: &mut dyn AstDatabase) {
+        if db.build_system().overlaid.remove(&self).is_some() {
+            // The source is compared with the disk before the overlay is invalidated, like in
+            // [VfsFile::open], as the diagnostics of the same text aren't reported again.
+            let data = db.lookup_intern_vfs_file(self);
+            let text = data.vfs.read_file(&data.name, &data.id.path);
+            if text.as_deref() != Some(db.source(self).as_str()) {
+                self.clear_diagnostics(db);
+            }
+            IsOverlaidQuery.in_db_mut(db).invalidate(&self);
+        }
+    }
+
+    /// Reads the source of the file from the disk again, when it's changed outside of the
+    /// editor. If it's opened, the unsaved source is kept.
+    pub fn reload(self, db: &mut dyn AstDatabase) {
+        if !db.build_system().overlaid.contains(&self) {
+            self.clear_diagnostics(db);
+            SourceQuery.in_db_mut(db).invalidate(&self);
+        }
+    }
+
+    /// Adds the file to the build system and to its package, and imports its declarations into
+    /// the global scope, qualified by the module name.
+    pub fn register(self, db: &dyn AstDatabase) {
+        let data = db.lookup_intern_vfs_file(self);
+        let name = FunctionId::new(&data.name);
+        let module = ModuleRef::Found(self);
+
+        db.build_system()
+            .add_file(data.id.path.clone(), module.clone());
+        db.build_system().add_module(module.clone(), data.pkg);
+        db.lookup_intern_package(data.pkg).files.insert(self);
+
+        let global_scope = db.global_scope();
+        let mut global_scope = global_scope.write().unwrap();
+        global_scope
+            .modules
+            .insert(name.to_string(), module.clone());
+        global_scope.paths.insert(data.id.path.clone(), module);
+        global_scope.import(db, self, Some(name));
+    }
+
+    /// Removes the file from the build system, from its package and from the global scope, when
+    /// it's deleted from the disk. It can be registered again, if it's created again.
+    pub fn unregister(self, db: &dyn AstDatabase) {
+        let data = db.lookup_intern_vfs_file(self);
+        let module = ModuleRef::Found(self);
+
+        db.build_system().files.remove(&data.id.path);
+        db.build_system().modules.remove(&module);
+        db.lookup_intern_package(data.pkg).files.remove(&self);
+        self.clear_diagnostics(db);
+
+        let global_scope = db.global_scope();
+        let mut global_scope = global_scope.write().unwrap();
+        global_scope.remove_module(db, &FunctionId::new(&data.name).to_string(), &data.id.path);
+    }
+
+    /// Removes the diagnostics of the file from its package, as they are reported again when
+    /// the queries of the file are executed with the new source.
+    fn clear_diagnostics(self, db: &dyn AstDatabase) {
+        let data = db.lookup_intern_vfs_file(self);
+        let package = db.lookup_intern_package(data.pkg);
+
+        package
+            .errors
+            .write()
+            .unwrap()
+            .retain(|diagnostic| diagnostic.message.span.file.as_ref() != Some(&data.id.path));
     }
 }
 
@@ -119,3 +262,146 @@ impl From<&str> for VfsPath {
         Self { path: value.into() }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::AstDatabaseStorage,
+        testing::{load_main, MAIN_PATH},
+    };
+
+    #[salsa::database(AstDatabaseStorage)]
+    struct TestDatabase {
+        storage: salsa::Storage<TestDatabase>,
+    }
+
+    impl salsa::Database for TestDatabase {}
+
+    /// The messages of the diagnostics of the file, after its tree is built.
+    fn messages(db: &TestDatabase, file: VfsFile) -> Vec<String> {
+        db.cst(file);
+
+        let data = db.lookup_intern_vfs_file(file);
+        let package = db.lookup_intern_package(data.pkg);
+        let errors = package.errors.read().unwrap();
+        errors
+            .iter()
+            .filter(|diagnostic| diagnostic.message.span.file.as_ref() == Some(&data.id.path))
+            .map(|diagnostic| diagnostic.message.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn overlay_keeps_the_diagnostics_of_the_same_text() {
+        let source = "one : Nat\none = (\n";
+        let mut db = TestDatabase {
+            storage: Default::default(),
+        };
+        let (_, file) = load_main(&mut db, source);
+
+        let on_disk = messages(&db, file);
+        assert!(!on_disk.is_empty());
+
+        // The source is backdated, so the tree isn't built again.
+        file.open(&mut db, Arc::new(source.to_string()));
+        assert_eq!(messages(&db, file), on_disk);
+
+        file.open(&mut db, Arc::new("one : Nat\none = Zero\n".to_string()));
+        assert_eq!(messages(&db, file), Vec::<String>::new());
+
+        file.close(&mut db);
+        assert_eq!(messages(&db, file), on_disk);
+
+        // Closing the file with the text of the disk keeps the diagnostics too.
+        file.open(&mut db, Arc::new(source.to_string()));
+        file.close(&mut db);
+        assert_eq!(messages(&db, file), on_disk);
+    }
+
+    #[test]
+    fn discovers_the_modules_of_the_source_directory() {
+        let source_dir = std::env::temp_dir().join(format!("asena-modules-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&source_dir);
+
+        for path in [
+            "Main.ase",
+            "Data/List.ase",
+            ".hidden/Secret.ase",
+            "target/Built.ase",
+            "notes.txt",
+        ] {
+            let path = source_dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+
+        let modules = discover_modules(&source_dir);
+        std::fs::remove_dir_all(&source_dir).unwrap();
+
+        assert_eq!(
+            modules,
+            vec![
+                ("Data.List".to_string(), source_dir.join("Data/List.ase")),
+                ("Main".to_string(), source_dir.join("Main.ase")),
+            ]
+        );
+    }
+
+    #[test]
+    fn unregisters_the_files_from_the_package_and_the_global_scope() {
+        let mut db = TestDatabase {
+            storage: Default::default(),
+        };
+        let (pkg, file) = load_main(&mut db, "two : Nat\ntwo = Succ (Succ Zero)\n");
+        let path = PathBuf::from(MAIN_PATH);
+
+        let is_registered = |db: &TestDatabase| {
+            let global_scope = db.global_scope();
+            let global_scope = global_scope.read().unwrap();
+
+            (
+                db.build_system().files.contains_key(&path),
+                db.lookup_intern_package(pkg).files.contains(&file),
+                global_scope.modules.contains_key("Main"),
+                global_scope.paths.contains_key(&path),
+                global_scope
+                    .functions
+                    .contains_key(&FunctionId::new("Main.two")),
+            )
+        };
+
+        assert_eq!(is_registered(&db), (true, true, true, true, true));
+
+        file.unregister(&db);
+        assert_eq!(is_registered(&db), (false, false, false, false, false));
+
+        // It's registered again when the file is created again.
+        file.register(&db);
+        assert_eq!(is_registered(&db), (true, true, true, true, true));
+    }
+
+    #[test]
+    fn removing_a_module_keeps_the_declarations_of_the_other_files() {
+        let mut db = TestDatabase {
+            storage: Default::default(),
+        };
+        load_main(&mut db, "two : Nat\ntwo = Succ (Succ Zero)\n");
+
+        let global_scope = db.global_scope();
+        let mut global_scope = global_scope.write().unwrap();
+        assert!(global_scope.functions.contains_key(&FunctionId::new("two")));
+
+        global_scope.remove_module(&db, "Main", &PathBuf::from(MAIN_PATH));
+
+        assert!(!global_scope.modules.contains_key("Main"));
+        assert!(!global_scope.functions.contains_key(&FunctionId::new("two")));
+        assert!(!global_scope
+            .functions
+            .contains_key(&FunctionId::new("Main.two")));
+
+        // The declarations of the standard library are kept.
+        assert!(global_scope.modules.contains_key("Nat"));
+        assert!(global_scope.types.contains_key(&FunctionId::new("Nat")));
+    }
+}
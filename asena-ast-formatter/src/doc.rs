@@ -0,0 +1,227 @@
+//! The document algebra of the formatter, like the one of Wadler's "A prettier printer". The
+//! documents are built from the syntax tree, and rendered fitting the groups in the width of the
+//! line, or breaking their lines if they don't fit.
+
+use crate::FormatOptions;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Doc {
+    #[default]
+    Nil,
+
+    /// A text without line breaks, except for the ones of the string literals.
+    Text(String),
+
+    /// A space, or a line break if the enclosing group doesn't fit in the line.
+    Line,
+
+    /// Nothing, or a line break if the enclosing group doesn't fit in the line.
+    SoftLine,
+
+    /// A line break, it breaks all the enclosing groups.
+    HardLine,
+
+    /// An empty line, like the ones between declarations. The blank lines are never repeated.
+    BlankLine,
+
+    /// Indents the line breaks of the document by one level.
+    Nest(Box<Doc>),
+
+    Concat(Vec<Doc>),
+
+    /// The line breaks of the document are rendered as spaces, if it fits in the line.
+    Group(Box<Doc>),
+}
+
+impl Doc {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text(text.into())
+    }
+
+    pub fn space() -> Self {
+        Self::text(" ")
+    }
+
+    pub fn concat(docs: impl IntoIterator<Item = Doc>) -> Self {
+        Self::Concat(docs.into_iter().collect())
+    }
+
+    pub fn nest(self) -> Self {
+        Self::Nest(Box::new(self))
+    }
+
+    pub fn group(self) -> Self {
+        Self::Group(Box::new(self))
+    }
+
+    pub fn append(self, other: Doc) -> Self {
+        match self {
+            Self::Nil => other,
+            Self::Concat(mut docs) => {
+                docs.push(other);
+                Self::Concat(docs)
+            }
+            doc => Self::Concat(vec![doc, other]),
+        }
+    }
+
+    pub fn is_nil(&self) -> bool {
+        match self {
+            Self::Nil => true,
+            Self::Concat(docs) => docs.iter().all(Doc::is_nil),
+            _ => false,
+        }
+    }
+
+    /// Renders the document, breaking the groups that don't fit in the width.
+    pub fn render(&self, options: &FormatOptions) -> String {
+        let mut renderer = Renderer {
+            options,
+            output: String::new(),
+            column: 0,
+            indent: 0,
+            line_start: true,
+        };
+        renderer.render(self);
+        renderer.finish()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+struct Renderer<'a> {
+    options: &'a FormatOptions,
+    output: String,
+    column: usize,
+
+    /// The indentation of the current line, it's written with its first text.
+    indent: usize,
+
+    /// If nothing was written in the current line yet.
+    line_start: bool,
+}
+
+impl Renderer<'_> {
+    fn render(&mut self, doc: &Doc) {
+        let mut stack = vec![(0, Mode::Break, doc)];
+
+        while let Some((indent, mode, doc)) = stack.pop() {
+            match doc {
+                Doc::Nil => {}
+                Doc::Text(text) => self.write(text),
+                Doc::Line if mode == Mode::Flat => self.write(" "),
+                Doc::SoftLine if mode == Mode::Flat => {}
+                Doc::Line | Doc::SoftLine | Doc::HardLine => self.newline(indent),
+                Doc::BlankLine => self.blank_line(indent),
+                Doc::Nest(doc) => stack.push((indent + self.options.indent, mode, doc)),
+                Doc::Concat(docs) => {
+                    stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc)));
+                }
+                Doc::Group(doc) if mode == Mode::Flat => stack.push((indent, Mode::Flat, doc)),
+                Doc::Group(doc) => {
+                    let width = self.options.width as isize - self.column as isize;
+                    let mode = if fits(width, (indent, Mode::Flat, doc), &stack) {
+                        Mode::Flat
+                    } else {
+                        Mode::Break
+                    };
+
+                    stack.push((indent, mode, doc));
+                }
+            }
+        }
+    }
+
+    fn write(&mut self, text: &str) {
+        if self.line_start {
+            // The spaces at the start of the line are replaced by the indentation.
+            if text.trim().is_empty() {
+                return;
+            }
+
+            self.output.push_str(&" ".repeat(self.indent));
+            self.column = self.indent;
+            self.line_start = false;
+        }
+
+        self.output.push_str(text);
+        match text.rfind('\n') {
+            Some(index) => self.column = text[index + 1..].chars().count(),
+            None => self.column += text.chars().count(),
+        }
+    }
+
+    fn newline(&mut self, indent: usize) {
+        if !self.line_start {
+            self.trim_end();
+            self.output.push('\n');
+            self.line_start = true;
+        }
+
+        self.indent = indent;
+        self.column = indent;
+    }
+
+    fn blank_line(&mut self, indent: usize) {
+        self.newline(indent);
+        if !self.output.is_empty() && !self.output.ends_with("\n\n") {
+            self.output.push('\n');
+        }
+    }
+
+    fn trim_end(&mut self) {
+        let len = self.output.trim_end_matches([' ', '\t']).len();
+        self.output.truncate(len);
+    }
+
+    fn finish(mut self) -> String {
+        let len = self.output.trim_end().len();
+        self.output.truncate(len);
+        if !self.output.is_empty() {
+            self.output.push('\n');
+        }
+        self.output
+    }
+}
+
+/// If the document fits in the width, until the next line break of the rest of the documents.
+fn fits(mut width: isize, next: (usize, Mode, &Doc), rest: &[(usize, Mode, &Doc)]) -> bool {
+    let mut rest = rest.iter().rev();
+    let mut stack = vec![next];
+
+    // The blank lines before the first text of the group don't break it, as they are placed
+    // before the group in the same way.
+    let mut line_start = true;
+
+    while width >= 0 {
+        let Some((indent, mode, doc)) = stack.pop().or_else(|| rest.next().copied()) else {
+            return true;
+        };
+
+        match doc {
+            Doc::Nil => {}
+            Doc::Text(text) => {
+                line_start = line_start && text.trim().is_empty();
+                match text.find('\n') {
+                    Some(index) => return width >= text[..index].chars().count() as isize,
+                    None => width -= text.chars().count() as isize,
+                }
+            }
+            Doc::Line if mode == Mode::Flat => width -= 1,
+            Doc::SoftLine if mode == Mode::Flat => {}
+            Doc::HardLine | Doc::BlankLine if line_start => {}
+            // The line break after a comment at the end of the group.
+            Doc::HardLine if stack.is_empty() => return true,
+            Doc::HardLine | Doc::BlankLine if mode == Mode::Flat => return false,
+            Doc::Line | Doc::SoftLine | Doc::HardLine | Doc::BlankLine => return true,
+            Doc::Nest(doc) | Doc::Group(doc) => stack.push((indent, mode, doc)),
+            Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc))),
+        }
+    }
+
+    false
+}
@@ -1 +1,202 @@
+//! The canonical code formatter of the language. The concrete syntax tree is printed into a
+//! [Doc], that is rendered in the width of the [FormatOptions], keeping the comments and the
+//! blank lines of the source code.
+//!
+//! The formatted code is parsed again, and it's only returned if it has the same tree as the
+//! source code.
 
+use std::ops::Range;
+
+use asena_leaf::node::{Child, Tree};
+use asena_leaf::token::kind::TokenKind;
+use asena_lexer::Lexer;
+use asena_parser::Parser;
+use asena_report::DiagnosticKind;
+use thiserror::Error;
+
+use printer::{count_tokens, Printer};
+
+pub mod doc;
+mod printer;
+mod trivia;
+
+pub use doc::Doc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// The maximum width of the lines, the groups that don't fit in it are broken.
+    pub width: usize,
+
+    /// The number of spaces of each indentation level.
+    pub indent: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            width: 100,
+            indent: 2,
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    #[error("invalid character at offset {0}")]
+    LexError(usize),
+
+    #[error("syntax error at offset {1}: {0}")]
+    SyntaxError(String, usize),
+
+    #[error("[internal] the formatted code has another syntax tree")]
+    ChangedTreeError,
+}
+
+/// Formats the source code of a file. The files with syntax errors can't be formatted.
+pub fn format(source: &str, options: &FormatOptions) -> Result<String, FormatError> {
+    let parsed = parse(source)?;
+    let formatted = Printer::new(&parsed.tree, parsed.tail())
+        .file(&parsed.tree)
+        .render(options);
+
+    check(&parsed.tree, &formatted)?;
+
+    Ok(formatted)
+}
+
+/// Formats the declarations that intersect the range of the source code. Returns the range of
+/// the source code that is replaced, and the formatted declarations, or `None` if there is no
+/// declaration in the range.
+pub fn format_range(
+    source: &str,
+    range: Range<usize>,
+    options: &FormatOptions,
+) -> Result<Option<(Range<usize>, String)>, FormatError> {
+    let parsed = parse(source)?;
+
+    let mut decls = vec![];
+    let mut bounds: Option<(usize, usize)> = None;
+    let mut index = 0;
+    for decl in &parsed.tree.children {
+        let count = count_tokens(&decl.value);
+        if count == 0 {
+            continue;
+        }
+
+        let (first, last) = (index, index + count - 1);
+        index += count;
+
+        let span = parsed.spans[first].start..parsed.spans[last].end;
+        let intersects = if range.is_empty() {
+            span.start <= range.start && range.start <= span.end
+        } else {
+            span.start < range.end && range.start < span.end
+        };
+
+        if intersects {
+            decls.push(decl);
+            bounds = Some((bounds.map_or(first, |(first, _)| first), last));
+        }
+    }
+
+    let Some((first, last)) = bounds else {
+        return Ok(None);
+    };
+
+    let formatted = Printer::new(&parsed.tree, parsed.tail())
+        .with_bounds(first, last)
+        .decls(&decls)
+        .render(options);
+    let formatted = formatted.trim_end().to_string();
+
+    let replaced = parsed.spans[first].start..parsed.spans[last].end;
+    let mut new_source = source.to_string();
+    new_source.replace_range(replaced.clone(), &formatted);
+    check(&parsed.tree, &new_source)?;
+
+    Ok(Some((replaced, formatted)))
+}
+
+struct Parsed<'a> {
+    source: &'a str,
+    tree: Tree,
+
+    /// The ranges of the tokens, in the order of the source code.
+    spans: Vec<Range<usize>>,
+}
+
+impl Parsed<'_> {
+    /// The whitespace and comments after the last token.
+    fn tail(&self) -> &str {
+        let end = self.spans.last().map_or(0, |span| span.end);
+        &self.source[end..]
+    }
+}
+
+fn parse(source: &str) -> Result<Parsed<'_>, FormatError> {
+    let lexer = Lexer::new(None, source);
+    if let Some(error) = lexer.errors.first() {
+        return Err(FormatError::LexError(error.span().start));
+    }
+
+    let spans = lexer
+        .tokens
+        .iter()
+        .map(|token| token.span.range.start()..token.span.range.end())
+        .collect();
+
+    let tree = Parser::from(lexer).run(asena_grammar::file).build_tree();
+    let error = tree.report.diagnostics.iter().find(|diagnostic| {
+        !matches!(
+            diagnostic.kind,
+            DiagnosticKind::Warning | DiagnosticKind::Unnecessary
+        )
+    });
+
+    if let Some(error) = error {
+        let message = error.message.value.to_string();
+        let offset = error.message.span.range.start();
+        return Err(FormatError::SyntaxError(message, offset));
+    }
+
+    Ok(Parsed {
+        source,
+        tree: tree.data.value,
+        spans,
+    })
+}
+
+/// Checks that the formatted code is parsed into the same tree, as the line breaks can end the
+/// declarations and the statements.
+fn check(tree: &Tree, formatted: &str) -> Result<(), FormatError> {
+    let parsed = parse(formatted).map_err(|_| FormatError::ChangedTreeError)?;
+    if same_tree(tree, &parsed.tree) {
+        Ok(())
+    } else {
+        Err(FormatError::ChangedTreeError)
+    }
+}
+
+fn same_tree(a: &Tree, b: &Tree) -> bool {
+    // The EOF token only holds the comments of the files without tokens.
+    fn children(tree: &Tree) -> Vec<&Child> {
+        tree.children
+            .iter()
+            .map(|child| &child.value)
+            .filter(|child| !matches!(child, Child::Token(token) if token.kind == TokenKind::Eof))
+            .collect()
+    }
+
+    let (a_children, b_children) = (children(a), children(b));
+
+    a.kind == b.kind
+        && a_children.len() == b_children.len()
+        && a_children
+            .iter()
+            .zip(&b_children)
+            .all(|(a, b)| match (a, b) {
+                (Child::Tree(a), Child::Tree(b)) => same_tree(a, b),
+                (Child::Token(a), Child::Token(b)) => a.kind == b.kind && a.text == b.text,
+                _ => false,
+            })
+}
@@ -0,0 +1,568 @@
+//! Builds the [Doc] of the concrete syntax tree. The tokens are kept as they are, only the
+//! whitespace between them is changed, so the formatted code is parsed into the same tree.
+//!
+//! The language uses the line breaks to end the statements and the declarations, so the line
+//! breaks are only added where the parser ignores them: between the declarations, the items of
+//! the blocks, inside of the lists, and before the operators.
+
+use asena_leaf::node::{Child, Token, Tree, TreeKind};
+use asena_leaf::token::kind::TokenKind;
+use asena_lexer::SYMBOLS;
+use asena_span::Spanned;
+
+use crate::doc::Doc;
+use crate::trivia::Trivia;
+
+pub struct Printer<'a> {
+    /// All the tokens of the tree, in the order of the source code.
+    tokens: Vec<&'a Token>,
+
+    /// The whitespace after the last token.
+    tail: &'a str,
+
+    /// The index of the next token to be printed.
+    index: usize,
+
+    /// The index of the token whose comments were already printed, before the group it starts.
+    printed_leading: Option<usize>,
+
+    /// The bounds of the printed tokens, when formatting a range. The comments before the first
+    /// token and after the last one aren't part of the range.
+    bounds: Option<(usize, usize)>,
+}
+
+impl<'a> Printer<'a> {
+    pub fn new(tree: &'a Tree, tail: &'a str) -> Self {
+        let mut tokens = vec![];
+        collect_tokens(tree, &mut tokens);
+
+        Self {
+            tokens,
+            tail,
+            index: 0,
+            printed_leading: None,
+            bounds: None,
+        }
+    }
+
+    /// Prints only the tokens between `first` and `last`, inclusive.
+    pub fn with_bounds(mut self, first: usize, last: usize) -> Self {
+        self.index = first;
+        self.bounds = Some((first, last));
+        self
+    }
+
+    /// The declarations of the file in their own lines, and the comments at the end of it.
+    pub fn file(&mut self, tree: &'a Tree) -> Doc {
+        let mut docs = vec![];
+        for child in tree.children.iter().filter(|child| has_tokens(child)) {
+            if !docs.is_empty() {
+                docs.push(Doc::HardLine);
+            }
+            docs.push(self.child(child));
+        }
+
+        let trivia = Trivia::parse(self.tail, self.tokens.is_empty());
+        for comment in trivia.leading {
+            docs.push(if comment.blank_line {
+                Doc::BlankLine
+            } else {
+                Doc::HardLine
+            });
+            docs.push(Doc::text(comment.text));
+        }
+
+        Doc::concat(docs)
+    }
+
+    /// The declarations of a range of the file, in their own lines.
+    pub fn decls(&mut self, decls: &[&'a Spanned<Child>]) -> Doc {
+        let docs = decls
+            .iter()
+            .enumerate()
+            .flat_map(|(i, decl)| {
+                let line = if i > 0 { Doc::HardLine } else { Doc::Nil };
+                [line, self.child(decl)]
+            })
+            .collect::<Vec<_>>();
+
+        Doc::concat(docs)
+    }
+
+    fn child(&mut self, child: &'a Spanned<Child>) -> Doc {
+        match &child.value {
+            Child::Tree(tree) => self.tree(tree),
+            Child::Token(token) => self.token(token),
+        }
+    }
+
+    fn tree(&mut self, tree: &'a Tree) -> Doc {
+        use TreeKind::*;
+
+        match tree.kind {
+            QualifiedPathTree | ExprLocal => self.tight(&tree.children),
+            DeclUse => self.decl_use(tree),
+            // The comments before the groups are printed outside of them, so they don't break
+            // the groups.
+            ExprBinary | ExprQual | ExprPi => self.leading().append(self.chain(tree)),
+            ExprArray => self.leading().append(self.list(tree)),
+            DeclAssign | InstanceImpl | StmtLet => self.leading().append(self.assign(tree)),
+            _ => self.inline(tree.kind, &refs(&tree.children)),
+        }
+    }
+
+    /// The children separated by spaces, and the blocks between braces with their items in
+    /// their own lines.
+    fn inline(&mut self, kind: TreeKind, children: &[&'a Spanned<Child>]) -> Doc {
+        let mut docs = vec![];
+        let mut prev: Option<&'a Child> = None;
+        let mut i = 0;
+
+        while i < children.len() {
+            let child = children[i];
+            if !has_tokens(child) {
+                i += 1;
+                continue;
+            }
+
+            if let Some(prev) = prev {
+                docs.push(separator(kind, prev, &child.value));
+            }
+
+            if let Some(close) = matching_brace(children, i) {
+                docs.push(self.block(child, &children[i + 1..close], children[close]));
+                prev = Some(&children[close].value);
+                i = close + 1;
+                continue;
+            }
+
+            docs.push(self.child(child));
+            prev = Some(&child.value);
+            i += 1;
+        }
+
+        Doc::concat(docs)
+    }
+
+    /// The tokens without spaces between them, like the paths `Data.List`.
+    fn tight(&mut self, children: &'a [Spanned<Child>]) -> Doc {
+        let mut docs = vec![];
+        let mut prev: Option<&'a Token> = None;
+        for child in children.iter().filter(|child| has_tokens(child)) {
+            let first = first_token(&child.value);
+            if let (Some(prev), Some(first)) = (prev, first) {
+                if glues(prev, first) {
+                    docs.push(Doc::space());
+                }
+            }
+
+            docs.push(self.child(child));
+            prev = last_token(&child.value);
+        }
+
+        Doc::concat(docs)
+    }
+
+    /// `use Data.List`
+    fn decl_use(&mut self, tree: &'a Tree) -> Doc {
+        let Some((keyword, path)) = tree.children.split_first() else {
+            return Doc::Nil;
+        };
+
+        Doc::concat([self.child(keyword), Doc::space(), self.tight(path)])
+    }
+
+    /// The operands of the operators, like `a -> b -> c`, or `a + b`. If they don't fit in the
+    /// line, each operator starts a new line.
+    fn chain(&mut self, tree: &'a Tree) -> Doc {
+        let mut segments = vec![];
+        split_chain(tree, &mut segments);
+
+        let mut segments = segments.into_iter();
+        let Some(first) = segments.next() else {
+            return Doc::Nil;
+        };
+
+        let head = self.inline(tree.kind, &first);
+        let tail = segments
+            .map(|segment| {
+                let (operator, operand) = segment.split_first().unwrap();
+                let operator_doc = self.child(operator);
+                let operand_doc = self.inline(tree.kind, operand);
+
+                Doc::concat([Doc::Line, operator_doc, Doc::space(), operand_doc])
+            })
+            .collect::<Vec<_>>();
+
+        Doc::concat([head, Doc::concat(tail).nest()]).group()
+    }
+
+    /// The items of a list, like `[a, b, c]`. If they don't fit in the line, each item is
+    /// placed in its own line.
+    fn list(&mut self, tree: &'a Tree) -> Doc {
+        let children = &tree.children;
+        let (Some(open), Some(close)) = (children.first(), children.last()) else {
+            return self.inline(tree.kind, &refs(children));
+        };
+        if children.len() < 3 {
+            return self.inline(tree.kind, &refs(children));
+        }
+
+        let open = self.child(open);
+        let mut items = vec![Doc::SoftLine];
+        let inner = &children[1..children.len() - 1];
+        for (i, child) in inner.iter().enumerate() {
+            let is_comma =
+                matches!(&child.value, Child::Token(token) if token.kind == TokenKind::Comma);
+            items.push(self.child(child));
+            if is_comma && i + 1 < inner.len() {
+                items.push(Doc::Line);
+            }
+        }
+        let items = Doc::concat(items).nest();
+        let close = self.child(close);
+
+        Doc::concat([open, items, Doc::SoftLine, close]).group()
+    }
+
+    /// The assignments, like `x = value`. If the value doesn't fit in the line, it's placed in
+    /// the next line.
+    fn assign(&mut self, tree: &'a Tree) -> Doc {
+        let children = &tree.children;
+        let equal = children.iter().position(|child| {
+            matches!(&child.value, Child::Token(token) if token.kind == TokenKind::EqualSymbol)
+        });
+
+        let Some(equal) = equal else {
+            return self.inline(tree.kind, &refs(children));
+        };
+
+        let (head, value) = children.split_at(equal + 1);
+        let has_block = value.iter().any(|child| {
+            let mut tokens = vec![];
+            collect_child_tokens(&child.value, &mut tokens);
+            tokens
+                .iter()
+                .any(|token| token.kind == TokenKind::LeftBrace)
+        });
+
+        // The arrays are broken between their items instead.
+        let is_array = value
+            .iter()
+            .find_map(|child| first_token(&child.value))
+            .is_some_and(|token| token.kind == TokenKind::LeftBracket);
+
+        if has_block || is_array || !value.iter().any(has_tokens) {
+            return self.inline(tree.kind, &refs(children));
+        }
+
+        let head = self.inline(tree.kind, &refs(head));
+        let value = self.inline(tree.kind, &refs(value));
+
+        Doc::concat([head, Doc::concat([Doc::Line, value]).nest()]).group()
+    }
+
+    /// The items of the block between the braces, like the statements or the variants, in their
+    /// own lines.
+    fn block(
+        &mut self,
+        open: &'a Spanned<Child>,
+        inner: &[&'a Spanned<Child>],
+        close: &'a Spanned<Child>,
+    ) -> Doc {
+        let open = self.child(open);
+
+        let mut items = vec![];
+        let mut current: Option<Doc> = None;
+        for child in inner.iter().filter(|child| has_tokens(child)) {
+            match &child.value {
+                Child::Tree(tree) => {
+                    items.extend(current.take());
+                    current = Some(self.tree(tree));
+                }
+                // The commas and semicolons are kept at the end of the items.
+                Child::Token(token) => {
+                    let token = self.token(token);
+                    current = Some(match current.take() {
+                        Some(item) => item.append(token),
+                        None => token,
+                    });
+                }
+            }
+        }
+        items.extend(current);
+
+        let Child::Token(close) = &close.value else {
+            unreachable!("the block should be closed by a brace")
+        };
+
+        let leading = self.leading();
+        if items.is_empty() && leading.is_nil() {
+            return Doc::concat([open, self.text(close)]);
+        }
+
+        let items = items
+            .into_iter()
+            .flat_map(|item| [Doc::HardLine, item])
+            .chain([leading])
+            .collect::<Vec<_>>();
+
+        Doc::concat([
+            open,
+            Doc::concat(items).nest(),
+            Doc::HardLine,
+            self.text(close),
+        ])
+    }
+
+    /// The token with its comments.
+    fn token(&mut self, token: &'a Token) -> Doc {
+        let leading = self.leading();
+
+        Doc::concat([leading, self.text(token)])
+    }
+
+    /// The comments and blank lines before the next token.
+    fn leading(&mut self) -> Doc {
+        let index = self.index;
+        if matches!(self.bounds, Some((first, _)) if first == index) {
+            return Doc::Nil;
+        }
+        if self.printed_leading.replace(index) == Some(index) {
+            return Doc::Nil;
+        }
+        let Some(token) = self.tokens.get(index) else {
+            return Doc::Nil;
+        };
+
+        let trivia = Trivia::parse(&token.full_text.before_whitespace, index == 0);
+
+        // The blank lines right after an opening delimiter, or before a closing one are removed.
+        let after_open = index > 0 && is_open(self.tokens[index - 1].kind);
+        let before_close = is_close(token.kind);
+
+        let mut docs = vec![];
+        for (i, comment) in trivia.leading.iter().enumerate() {
+            let blank_line = comment.blank_line && !(i == 0 && after_open);
+            docs.push(if blank_line {
+                Doc::BlankLine
+            } else {
+                Doc::HardLine
+            });
+            docs.push(Doc::text(comment.text));
+            docs.push(Doc::HardLine);
+        }
+
+        let first = trivia.leading.is_empty();
+        if trivia.blank_line && !before_close && !(first && after_open) {
+            docs.push(Doc::BlankLine);
+        }
+
+        Doc::concat(docs)
+    }
+
+    /// The text of the next token, and the comment at the end of its line.
+    fn text(&mut self, token: &'a Token) -> Doc {
+        let index = self.index;
+        debug_assert!(
+            std::ptr::eq(self.tokens[index], token),
+            "the tokens should be printed in order"
+        );
+        self.index += 1;
+
+        let text = Doc::text(&token.text);
+        if matches!(self.bounds, Some((_, last)) if last == index) {
+            return text;
+        }
+
+        match Trivia::parse(&token.full_text.after_whitespace, false).trailing {
+            Some(comment) => {
+                Doc::concat([text, Doc::space(), Doc::text(comment.text), Doc::HardLine])
+            }
+            None => text,
+        }
+    }
+}
+
+/// The separator between two children of a tree.
+fn separator(kind: TreeKind, prev: &Child, next: &Child) -> Doc {
+    use TokenKind::*;
+
+    let (Some(left), Some(right)) = (last_token(prev), first_token(next)) else {
+        return Doc::Nil;
+    };
+
+    let space = match () {
+        _ if matches!(left.kind, LeftParen | LeftBracket | HashSymbol | HelpSymbol) => false,
+        _ if kind == TreeKind::ExprLam && left.kind == Symbol && left.text == "\\" => false,
+        _ if matches!(right.kind, RightParen | RightBracket | Comma | Semi) => false,
+        _ if right.kind == Colon => matches!(
+            kind,
+            TreeKind::DeclSignature
+                | TreeKind::ClassField
+                | TreeKind::VariantType
+                | TreeKind::DeclEnum
+        ),
+        // The methods and the variants are called like `show(self)`, and `Some(a)`.
+        _ if matches!(
+            kind,
+            TreeKind::ClassMethod | TreeKind::TraitDefault | TreeKind::VariantConstructor
+        ) =>
+        {
+            !(is_tree(prev, TreeKind::QualifiedPathTree)
+                && (is_tree(next, TreeKind::Param)
+                    || is_tree(next, TreeKind::SelfParam)
+                    || right.kind == LeftParen))
+        }
+        _ => true,
+    };
+
+    if space || glues(left, right) {
+        Doc::space()
+    } else {
+        Doc::Nil
+    }
+}
+
+/// If the tokens would be lexed as a single token, without a space between them.
+fn glues(left: &Token, right: &Token) -> bool {
+    let (Some(last), Some(first)) = (left.text.chars().last(), right.text.chars().next()) else {
+        return false;
+    };
+
+    let is_symbol = |c: char| SYMBOLS.iter().any(|symbol| symbol.contains(c));
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+
+    (is_symbol(last) && is_symbol(first)) || (is_ident(last) && is_ident(first))
+}
+
+/// Splits the operands and the operators of the tree, the first segment is the first operand,
+/// and the others are an operator followed by its operand. The right-associative chains, like
+/// `a -> (b -> c)`, are flattened.
+fn split_chain<'a>(tree: &'a Tree, segments: &mut Vec<Vec<&'a Spanned<Child>>>) {
+    let operator = match tree.kind {
+        TreeKind::ExprBinary => TokenKind::Symbol,
+        TreeKind::ExprQual => TokenKind::DoubleArrow,
+        _ => TokenKind::RightArrow,
+    };
+
+    let mut current = vec![];
+    for child in &tree.children {
+        if is_token(&child.value, operator) && !current.is_empty() {
+            segments.push(std::mem::take(&mut current));
+        }
+        current.push(child);
+    }
+
+    match current[..] {
+        [operator, operand] if !segments.is_empty() && is_tree(&operand.value, tree.kind) => {
+            let Child::Tree(operand) = &operand.value else {
+                unreachable!()
+            };
+
+            let mut nested = vec![];
+            split_chain(operand, &mut nested);
+
+            let mut nested = nested.into_iter();
+            let mut first = vec![operator];
+            first.extend(nested.next().unwrap_or_default());
+            segments.push(first);
+            segments.extend(nested);
+        }
+        _ => segments.push(current),
+    }
+}
+
+fn matching_brace(children: &[&Spanned<Child>], open: usize) -> Option<usize> {
+    if !is_token(&children[open].value, TokenKind::LeftBrace) {
+        return None;
+    }
+
+    let mut depth = 0;
+    for (i, child) in children.iter().enumerate().skip(open) {
+        if is_token(&child.value, TokenKind::LeftBrace) {
+            depth += 1;
+        } else if is_token(&child.value, TokenKind::RightBrace) {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+
+    None
+}
+
+fn refs(children: &[Spanned<Child>]) -> Vec<&Spanned<Child>> {
+    children.iter().collect()
+}
+
+fn is_open(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::LeftBrace | TokenKind::LeftParen | TokenKind::LeftBracket
+    )
+}
+
+fn is_close(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::RightBrace | TokenKind::RightParen | TokenKind::RightBracket
+    )
+}
+
+fn is_token(child: &Child, kind: TokenKind) -> bool {
+    matches!(child, Child::Token(token) if token.kind == kind)
+}
+
+fn is_tree(child: &Child, kind: TreeKind) -> bool {
+    matches!(child, Child::Tree(tree) if tree.kind == kind)
+}
+
+fn has_tokens(child: &Spanned<Child>) -> bool {
+    count_tokens(&child.value) > 0
+}
+
+fn first_token(child: &Child) -> Option<&Token> {
+    match child {
+        Child::Token(token) => Some(token),
+        Child::Tree(tree) => tree
+            .children
+            .iter()
+            .find_map(|child| first_token(&child.value)),
+    }
+}
+
+fn last_token(child: &Child) -> Option<&Token> {
+    match child {
+        Child::Token(token) => Some(token),
+        Child::Tree(tree) => tree
+            .children
+            .iter()
+            .rev()
+            .find_map(|child| last_token(&child.value)),
+    }
+}
+
+fn collect_tokens<'a>(tree: &'a Tree, tokens: &mut Vec<&'a Token>) {
+    for child in &tree.children {
+        collect_child_tokens(&child.value, tokens);
+    }
+}
+
+fn collect_child_tokens<'a>(child: &'a Child, tokens: &mut Vec<&'a Token>) {
+    match child {
+        // The EOF token of the files without tokens has their comments, that are printed as
+        // the comments at the end of the file.
+        Child::Token(token) if token.kind == TokenKind::Eof => {}
+        Child::Token(token) => tokens.push(token),
+        Child::Tree(tree) => collect_tokens(tree, tokens),
+    }
+}
+
+/// The number of tokens of the child.
+pub fn count_tokens(child: &Child) -> usize {
+    let mut tokens = vec![];
+    collect_child_tokens(child, &mut tokens);
+    tokens.len()
+}
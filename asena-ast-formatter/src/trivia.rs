@@ -0,0 +1,61 @@
+//! The comments and blank lines between the tokens. The lexer keeps them in the trivia of the
+//! tokens: the end of the line of a token is its trailing trivia, and the lines before a token
+//! are its leading trivia.
+
+/// A `//` comment, without the line break.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Comment<'a> {
+    pub text: &'a str,
+
+    /// If there is a blank line before the comment.
+    pub blank_line: bool,
+}
+
+/// The trivia between two tokens.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Trivia<'a> {
+    /// The comment at the end of the line of the previous token.
+    pub trailing: Option<Comment<'a>>,
+
+    /// The comments in their own lines, before the token.
+    pub leading: Vec<Comment<'a>>,
+
+    /// If there is a blank line right before the token.
+    pub blank_line: bool,
+}
+
+impl<'a> Trivia<'a> {
+    /// Splits the trivia of a token, the comment before the first line break is a trailing
+    /// comment. If it's the start of the file, there is no previous token, so all the comments
+    /// are in their own lines.
+    pub fn parse(whitespace: &'a str, start_of_file: bool) -> Self {
+        let mut trivia = Trivia::default();
+        let mut newlines = if start_of_file { 1 } else { 0 };
+        let mut rest = whitespace;
+
+        loop {
+            let trimmed = rest.trim_start();
+            newlines += rest[..rest.len() - trimmed.len()].matches('\n').count();
+            rest = trimmed;
+
+            if !rest.starts_with("//") {
+                trivia.blank_line = newlines >= 2;
+                return trivia;
+            }
+
+            let end = rest.find('\n').unwrap_or(rest.len());
+            let comment = Comment {
+                text: rest[..end].trim_end(),
+                blank_line: newlines >= 2,
+            };
+            if newlines == 0 {
+                trivia.trailing = Some(comment);
+            } else {
+                trivia.leading.push(comment);
+            }
+
+            newlines = 0;
+            rest = &rest[end..];
+        }
+    }
+}
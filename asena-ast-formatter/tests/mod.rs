@@ -0,0 +1,139 @@
+use asena_ast_formatter::{format, format_range, FormatError, FormatOptions};
+
+/// Formats the source with the default options, and checks that formatting it again doesn't
+/// change it.
+fn formatted(source: &str) -> String {
+    let options = FormatOptions::default();
+    let formatted = format(source, &options).unwrap();
+    assert_eq!(format(&formatted, &options).unwrap(), formatted);
+
+    formatted
+}
+
+#[test]
+fn it_formats_the_uses() {
+    assert_eq!(
+        formatted("use   IO\nuse Data.List\n"),
+        "use IO\nuse Data.List\n"
+    );
+}
+
+#[test]
+fn it_formats_the_signatures_and_the_assignments() {
+    assert_eq!(
+        formatted("id : a->a\nid x =   x\n\n\n\nconst:a -> b -> a\nconst   x _ =x"),
+        "id : a -> a\nid x = x\n\nconst : a -> b -> a\nconst x _ = x\n"
+    );
+}
+
+#[test]
+fn it_formats_the_classes() {
+    let source = "class   Person {\n  name :String,\n  age:Nat,\n  \
+                  fun greet(self): String { \"hi\" }\n}\n";
+
+    assert_eq!(
+        formatted(source),
+        "class Person {\n  name : String,\n  age : Nat,\n  fun greet(self): String {\n    \
+         \"hi\"\n  }\n}\n"
+    );
+}
+
+#[test]
+fn it_formats_the_traits() {
+    assert_eq!(
+        formatted("trait Show (a:Set) {\n    show : a -> String,\n}\n"),
+        "trait Show (a: Set) {\n  show : a -> String,\n}\n"
+    );
+}
+
+#[test]
+fn it_formats_the_instances() {
+    assert_eq!(
+        formatted("instance Show Nat {\n      show n = \"nat\"\n}\n"),
+        "instance Show Nat {\n  show n = \"nat\"\n}\n"
+    );
+}
+
+#[test]
+fn it_formats_the_enums() {
+    let source = "enum Maybe (a:Set) {\n  Nothing : Maybe a,\n    Just : (value: a) -> Maybe a,\n}";
+
+    assert_eq!(
+        formatted(source),
+        "enum Maybe (a: Set) {\n  Nothing : Maybe a,\n  Just : (value: a) -> Maybe a,\n}\n"
+    );
+}
+
+#[test]
+fn it_keeps_the_comments() {
+    let source = "// leading\nid : a -> a   // trailing\n\n\n// between\nid x = x\n// end\n";
+
+    assert_eq!(
+        formatted(source),
+        "// leading\nid : a -> a // trailing\n\n// between\nid x = x\n// end\n"
+    );
+}
+
+#[test]
+fn it_formats_the_sources_without_declarations() {
+    assert_eq!(formatted(""), "");
+    assert_eq!(formatted("\n\n"), "");
+    assert_eq!(formatted("// only a comment"), "// only a comment\n");
+    assert_eq!(formatted("\n// a\n\n\n// b\n\n"), "// a\n\n// b\n");
+}
+
+#[test]
+fn it_is_idempotent_over_the_standard_library() {
+    let modules = [
+        include_str!("../../asena-ast-db/std/Prelude.ase"),
+        include_str!("../../asena-ast-db/std/Bool.ase"),
+        include_str!("../../asena-ast-db/std/Nat.ase"),
+        include_str!("../../asena-ast-db/std/Maybe.ase"),
+        include_str!("../../asena-ast-db/std/List.ase"),
+        include_str!("../../asena-ast-db/std/IO.ase"),
+    ];
+
+    for source in modules {
+        formatted(source);
+    }
+}
+
+#[test]
+fn it_formats_the_declarations_in_the_range() {
+    let options = FormatOptions::default();
+    let source = "id : a -> a\nid x =   x\n\nconst : a -> b -> a\nconst   x _ =   x\n// end\n";
+    let assign = source.find("const   x").unwrap();
+
+    let (range, formatted) = format_range(source, assign + 2..assign + 4, &options)
+        .unwrap()
+        .unwrap();
+    assert_eq!(&source[range], "const   x _ =   x");
+    assert_eq!(formatted, "const x _ = x");
+
+    // The signatures and the assignments are different declarations.
+    let (range, formatted) = format_range(source, 12..source.len(), &options)
+        .unwrap()
+        .unwrap();
+    assert_eq!(range, 12..assign + "const   x _ =   x".len());
+    assert_eq!(formatted, "id x = x\n\nconst : a -> b -> a\nconst x _ = x");
+
+    let comment = source.find("// end").unwrap();
+    assert_eq!(
+        format_range(source, comment..comment + 2, &options),
+        Ok(None)
+    );
+}
+
+#[test]
+fn it_rejects_the_invalid_sources() {
+    let options = FormatOptions::default();
+
+    assert!(matches!(
+        format("id x = (x\n", &options),
+        Err(FormatError::SyntaxError(..))
+    ));
+    assert_eq!(
+        format_range("id x = (x\n", 0..0, &options),
+        format("id x = (x\n", &options).map(|_| None)
+    );
+}
@@ -78,10 +78,11 @@ fn hir_file(db: &dyn AstLowerrer, file: AstParam<AsenaFile>) -> InternalAsenaFil
     }
 
     for (span, group) in signatures.values().cloned() {
+        let docs = group.docs.clone();
         let top_level = db.intern_top_level(HirTopLevelData {
             kind: HirTopLevelKind::from(group),
             attributes: vec![],
-            docs: vec![],
+            docs,
             span,
         });
 
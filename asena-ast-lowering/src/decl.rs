@@ -1,13 +1,15 @@
-use asena_ast::{traits::global_decl::GlobalDecl, GlobalName, Method, Parameter, Typed};
+use asena_ast::{
+    traits::global_decl::GlobalDecl, GlobalName, Method, Multiplicity, Parameter, Typed,
+};
 use asena_ast_db::package::HasDiagnostic;
 use asena_hir::top_level::{
-    data::{HirDeclaration, HirParameterData, HirParameterKind, HirSignature},
+    data::{HirDeclaration, HirMultiplicity, HirParameterData, HirParameterKind, HirSignature},
     HirBindingGroup,
 };
 use asena_report::WithError;
 use im::hashset;
 
-use crate::{db::AstLowerrer, error::AstLoweringError::*};
+use crate::{db::AstLowerrer, error::AstLoweringError::*, make_docs};
 
 pub mod class;
 pub mod r#enum;
@@ -18,12 +20,22 @@ pub fn compute_parameters(db: &dyn AstLowerrer, decl: &impl GlobalDecl) -> Vec<H
     let mut parameters = Vec::new();
     for (name, parameter) in Parameter::compute_parameters(decl.parameters()) {
         let name = db.intern_name(name.to_string());
+        let multiplicity = match parameter.multiplicity() {
+            Some(Multiplicity::Zero) => HirMultiplicity::Zero,
+            Some(Multiplicity::One) => HirMultiplicity::One,
+            Some(Multiplicity::Many) => HirMultiplicity::Many,
+            None => {
+                parameter.clone().fail(InvalidMultiplicityError).push(db);
+                HirMultiplicity::Many
+            }
+        };
         let data = HirParameterData {
             name,
             parameter_type: match parameter.parameter_type() {
                 Typed::Infer => None,
                 Typed::Explicit(expr) => Some(db.hir_type(expr.into())),
             },
+            multiplicity,
         };
 
         match true {
@@ -67,6 +79,7 @@ pub fn compute_methods(db: &dyn AstLowerrer, methods: Vec<Method>) -> im::HashSe
                 patterns: vec![],
                 value: db.hir_block(method.body().into()),
             }],
+            docs: make_docs(&method),
         };
         groups.insert(group);
     }
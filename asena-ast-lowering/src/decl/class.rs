@@ -9,7 +9,7 @@ use asena_leaf::ast::AstParam;
 use asena_report::WithError;
 use im::HashMap;
 
-use crate::{db::AstLowerrer, error::AstLoweringError::*, make_location};
+use crate::{db::AstLowerrer, error::AstLoweringError::*, make_docs, make_location};
 
 use super::{compute_methods, compute_parameters};
 
@@ -29,7 +29,7 @@ pub fn lower_class(db: &dyn AstLowerrer, decl: AstParam<Class>) -> HirTopLevel {
     db.intern_top_level(HirTopLevelData {
         kind: kind.into(),
         attributes: vec![],
-        docs: vec![],
+        docs: make_docs(&decl.data),
         span,
     })
 }
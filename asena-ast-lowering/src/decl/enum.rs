@@ -14,7 +14,7 @@ use itertools::Itertools;
 
 use crate::db::AstLowerrer;
 use crate::error::AstLoweringError::*;
-use crate::make_location;
+use crate::{make_docs, make_location};
 
 use super::{compute_methods, compute_parameters};
 
@@ -37,7 +37,7 @@ pub fn lower_enum(db: &dyn AstLowerrer, decl: AstParam<Enum>) -> HirTopLevel {
     db.intern_top_level(HirTopLevelData {
         kind: kind.into(),
         attributes: vec![],
-        docs: vec![],
+        docs: make_docs(&decl.data),
         span,
     })
 }
@@ -50,6 +50,7 @@ pub fn lower_variants(db: &dyn AstLowerrer, decl: &Enum) -> HashMap<Name, HirVar
 
     for variant in variants {
         let name = db.intern_name(variant.name().to_fn_id().to_string());
+        let docs = make_docs(&variant);
         let variant_type = match variant {
             Variant::Error => HirType::error(db),
             Variant::TypeVariant(type_variant) => match type_variant.value() {
@@ -76,7 +77,12 @@ pub fn lower_variants(db: &dyn AstLowerrer, decl: &Enum) -> HashMap<Name, HirVar
             }
         };
 
-        map.insert(name, HirVariant { name, variant_type });
+        let variant = HirVariant {
+            name,
+            variant_type,
+            docs,
+        };
+        map.insert(name, variant);
     }
 
     map
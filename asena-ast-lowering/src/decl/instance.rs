@@ -5,7 +5,7 @@ use asena_hir::{
 };
 use asena_leaf::ast::AstParam;
 
-use crate::{db::AstLowerrer, make_location};
+use crate::{db::AstLowerrer, make_docs, make_location};
 
 use super::{compute_methods, compute_parameters};
 
@@ -23,7 +23,7 @@ pub fn lower_instance(db: &dyn AstLowerrer, decl: AstParam<Instance>) -> HirTopL
     db.intern_top_level(HirTopLevelData {
         kind: kind.into(),
         attributes: vec![],
-        docs: vec![],
+        docs: make_docs(&decl.data),
         span,
     })
 }
@@ -9,9 +9,9 @@ use asena_leaf::ast::AstParam;
 use asena_report::WithError;
 use im::{hashset, HashMap};
 
-use crate::make_location;
 use crate::pattern::build_patterns;
 use crate::{db::AstLowerrer, error::AstLoweringError::*};
+use crate::{make_docs, make_location};
 
 use super::compute_parameters;
 
@@ -34,7 +34,7 @@ pub fn lower_trait(db: &dyn AstLowerrer, decl: AstParam<Trait>) -> HirTopLevel {
     db.intern_top_level(HirTopLevelData {
         kind: kind.into(),
         attributes: vec![],
-        docs: vec![],
+        docs: make_docs(&decl.data),
         span,
     })
 }
@@ -63,6 +63,7 @@ fn compute_abstract_fields(db: &dyn AstLowerrer, fields: Vec<Field>) -> Methods
                 return_type,
             },
             declarations: hashset![],
+            docs: make_docs(&field),
         };
 
         methods.insert(name, method);
@@ -82,8 +83,14 @@ fn defaults(db: &dyn AstLowerrer, mut methods: Methods, defaults: Vec<DefaultMet
                 return_type: None,
             },
             declarations: hashset![],
+            docs: vec![],
         });
 
+        // The default methods are documented only if their fields aren't.
+        if group.docs.is_empty() {
+            group.docs = make_docs(&method);
+        }
+
         group.declarations.insert(HirDeclaration {
             patterns: build_patterns(db, parameters),
             value: db.hir_block(method.body().into()),
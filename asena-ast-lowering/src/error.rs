@@ -36,6 +36,9 @@ pub enum AstLoweringError {
 
     #[error("type exporessions aren't supported yet")]
     UnsupportedTypeExprsError,
+
+    #[error("invalid multiplicity, expected `0`, `1` or `ω`")]
+    InvalidMultiplicityError,
 }
 
 impl AstLoweringError {
@@ -49,7 +52,8 @@ impl AstLoweringError {
 
 impl InternalError for AstLoweringError {
     fn code(&self) -> u16 {
-        self.discriminant() as u16
+        // The lowering errors are `E04xx`, they are listed in `docs/diagnostics.md`.
+        400 + self.discriminant() as u16
     }
 
     fn kind(&self) -> DiagnosticKind {
@@ -4,7 +4,7 @@ use asena_ast::*;
 use asena_ast_db::package::HasDiagnostic;
 use asena_hir::expr::data::HirBranch;
 use asena_hir::expr::{data::HirCallee, *};
-use asena_hir::top_level::data::{HirDeclaration, HirSignature};
+use asena_hir::top_level::data::{HirDeclaration, HirDoc, HirSignature};
 use asena_hir::top_level::HirBindingGroup;
 use asena_hir::{literal::*, Name};
 use asena_hir::{value::*, HirLoc};
@@ -64,6 +64,7 @@ pub(crate) fn make_signature(db: &dyn AstLowerrer, signatures: &mut Signatures,
             return_type,
         },
         declarations,
+        docs: make_docs(decl),
     };
 
     signatures.insert(name, (span, group));
@@ -84,6 +85,11 @@ pub(crate) fn make_assign(db: &dyn AstLowerrer, signatures: &mut Signatures, dec
         .entry(name)
         .or_insert_with(|| (span, new_default_group(name)));
 
+    // The equations are documented only if their signature isn't.
+    if group.docs.is_empty() {
+        group.docs = make_docs(decl);
+    }
+
     group.declarations.insert(HirDeclaration {
         patterns,
         value: db.hir_value(decl.body().into()),
@@ -98,6 +104,7 @@ pub(crate) fn new_default_group(name: Name) -> HirBindingGroup {
             return_type: None,
         },
         declarations: hashset![],
+        docs: vec![],
     }
 }
 
@@ -114,6 +121,14 @@ pub fn make_location(db: &dyn AstLowerrer, node: &impl Located) -> HirLoc {
     }
 }
 
+/// The `///` doc comments of the node, a [HirDoc] for each line.
+pub fn make_docs(node: &impl Documented) -> Vec<HirDoc> {
+    node.docs()
+        .into_iter()
+        .map(|text| HirDoc { text })
+        .collect()
+}
+
 pub fn lower_value(db: &dyn AstLowerrer, value: AstParam<Expr>) -> HirValue {
     let span = make_location(db, &value);
     let mut lowering = ExprLowering::new(db);
@@ -62,6 +62,8 @@ pub fn lower_pattern(db: &dyn AstLowerrer, pattern: AstParam<Pat>) -> HirPattern
     })
 }
 
+/// The patterns of the declaration written in the signature. The implicit parameters aren't
+/// passed in the calls, so they aren't matched.
 pub fn build_patterns(db: &dyn AstLowerrer, parameters: Vec<HirParameterKind>) -> Vec<HirPattern> {
     let mut patterns = Vec::new();
     for parameter in parameters {
@@ -69,7 +71,7 @@ pub fn build_patterns(db: &dyn AstLowerrer, parameters: Vec<HirParameterKind>) -
             HirParameterKind::Error => HirPattern::error(db),
             HirParameterKind::This => HirPattern::this(db),
             HirParameterKind::Explicit(data) => HirPattern::name(db, data.name),
-            HirParameterKind::Implicit(data) => HirPattern::name(db, data.name),
+            HirParameterKind::Implicit(_) => continue,
         };
         patterns.push(kind)
     }
@@ -0,0 +1,128 @@
+//! The calls between the declarations, computed from the resolved references, to answer the call
+//! hierarchy requests. A call is any reference that reads a global inside of a declaration, like
+//! the binding group of a function, or a method.
+
+use asena_ast_db::symbols::{document_symbols, AstSymbol};
+use asena_span::Loc;
+
+use crate::{db::AstResolverDatabase, references::AstReferenceKind, *};
+
+/// A declaration that calls, or that is called by another one, with the locations of the calls,
+/// that are always in the caller.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct AstCall {
+    pub symbol: AstSymbol,
+    pub locations: Vec<Loc>,
+}
+
+/// The declaration of the global, like the binding group of a function, or the method of a
+/// class. The locals, and the globals without a file, have no declaration.
+pub fn declaration_of(db: &dyn AstResolverDatabase, def: DefWithId) -> Option<AstSymbol> {
+    declaration(db, def).map(|(_, symbol)| symbol)
+}
+
+/// The declarations of the files that call the global, with the locations of the calls.
+pub fn incoming_calls(
+    db: &dyn AstResolverDatabase,
+    files: &[VfsFile],
+    def: DefWithId,
+) -> Vec<AstCall> {
+    let mut calls = vec![];
+    for file in files {
+        let symbols = document_symbols(db, *file);
+
+        for reference in db.ast_references(*file).iter() {
+            if reference.def != def || reference.kind != AstReferenceKind::Read {
+                continue;
+            }
+
+            if let Some(caller) = enclosing(&symbols, reference.loc.range.start()).last() {
+                push_call(&mut calls, caller, &reference.loc);
+            }
+        }
+    }
+
+    calls
+}
+
+/// The declarations called by the declaration of the global, with the locations of the calls.
+pub fn outgoing_calls(db: &dyn AstResolverDatabase, def: DefWithId) -> Vec<AstCall> {
+    let Some((file, symbol)) = declaration(db, def) else {
+        return vec![];
+    };
+
+    let symbols = document_symbols(db, file);
+
+    let mut calls = vec![];
+    for reference in db.ast_references(file).iter() {
+        if reference.kind != AstReferenceKind::Read {
+            continue;
+        }
+
+        // The equations of a binding group can be apart, so the declarations between them are
+        // inside of its location, but they aren't part of it.
+        let is_inside = enclosing(&symbols, reference.loc.range.start())
+            .into_iter()
+            .any(|parent| *parent == symbol);
+        if !is_inside {
+            continue;
+        }
+
+        if let Some(callee) = declaration_of(db, reference.def) {
+            push_call(&mut calls, &callee, &reference.loc);
+        }
+    }
+
+    calls
+}
+
+/// The file, and the declaration of the global. The declaration is the innermost one that
+/// contains the name of the global, and that has the same name.
+fn declaration(db: &dyn AstResolverDatabase, def: DefWithId) -> Option<(VfsFile, AstSymbol)> {
+    let data = db.lookup_intern_def(def);
+    let path = data.token.span.file.clone()?;
+    let file = db.vfs_file(db.path_module(path));
+
+    let name = data.name.to_string();
+    let name = name.rsplit('.').next().unwrap_or_default();
+
+    let symbols = document_symbols(db, file);
+    let symbol = enclosing(&symbols, data.token.span.range.start())
+        .into_iter()
+        .rev()
+        .find(|symbol| symbol.name == name)?
+        .clone();
+
+    Some((file, symbol))
+}
+
+/// The symbols that enclose the offset, from the outermost to the innermost one. The smallest
+/// symbol wins when many of them enclose the offset.
+fn enclosing(symbols: &[AstSymbol], offset: usize) -> Vec<&AstSymbol> {
+    let symbol = symbols
+        .iter()
+        .filter(|symbol| {
+            let range = &symbol.loc.range;
+            range.start() <= offset && offset < range.end()
+        })
+        .min_by_key(|symbol| symbol.loc.range.end() - symbol.loc.range.start());
+
+    match symbol {
+        Some(symbol) => {
+            let mut chain = vec![symbol];
+            chain.extend(enclosing(&symbol.children, offset));
+            chain
+        }
+        None => vec![],
+    }
+}
+
+fn push_call(calls: &mut Vec<AstCall>, symbol: &AstSymbol, loc: &Loc) {
+    match calls.iter_mut().find(|call| call.symbol == *symbol) {
+        Some(call) => call.locations.push(loc.clone()),
+        None => calls.push(AstCall {
+            symbol: symbol.clone(),
+            locations: vec![loc.clone()],
+        }),
+    }
+}
@@ -0,0 +1,222 @@
+//! Finds the names that can be written at a position of the file, using the scopes stored in the
+//! resolved tree by the [`crate::scopes::ScopeResolver`].
+
+use std::collections::HashSet;
+
+use asena_ast_db::scope::ScopeRef;
+use asena_leaf::ast::{Located, Node};
+use asena_span::TextRange;
+
+use crate::{db::AstResolverDatabase, *};
+
+/// The keywords that starts a declaration.
+pub const DECL_KEYWORDS: &[&str] = &["use", "enum", "trait", "class", "instance", "default"];
+
+/// The keywords that can be used inside of an expression, or a statement.
+pub const EXPR_KEYWORDS: &[&str] = &[
+    "let", "if", "then", "else", "match", "case", "return", "fun", "in", "self", "true", "false",
+];
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum AstCompletionKind {
+    Local,
+    Function,
+    Constructor,
+    Type,
+    Module,
+    Keyword,
+}
+
+/// A name that can be written at the position.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct AstCompletion {
+    /// The text to insert, without the qualifier already written, like `println` in `IO.pri`.
+    pub label: String,
+
+    /// The full name of the completion, like `IO.println`.
+    pub name: String,
+    pub kind: AstCompletionKind,
+    pub def: Option<DefWithId>,
+}
+
+/// The completions at the `offset` of the file: the locals of the scope at the position, the
+/// globals, the members of the module if there is a qualifier, and the keywords.
+pub fn completions(
+    db: &dyn AstResolverDatabase,
+    file: VfsFile,
+    offset: usize,
+) -> Vec<AstCompletion> {
+    let source = db.source(file);
+    let qualifier = qualifier_at(&source, offset);
+
+    let global_scope = db.global_scope();
+    let global_scope = global_scope.read().unwrap().clone();
+    let scope = scope_at(db, file, offset);
+    let in_expr = scope.is_some() || is_indented(&source, offset);
+    let scope = scope
+        .map(|scope| scope.read().unwrap().clone())
+        .unwrap_or_else(|| global_scope.clone());
+
+    let mut collector = CompletionCollector {
+        qualifier: qualifier.clone(),
+        seen: HashSet::new(),
+        items: vec![],
+    };
+
+    for (name, def) in scope.types.iter() {
+        collector.push(name, AstCompletionKind::Type, Some(*def));
+    }
+
+    for (name, def) in scope.constructors.iter() {
+        collector.push(name, AstCompletionKind::Constructor, Some(*def));
+    }
+
+    for (name, def) in scope.functions.iter() {
+        if scope.constructors.contains_key(name) {
+            continue;
+        }
+
+        let kind = if global_scope.functions.get(name) == Some(def) {
+            AstCompletionKind::Function
+        } else {
+            AstCompletionKind::Local
+        };
+
+        collector.push(name, kind, Some(*def));
+    }
+
+    for name in scope.modules.keys() {
+        collector.push(&FunctionId::new(name), AstCompletionKind::Module, None);
+    }
+
+    if qualifier.is_none() {
+        let keywords = if in_expr {
+            EXPR_KEYWORDS
+        } else {
+            DECL_KEYWORDS
+        };
+
+        collector
+            .items
+            .extend(keywords.iter().map(|keyword| AstCompletion {
+                label: keyword.to_string(),
+                name: keyword.to_string(),
+                kind: AstCompletionKind::Keyword,
+                def: None,
+            }));
+    }
+
+    collector.items
+}
+
+/// The scope of the innermost expression at the `offset`, if the position is in an expression.
+pub fn scope_at(db: &dyn AstResolverDatabase, file: VfsFile, offset: usize) -> Option<ScopeRef> {
+    let ast = db.ast(file);
+    let ast = db.infix_commands(ast.into());
+    let ast = db.ordered_prec(ast.into());
+    let ast = db.ast_resolved_file(ast.into());
+
+    let mut finder = ScopeFinder {
+        offset,
+        scope: None,
+    };
+    finder.listens(ast.declarations());
+
+    finder.scope.map(|(_, scope)| scope)
+}
+
+/// The qualifier of the path being written at the `offset`, like `IO` in `IO.pri`.
+fn qualifier_at(source: &str, offset: usize) -> Option<String> {
+    let text = source.get(..offset)?;
+    let start = text
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_alphanumeric() || matches!(c, '_' | '\'' | '.'))
+        .last()
+        .map(|(index, _)| index)?;
+
+    let path = &text[start..];
+    let (qualifier, _) = path.rsplit_once('.')?;
+
+    Some(qualifier.to_string()).filter(|qualifier| !qualifier.is_empty())
+}
+
+/// If the line of the `offset` is indented, so the position is inside of a declaration.
+fn is_indented(source: &str, offset: usize) -> bool {
+    let Some(text) = source.get(..offset) else {
+        return false;
+    };
+
+    let line = text.rsplit('\n').next().unwrap_or_default();
+    line.starts_with(char::is_whitespace)
+}
+
+struct CompletionCollector {
+    qualifier: Option<String>,
+    seen: HashSet<String>,
+    items: Vec<AstCompletion>,
+}
+
+impl CompletionCollector {
+    /// Pushes the name if it's visible with the qualifier: the members of the module after the
+    /// qualifier, or the unqualified names, and the first segment of the qualified ones.
+    fn push(&mut self, name: &FunctionId, kind: AstCompletionKind, def: Option<DefWithId>) {
+        let full_name = name.to_string();
+        let relative = match self.qualifier {
+            Some(ref qualifier) => match full_name.strip_prefix(&format!("{qualifier}.")) {
+                Some(relative) => relative,
+                None => return,
+            },
+            None => &full_name,
+        };
+
+        let (label, kind, def) = match relative.split_once('.') {
+            Some((module, _)) => (module.to_string(), AstCompletionKind::Module, None),
+            None => (relative.to_string(), kind, def),
+        };
+
+        if label.is_empty() || !self.seen.insert(label.clone()) {
+            return;
+        }
+
+        let name = match (kind, &self.qualifier) {
+            (AstCompletionKind::Module, Some(qualifier)) => format!("{qualifier}.{label}"),
+            (AstCompletionKind::Module, None) => label.clone(),
+            _ => full_name.clone(),
+        };
+
+        self.items.push(AstCompletion {
+            label,
+            name,
+            kind,
+            def,
+        });
+    }
+}
+
+/// Finds the innermost expression at the offset, that has a scope.
+struct ScopeFinder {
+    offset: usize,
+    scope: Option<(usize, ScopeRef)>,
+}
+
+impl AsenaListener for ScopeFinder {
+    fn enter_expr(&mut self, value: Expr) {
+        let range = value.location().range.clone();
+        if !matches!(range, TextRange::Actual(_))
+            || self.offset < range.start()
+            || self.offset > range.end()
+        {
+            return;
+        }
+
+        let len = range.end() - range.start();
+        if self.scope.as_ref().is_some_and(|(best, _)| *best < len) {
+            return;
+        }
+
+        if let Some(scope) = value.unwrap().key(ScopeKey).as_ref() {
+            self.scope = Some((len, scope.clone()));
+        }
+    }
+}
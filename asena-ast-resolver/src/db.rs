@@ -1,12 +1,18 @@
+use std::sync::Arc;
+
 use asena_ast::AsenaFile;
-use asena_ast_db::db::AstDatabase;
+use asena_ast_db::vfs::VfsFile;
 use asena_leaf::ast::{AstParam, Located};
+use asena_prec::PrecDatabase;
 
-use crate::decl::AstResolver;
+use crate::{decl::AstResolver, references::AstReference};
 
 #[salsa::query_group(AstResolverStorage)]
-pub trait AstResolverDatabase: AstDatabase {
+pub trait AstResolverDatabase: PrecDatabase {
     fn ast_resolved_file(&self, file: AstParam<AsenaFile>) -> AsenaFile;
+
+    #[salsa::invoke(crate::references::ast_references)]
+    fn ast_references(&self, file: VfsFile) -> Arc<Vec<AstReference>>;
 }
 
 fn ast_resolved_file(db: &dyn AstResolverDatabase, ast: AstParam<AsenaFile>) -> AsenaFile {
@@ -17,6 +17,12 @@ pub enum ResolutionError {
 
     #[error("could not find the type constructor: `{0}`")]
     UnresolvedConstructorError(FunctionId),
+
+    #[error("`{0}` is not a valid name")]
+    InvalidRenameError(String),
+
+    #[error("can not rename `{0}`, it's defined outside the workspace")]
+    ExternalRenameError(FunctionId),
 }
 
 impl ResolutionError {
@@ -30,7 +36,8 @@ impl ResolutionError {
 
 impl InternalError for ResolutionError {
     fn code(&self) -> u16 {
-        self.discriminant() as u16
+        // The resolution errors are `E03xx`, they are listed in `docs/diagnostics.md`.
+        300 + self.discriminant() as u16
     }
 
     fn kind(&self) -> asena_report::DiagnosticKind {
@@ -4,7 +4,7 @@ use std::sync::Arc;
 
 use asena_ast::*;
 
-use asena_ast_db::scope::VariantResolution;
+use asena_ast_db::scope::{ScopeRef, VariantResolution};
 use asena_ast_db::vfs::*;
 
 use asena_leaf::ast::Lexeme;
@@ -16,9 +16,12 @@ use thiserror::Error;
 
 use crate::error::ResolutionError::*;
 
+pub mod calls;
+pub mod completion;
 pub mod db;
 pub mod decl;
 pub mod error;
+pub mod references;
 pub mod scopes;
 
 #[derive(Default, Clone)]
@@ -54,3 +57,7 @@ ast_key! {
 ast_key! {
     pub struct PatResolutionKey : PatResolution;
 }
+
+ast_key! {
+    pub struct ScopeKey : Option<ScopeRef>;
+}
@@ -0,0 +1,347 @@
+//! Finds the definitions and the references of the names, using the resolutions stored in the
+//! resolved tree, to answer the go-to-definition, find-references and rename requests.
+
+use asena_ast_db::{
+    def::DefData,
+    symbols::{document_symbols, AstSymbol, AstSymbolKind},
+};
+use asena_leaf::{ast::Located, token::kind::TokenKind};
+use asena_lexer::Lexer;
+use asena_span::{Loc, TextRange};
+
+use crate::{db::AstResolverDatabase, error::ResolutionError, *};
+
+/// A name in the source, that refers to a definition.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct AstReference {
+    pub def: DefWithId,
+    pub loc: Loc,
+    pub kind: AstReferenceKind,
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum AstReferenceKind {
+    /// The value of the name is read, like a variable in an expression, or a variant matched by
+    /// a pattern.
+    Read,
+
+    /// The name is bound, like the name of a declaration, or a variable of a pattern.
+    Binding,
+}
+
+/// An edit that replaces the text at `loc` with `text`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct AstEdit {
+    pub loc: Loc,
+    pub text: String,
+}
+
+pub fn ast_references(db: &dyn AstResolverDatabase, file: VfsFile) -> Arc<Vec<AstReference>> {
+    let ast = db.ast(file);
+    let ast = db.infix_commands(ast.into());
+    let ast = db.ordered_prec(ast.into());
+    let ast = db.ast_resolved_file(ast.into());
+
+    let mut collector = ReferenceCollector {
+        db,
+        file,
+        references: vec![],
+    };
+    collector.listens(ast.declarations());
+
+    Arc::new(collector.references)
+}
+
+/// The definition of the name at the `offset` of the file.
+pub fn definition_at(
+    db: &dyn AstResolverDatabase,
+    file: VfsFile,
+    offset: usize,
+) -> Option<DefWithId> {
+    db.ast_references(file)
+        .iter()
+        .filter(|reference| {
+            let range = &reference.loc.range;
+            matches!(range, TextRange::Actual(_))
+                && range.start() <= offset
+                && offset <= range.end()
+        })
+        .min_by_key(|reference| reference.loc.range.end() - reference.loc.range.start())
+        .map(|reference| reference.def)
+}
+
+/// The locations of the definition, and of all its references in the files.
+pub fn find_references(
+    db: &dyn AstResolverDatabase,
+    files: &[VfsFile],
+    def: DefWithId,
+) -> Vec<Loc> {
+    let mut locations = vec![db.lookup_intern_def(def).token.span];
+    for file in files {
+        for reference in db.ast_references(*file).iter() {
+            if reference.def == def && !locations.contains(&reference.loc) {
+                locations.push(reference.loc.clone());
+            }
+        }
+    }
+
+    locations
+}
+
+/// The locations where the definition is read in the files, without the bindings of its name,
+/// like the equations of a function.
+pub fn find_reads(db: &dyn AstResolverDatabase, files: &[VfsFile], def: DefWithId) -> Vec<Loc> {
+    let mut locations = vec![];
+    for file in files {
+        for reference in db.ast_references(*file).iter() {
+            if reference.def == def && reference.kind == AstReferenceKind::Read {
+                locations.push(reference.loc.clone());
+            }
+        }
+    }
+
+    locations
+}
+
+/// The global defined by the top level declaration of the file, with the name at `name`, like
+/// the function of a signature, or an enum.
+pub fn declared_def(db: &dyn AstResolverDatabase, file: VfsFile, name: &Loc) -> Option<DefWithId> {
+    let data = db.lookup_intern_vfs_file(file);
+    let scope = data.read_scope();
+
+    scope
+        .types
+        .values()
+        .chain(scope.functions.values())
+        .copied()
+        .find(|def| db.lookup_intern_def(*def).token.span == *name)
+}
+
+/// The references of the definition in the file, with the definition itself, if it's in the
+/// file, to highlight them in the editor.
+pub fn document_highlights(
+    db: &dyn AstResolverDatabase,
+    file: VfsFile,
+    def: DefWithId,
+) -> Vec<AstReference> {
+    let data = db.lookup_intern_def(def);
+    let path = db.lookup_intern_vfs_file(file).id.path;
+
+    let mut highlights = vec![];
+    if data.token.span.file.as_ref() == Some(&path) {
+        highlights.push(AstReference {
+            def,
+            loc: data.token.span,
+            kind: AstReferenceKind::Binding,
+        });
+    }
+
+    for reference in db.ast_references(file).iter() {
+        let is_new = highlights
+            .iter()
+            .all(|highlight| highlight.loc != reference.loc);
+        if reference.def == def && is_new {
+            highlights.push(reference.clone());
+        }
+    }
+
+    highlights
+}
+
+/// Renames the definition, and all its references in the files, to `new_name`. The definitions
+/// outside the files, like the ones of the standard library, can't be renamed.
+pub fn rename(
+    db: &dyn AstResolverDatabase,
+    files: &[VfsFile],
+    def: DefWithId,
+    new_name: &str,
+) -> Result<Vec<AstEdit>, ResolutionError> {
+    let data = db.lookup_intern_def(def);
+    let file = files.iter().copied().find(|file| {
+        let file = db.lookup_intern_vfs_file(*file);
+        data.token.span.file.as_ref() == Some(&file.id.path)
+    });
+    let Some(file) = file else {
+        return Err(ResolutionError::ExternalRenameError(data.name));
+    };
+
+    if !is_valid_name(db, file, &data, new_name) {
+        return Err(ResolutionError::InvalidRenameError(new_name.into()));
+    }
+
+    let edits = find_references(db, files, def)
+        .into_iter()
+        .map(|loc| AstEdit {
+            loc: last_segment(&data, loc),
+            text: new_name.into(),
+        })
+        .collect();
+
+    Ok(edits)
+}
+
+/// The location of the last segment of the reference, so the qualified references like
+/// `IO.println` keep their qualifiers.
+fn last_segment(data: &DefData, loc: Loc) -> Loc {
+    let name = data.name.to_string();
+    let name = name.rsplit('.').next().unwrap_or_default();
+    let end = loc.range.end();
+    let start = end.saturating_sub(name.len()).max(loc.range.start());
+
+    Loc::new(loc.file.clone(), start, end)
+}
+
+/// If the name is a single identifier, that isn't a keyword, and is capitalized like the kind of
+/// the definition: the types, variants, classes and traits are capitalized, as the capitalized
+/// names are read as constructors, and the functions, fields and locals aren't.
+fn is_valid_name(db: &dyn AstResolverDatabase, file: VfsFile, data: &DefData, name: &str) -> bool {
+    let lexer = Lexer::new(None, name);
+    let is_identifier = match lexer.tokens.as_slice() {
+        [token] => token.kind == TokenKind::Identifier && token.text == name,
+        _ => false,
+    };
+    if !lexer.errors.is_empty() || !is_identifier {
+        return false;
+    }
+
+    let kind = find_symbol(&document_symbols(db, file), &data.token.span).map(|symbol| symbol.kind);
+    let is_capitalized = matches!(
+        kind,
+        Some(AstSymbolKind::Enum)
+            | Some(AstSymbolKind::Variant)
+            | Some(AstSymbolKind::Class)
+            | Some(AstSymbolKind::Trait)
+    );
+
+    name.starts_with(|c: char| c.is_uppercase()) == is_capitalized
+}
+
+/// The symbol, or the child symbol, whose name is at the location.
+fn find_symbol<'a>(symbols: &'a [AstSymbol], selection: &Loc) -> Option<&'a AstSymbol> {
+    symbols.iter().find_map(|symbol| {
+        if symbol.selection == *selection {
+            return Some(symbol);
+        }
+
+        find_symbol(&symbol.children, selection)
+    })
+}
+
+struct ReferenceCollector<'db> {
+    db: &'db dyn AstResolverDatabase,
+    file: VfsFile,
+    references: Vec<AstReference>,
+}
+
+impl ReferenceCollector<'_> {
+    fn push(&mut self, def: DefWithId, loc: Loc, kind: AstReferenceKind) {
+        self.references.push(AstReference { def, loc, kind });
+    }
+
+    /// The equations and the signatures are references to the global function, that is
+    /// defined by the first declaration.
+    fn global(&mut self, name: BindingId) {
+        let fn_id = name.to_fn_id();
+        let def = self
+            .db
+            .lookup_intern_vfs_file(self.file)
+            .read_scope()
+            .functions
+            .get(&fn_id)
+            .copied()
+            .or_else(|| {
+                let scope = self.db.global_scope();
+                let scope = scope.read().unwrap();
+                scope.functions.get(&fn_id).copied()
+            });
+
+        if let Some(def) = def {
+            self.push(def, name.location().into_owned(), AstReferenceKind::Binding);
+        }
+    }
+}
+
+impl AsenaListener for ReferenceCollector<'_> {
+    fn enter_signature(&mut self, value: Signature) {
+        self.global(value.name());
+    }
+
+    fn enter_assign(&mut self, value: Assign) {
+        self.global(value.name());
+    }
+
+    fn enter_local_expr(&mut self, value: LocalExpr) {
+        let loc = value.location().into_owned();
+
+        if let ExprResolution::Resolved(def) = &*value.key(ExprResolutionKey) {
+            self.push(*def, loc.clone(), AstReferenceKind::Read);
+        }
+
+        if let TypeResolution::Resolved(def) = &*value.key(TypeResolutionKey) {
+            self.push(*def, loc, AstReferenceKind::Read);
+        }
+    }
+
+    fn enter_global_pat(&mut self, value: GlobalPat) {
+        let loc = value.name().location().into_owned();
+
+        match &*value.key(PatResolutionKey) {
+            PatResolution::Unresolved => {}
+            PatResolution::Variant(def) => self.push(*def, loc, AstReferenceKind::Read),
+            PatResolution::LocalBinding(name) => {
+                let location = name.location().into_owned();
+                let def = DefWithId::new(self.db, *name.clone(), location);
+
+                self.push(def, loc, AstReferenceKind::Binding);
+            }
+        }
+    }
+
+    fn enter_constructor_pat(&mut self, value: ConstructorPat) {
+        if let PatResolution::Variant(def) = &*value.key(PatResolutionKey) {
+            let loc = value.name().location().into_owned();
+            self.push(*def, loc, AstReferenceKind::Read);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use asena_ast_db::{db::AstDatabaseStorage, testing::load_main};
+    use asena_prec::db::PrecStorage;
+
+    use super::*;
+    use crate::db::AstResolverStorage;
+
+    #[salsa::database(PrecStorage, AstDatabaseStorage, AstResolverStorage)]
+    struct TestDatabase {
+        storage: salsa::Storage<TestDatabase>,
+    }
+
+    impl salsa::Database for TestDatabase {}
+
+    #[test]
+    fn rename_checks_the_new_names() {
+        let source = "enum Color {\n  Red,\n}\n\nfavorite : Color\nfavorite = Red\n";
+        let mut db = TestDatabase {
+            storage: Default::default(),
+        };
+        let (_, file) = load_main(&mut db, source);
+
+        let renames = |name: &str, new_name: &str| {
+            let offset = source.rfind(name).unwrap();
+            let def = definition_at(&db, file, offset).unwrap();
+
+            rename(&db, &[file], def, new_name).is_ok()
+        };
+
+        // The variants are capitalized, the functions aren't, and the keywords are not names.
+        assert!(renames("Red", "Blue"));
+        assert!(!renames("Red", "blue"));
+        assert!(renames("Red", "Match"));
+        assert!(renames("favorite", "best"));
+        assert!(!renames("favorite", "Best"));
+        assert!(!renames("favorite", "match"));
+        assert!(!renames("favorite", "two words"));
+    }
+}
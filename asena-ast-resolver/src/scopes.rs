@@ -1,7 +1,8 @@
 use crate::{decl::AstResolver, *};
 use asena_ast_db::{def::Def, package::HasDiagnostic, scope::ScopeRef};
-use asena_leaf::ast::Located;
-use asena_report::WithError;
+use asena_leaf::ast::{Located, Node};
+use asena_report::{quickfix, Insert, Quickfix, WithError};
+use asena_span::Loc;
 
 pub enum Level {
     Type,
@@ -51,6 +52,56 @@ impl<'db, 'ctx> ScopeResolver<'db, 'ctx> {
             .cloned()
             .unwrap_or_else(|| self.owner.db.global_scope())
     }
+
+    /// Stores a snapshot of the current scope in the expression, so the names visible at a
+    /// position can be found later, like by the code completion.
+    pub fn attach_scope(&mut self, value: Expr) {
+        let scope = self.last_scope().read().unwrap().fork();
+
+        value.unwrap().dynamic(ScopeKey, Some(scope));
+    }
+
+    /// Suggests to import the modules that defines the unresolved `name`, like `use IO` for
+    /// `println`, if they aren't imported by the file yet.
+    pub fn missing_imports(&self, name: &FunctionId) -> Vec<Quickfix> {
+        let db = self.owner.db;
+        let file = self.owner.file;
+        let name = name.to_string();
+
+        let imported = db
+            .ast(file)
+            .declarations()
+            .into_iter()
+            .filter_map(|decl| match decl {
+                Decl::Use(value) => Some(value.to_fn_id().to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let global_scope = db.global_scope();
+        let global_scope = global_scope.read().unwrap();
+        let path = db.lookup_intern_vfs_file(file).id.path;
+
+        global_scope
+            .functions
+            .keys()
+            .filter_map(|global| {
+                let global = global.to_string();
+                let (module, member) = global.rsplit_once('.')?;
+
+                Some(module.to_string()).filter(|module| {
+                    member == name
+                        && global_scope.modules.contains_key(module)
+                        && !imported.contains(module)
+                })
+            })
+            .flat_map(|module| {
+                let loc = Loc::new(path.clone(), 0, 0);
+
+                quickfix!(before, loc, [Insert(format!("use {module}\n"))])
+            })
+            .collect()
+    }
 }
 
 impl AsenaListener for ScopeResolver<'_, '_> {
@@ -67,6 +118,7 @@ impl AsenaListener for ScopeResolver<'_, '_> {
         }
 
         self.frames.push(scope);
+        self.attach_scope(pi.into());
     }
 
     fn exit_pi(&mut self, _: asena_ast::Pi) {
@@ -82,9 +134,10 @@ impl AsenaListener for ScopeResolver<'_, '_> {
         self.frames.pop();
     }
 
-    fn enter_lam(&mut self, _: Lam) {
+    fn enter_lam(&mut self, lam: Lam) {
         let scope = self.last_scope().read().unwrap().fork();
         self.frames.push(scope);
+        self.attach_scope(lam.into());
     }
 
     fn exit_lam(&mut self, _: Lam) {
@@ -100,6 +153,10 @@ impl AsenaListener for ScopeResolver<'_, '_> {
     }
     // <<< Enter/Exit scope abstractions
 
+    fn enter_expr(&mut self, value: Expr) {
+        self.attach_scope(value);
+    }
+
     /// Just bridges to ast resolver, which will search, and report if it's bound.
     fn enter_qualified_path(&mut self, value: asena_ast::QualifiedPath) {
         self.owner.visit_qualified_path(value);
@@ -115,6 +172,8 @@ impl AsenaListener for ScopeResolver<'_, '_> {
     }
 
     fn enter_local_expr(&mut self, value: LocalExpr) {
+        self.attach_scope(value.clone().into());
+
         let scope = self.last_scope();
         let scope = scope.read().unwrap();
         match self.level {
@@ -136,9 +195,12 @@ impl AsenaListener for ScopeResolver<'_, '_> {
                     value.dynamic(ExprResolutionKey, ExprResolution::Resolved(resolved));
                 }
                 None => {
+                    let quickfixes = self.missing_imports(&value.to_fn_id());
+
                     value
                         .segments()
                         .fail(UnresolvedNameError(value.to_fn_id()))
+                        .with_quickfixes(quickfixes)
                         .push(self.owner.db);
                 }
             },
@@ -52,6 +52,7 @@ pub use parameter::*;
 pub use pat::*;
 pub use stmt::*;
 pub use traits::binary::*;
+pub use traits::documented::*;
 pub use traits::function::*;
 pub use visitor::*;
 
@@ -70,6 +71,7 @@ pub mod visitor;
 
 pub mod traits {
     pub mod binary;
+    pub mod documented;
     pub mod function;
     pub mod global_decl;
     pub mod has_name;
@@ -4,17 +4,19 @@ use asena_leaf::ast::{GreenTree, Leaf, Lexeme, Node};
 use asena_leaf::node::TreeKind::*;
 use asena_leaf::token::kind::TokenKind;
 use if_chain::if_chain;
-use im::HashMap;
 
 use crate::*;
 
 /// A function parameter, or a generic parameter. It can be explicit, or implicit, like a generic,
 /// or either a `self` parameter.
 ///
+/// The explicit parameters can have a [Multiplicity], that is how many times the value can be
+/// used at runtime.
+///
 /// # Examples
 ///
 /// ```asena
-/// foo (a : Int) (b : Int) : Int
+/// foo (a : Int) (1 b : Handle) (0 n : Nat) : Int
 /// ```
 #[derive(Default, Node, Located, Clone, Hash, PartialEq, Eq)]
 pub struct Parameter(GreenTree);
@@ -47,11 +49,44 @@ impl Parameter {
         !self.token(TokenKind::SelfKeyword).is_error()
     }
 
-    pub fn compute_parameters(parameters: Vec<Parameter>) -> HashMap<FunctionId, Parameter> {
-        let mut map = HashMap::new();
+    /// The multiplicity of the parameter, the implicit parameters are always erased, and the
+    /// explicit ones are unrestricted by default. Returns [None] if the multiplicity is not
+    /// `0`, `1` or `ω`.
+    pub fn multiplicity(&self) -> Option<Multiplicity> {
+        if !self.explicit() {
+            return Some(Multiplicity::Zero);
+        }
+
+        if !self.token(TokenKind::OmegaUnicode).is_error() {
+            return Some(Multiplicity::Many);
+        }
+
+        let quantity = [
+            TokenKind::Int32,
+            TokenKind::Int64,
+            TokenKind::Int128,
+            TokenKind::Float64,
+        ]
+        .into_iter()
+        .map(|kind| self.token(kind))
+        .find(|quantity| !quantity.is_error());
+        let Some(quantity) = quantity else {
+            return Some(Multiplicity::Many);
+        };
+
+        match quantity.text.as_str() {
+            "0" => Some(Multiplicity::Zero),
+            "1" => Some(Multiplicity::One),
+            _ => None,
+        }
+    }
+
+    /// Computes the parameters by their names, in the order they are declared.
+    pub fn compute_parameters(parameters: Vec<Parameter>) -> Vec<(FunctionId, Parameter)> {
+        let mut computed = Vec::new();
         for parameter in parameters.into_iter() {
             if parameter.explicit() {
-                map.insert(parameter.name().to_fn_id(), parameter);
+                computed.push((parameter.name().to_fn_id(), parameter));
                 continue;
             }
 
@@ -60,14 +95,28 @@ impl Parameter {
                 if let Expr::LocalExpr(local_expr) = ann.value();
                 if let Some(name) = local_expr.is_ident();
                 then {
-                    map.insert(name.to_fn_id(), parameter);
+                    computed.push((name.to_fn_id(), parameter));
                 }
             }
         }
-        map
+        computed
     }
 }
 
+/// How many times a value can be used at runtime, like in quantitative type theory.
+#[derive(Default, Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Multiplicity {
+    /// The value is erased, it can only be used at the type level.
+    Zero,
+
+    /// The value is linear, it must be used exactly once.
+    One,
+
+    /// The value is unrestricted.
+    #[default]
+    Many,
+}
+
 impl Parameter {
     /// Walks the tree using the given visitor, it will call the visitor's methods for each node
     /// in the tree.
@@ -0,0 +1,31 @@
+use asena_leaf::token::token_set::HasTokens;
+
+/// A node that can be documented by the `///` doc comments before it, like the declarations,
+/// the variants of the enums, and the fields and methods of the classes and traits.
+///
+/// The doc comments aren't tokens, they are kept in the leading trivia of the first token of the
+/// node, like the other comments. So the grammar doesn't need to skip them between the tokens,
+/// and a misplaced doc comment, like one inside of an expression, is a plain comment instead of
+/// a syntax error. The tree is lossless, so they are still printed by the formatter.
+///
+/// # Examples
+///
+/// ```haskell
+/// /// The optional values.
+/// enum Maybe (a: Set) {
+///   /// The value is missing.
+///   Nothing,
+/// }
+/// ```
+pub trait Documented: HasTokens {
+    /// The lines of the doc comments, without the slashes. They are in the leading trivia of
+    /// the first token of the node.
+    fn docs(&self) -> Vec<String> {
+        self.tokens()
+            .first()
+            .map(|token| token.full_text.docs())
+            .unwrap_or_default()
+    }
+}
+
+impl<T: HasTokens> Documented for T {}
@@ -1,16 +1,18 @@
-import { workspace, EventEmitter, ExtensionContext, window, TextDocumentChangeEvent } from 'vscode';
+import { commands, workspace, EventEmitter, ExtensionContext, window, TextDocumentChangeEvent } from 'vscode';
 
 import {
   Disposable,
   Executable,
   LanguageClient,
   LanguageClientOptions,
+  Location,
+  Position,
   ServerOptions,
 } from 'vscode-languageclient/node';
 
 let client: LanguageClient;
 
-export async function activate(_ctx: ExtensionContext) {
+export async function activate(ctx: ExtensionContext) {
   const traceOutputChannel = window.createOutputChannel('Asena Language Server Trace');
   const command = process.env.SERVER_PATH || 'asena-language-server';
 
@@ -47,6 +49,19 @@ export async function activate(_ctx: ExtensionContext) {
   // Create the language client and start the client.
   client = new LanguageClient('asena-language-server', 'asena language server', serverOptions, clientOptions);
   client.start();
+
+  // The reference lenses of the server show the references with this command, that converts the
+  // protocol types of the arguments to the ones of vscode.
+  ctx.subscriptions.push(
+    commands.registerCommand('asena.showReferences', (uri: string, position: Position, locations: Location[]) =>
+      commands.executeCommand(
+        'editor.action.showReferences',
+        client.protocol2CodeConverter.asUri(uri),
+        client.protocol2CodeConverter.asPosition(position),
+        locations.map((location) => client.protocol2CodeConverter.asLocation(location)),
+      ),
+    ),
+  );
 }
 
 export function deactivate(): Thenable<void> | undefined {
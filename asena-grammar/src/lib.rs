@@ -93,6 +93,7 @@ const EXPR_FIRST: &[TokenKind] = &[
     FalseKeyword,
     MatchKeyword,
     IfKeyword,
+    HelpSymbol,
     Int8,
     Int16,
     Int32,
@@ -529,6 +530,11 @@ pub fn params(p: &mut Parser) {
 }
 
 /// Param = ImplicitParam | ExplicitParam
+/// ExplicitParam = '(' Multiplicity? Identifier ':' TypeExpr ')'
+/// Multiplicity = Number | 'ω'
+///
+/// Any number is read as the multiplicity, but only `0` and `1` are valid, the other quantities
+/// are reported by the lowering.
 pub fn param(p: &mut Parser) -> bool {
     let m = p.open();
     let token = p.peek();
@@ -542,6 +548,9 @@ pub fn param(p: &mut Parser) -> bool {
                 p.close(m, SelfParam);
                 return false;
             }
+            if p.at_any(&[OmegaUnicode, Int32, Int64, Int128, Float64]) {
+                p.advance();
+            }
             p.expect(Identifier);
             p.expect(Colon);
             type_expr(p, Linebreak::Cont);
@@ -1276,7 +1285,12 @@ fn _semi(p: &mut Parser, mode: Semi) -> bool {
     match mode {
         Semi::Optional | Semi::OrNewLine => {
             let v = p.at_newline(1) || p.at(Semi);
-            while p.eat(Semi) {}
+            if p.eat(Semi) {
+                while !p.eof() && p.at(Semi) {
+                    p.warning(UeselessSemiWarning);
+                    p.advance();
+                }
+            }
             v
         }
         Semi::Required => {
@@ -1284,8 +1298,9 @@ fn _semi(p: &mut Parser, mode: Semi) -> bool {
                 p.report(MissingSemiError);
             }
 
-            while !p.eof() && p.eat(Semi) {
+            while !p.eof() && p.at(Semi) {
                 p.warning(UeselessSemiWarning);
+                p.advance();
             }
 
             // returns if can continues
@@ -1,8 +1,10 @@
-use asena_ast::{Binary, Infix};
+use asena_ast::{AsenaFile, Binary, Decl, Expr, Infix, Literal, Multiplicity, Signed};
 use asena_grammar::Linebreak;
-use asena_leaf::ast::Node;
+use asena_leaf::ast::{GreenTree, Node};
+use asena_leaf::token::kind::TokenKind::*;
 use asena_lexer::Lexer;
 use asena_parser::Parser;
+use proptest::prelude::*;
 
 #[test]
 fn it_works() {
@@ -19,3 +21,214 @@ fn it_works() {
 
     println!("{:#?}", infix);
 }
+
+/// The multiplicities are written before the names of the explicit parameters, the parameters
+/// without them are unrestricted, and the other quantities are read, but have no multiplicity.
+#[test]
+fn it_parses_the_multiplicities_of_the_parameters() {
+    let code = "pick (0 t : Set) (1 x : t) (y : t) (ω z : t) (2 w : t) (1.0 v : t) : t\n";
+    let tree = Parser::from(Lexer::new(None, code))
+        .run(asena_grammar::file)
+        .build_tree();
+    let errors = tree
+        .report()
+        .diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.message.value.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(errors, []);
+
+    let file = AsenaFile::new(GreenTree::from(tree.data));
+
+    let Some(Decl::Signature(signature)) = file.declarations().into_iter().next() else {
+        panic!("expected a signature");
+    };
+    let multiplicities = signature
+        .parameters()
+        .iter()
+        .map(|parameter| parameter.multiplicity())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        multiplicities,
+        [
+            Some(Multiplicity::Zero),
+            Some(Multiplicity::One),
+            Some(Multiplicity::Many),
+            Some(Multiplicity::Many),
+            None,
+            None,
+        ]
+    );
+}
+
+/// The `ω` is only lexed as the multiplicity when it's alone, and starts identifiers otherwise.
+#[test]
+fn it_lexes_the_omega_as_an_identifier_start() {
+    let kinds = Lexer::new(None, "(ω ωx : t)")
+        .tokens
+        .into_iter()
+        .map(|token| token.value.kind)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        kinds,
+        [
+            LeftParen,
+            OmegaUnicode,
+            Identifier,
+            Colon,
+            Identifier,
+            RightParen
+        ]
+    );
+}
+
+/// The integers that don't fit in 32 bits are lexed into the wider literals.
+#[test]
+fn it_lexes_the_wide_integers() {
+    let code = "1 5000000000 170141183460469231731687303715884105727 1.5";
+    let kinds = Lexer::new(None, code)
+        .tokens
+        .into_iter()
+        .map(|token| token.value.kind)
+        .collect::<Vec<_>>();
+    assert_eq!(kinds, [Int32, Int64, Int128, Float64]);
+
+    let code = "big = 5000000000\n";
+    let tree = Parser::from(Lexer::new(None, code))
+        .run(asena_grammar::file)
+        .build_tree();
+    assert!(tree.report().diagnostics.is_empty());
+
+    let file = AsenaFile::new(GreenTree::from(tree.data));
+    let Some(Decl::Assign(assign)) = file.declarations().into_iter().next() else {
+        panic!("expected an assignment");
+    };
+    let Expr::LiteralExpr(literal) = assign.body() else {
+        panic!("expected a literal");
+    };
+    let literal = literal.literal();
+    assert!(
+        matches!(literal.data(), Literal::Int64(5000000000, Signed::Signed)),
+        "{literal:?}"
+    );
+}
+
+/// The declarations of the generated sources, their tokens are separated by the generated
+/// spaces and tabs.
+const DECLS: &[&str] = &[
+    "use Data.List",
+    "id : a -> a",
+    "id x = x",
+    "add : (1 n : Nat) -> (0 m : Nat) -> Nat",
+    "main = println \"hello, world\"",
+    "#infixr \"+\", 1",
+    "xs = [1, 2, 3]",
+    "If f true thenf _ = thenf",
+    "Cond : [f: Bool -> Set] => (cond: Bool) -> f cond",
+    "enum Maybe (a: Set) { Nothing : Maybe a, Just : (value: a) -> Maybe a, }",
+    "trait Show (a: Set) { show : a -> String, }",
+    "instance Show Nat { show n = \"nat\" }",
+    "class Person { name : String, fun greet(self): String { \"hi\" } }",
+    "Main { IO.println $ fromMaybe 0 (Just 1) }",
+];
+
+/// The whitespace and the comments between the declarations.
+const TRIVIA: &[&str] = &[
+    "\n",
+    "\r\n",
+    "\n\n",
+    " \n",
+    "\t\n",
+    "// comment\n",
+    "  // ☃ ünïcode\n",
+];
+
+/// The ends of the lines of the declarations.
+const LINE_ENDS: &[&str] = &["\n", "\r\n", " // trailing\n"];
+
+/// A declaration, with spaces and tabs between its tokens.
+fn decl() -> impl Strategy<Value = String> {
+    prop::sample::select(DECLS).prop_flat_map(|decl| {
+        let words = decl.split(' ').collect::<Vec<_>>();
+        let spaces = prop::collection::vec("[ \t]{1,3}", words.len() - 1);
+
+        (spaces, prop::sample::select(LINE_ENDS)).prop_map(move |(spaces, line_end)| {
+            let mut source = words[0].to_string();
+            for (space, word) in spaces.iter().zip(&words[1..]) {
+                source.push_str(space);
+                source.push_str(word);
+            }
+            source.push_str(line_end);
+            source
+        })
+    })
+}
+
+fn trivia() -> impl Strategy<Value = String> {
+    prop::collection::vec(prop::sample::select(TRIVIA), 0..4).prop_map(|trivia| trivia.concat())
+}
+
+/// The comment at the end of the file, without a line break.
+fn last_comment() -> impl Strategy<Value = Option<&'static str>> {
+    prop::option::of(Just("// end"))
+}
+
+/// A source code with the declarations between the trivia.
+fn source() -> impl Strategy<Value = String> {
+    let decls = prop::collection::vec((decl(), trivia()), 0..6);
+
+    (trivia(), decls, last_comment()).prop_map(|(leading, decls, last_comment)| {
+        let mut source = leading;
+        for (decl, trivia) in decls {
+            source.push_str(&decl);
+            source.push_str(&trivia);
+        }
+        source.extend(last_comment);
+        source
+    })
+}
+
+fn print(source: &str) -> String {
+    let tree = Parser::from(Lexer::new(None, source))
+        .run(asena_grammar::file)
+        .build_tree();
+
+    tree.data.value.to_string()
+}
+
+/// The `.ase` files of the repository, relative to the directory of this crate.
+const FILES: &[&str] = &[
+    "tests/simple.ase",
+    "../asena/Test.ase",
+    "../asena-ast-lowering/Test.ase",
+    "../asena-ast-resolver/Test.ase",
+    "../asena-highlight/src/simple.ase",
+    "../asena-highlight/src/test.ase",
+];
+
+#[test]
+fn it_prints_the_source_code_of_the_files() {
+    for path in FILES {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(path);
+        let source = std::fs::read_to_string(&path).unwrap();
+
+        assert_eq!(print(&source), source, "{}", path.display());
+    }
+}
+
+proptest! {
+    /// The printed tree of a file is its source code, with the comments and the whitespace.
+    #[test]
+    fn it_prints_the_source_code(source in source()) {
+        prop_assert_eq!(print(&source), source);
+    }
+
+    /// The files without tokens keep their whitespace and comments in the EOF token.
+    #[test]
+    fn it_prints_the_source_code_without_tokens(leading in trivia(), end in last_comment()) {
+        let source = leading + end.unwrap_or_default();
+        prop_assert_eq!(print(&source), source);
+    }
+}
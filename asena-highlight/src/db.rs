@@ -0,0 +1,27 @@
+use std::sync::Arc;
+
+use asena_ast_db::{db::AstDatabase, vfs::VfsFile};
+use asena_lexer::Lexer;
+use asena_parser::Parser;
+use asena_span::Loc;
+
+use crate::{Annotator, HighlightColor, VirtualFile};
+
+#[salsa::query_group(HighlightStorage)]
+pub trait HighlightDatabase: AstDatabase {
+    /// The color of the tokens of the file, sorted by their position.
+    fn highlight(&self, file: VfsFile) -> Arc<Vec<(Loc, HighlightColor)>>;
+}
+
+fn highlight(db: &dyn HighlightDatabase, file: VfsFile) -> Arc<Vec<(Loc, HighlightColor)>> {
+    let source = db.source(file);
+    let data = db.lookup_intern_vfs_file(file);
+
+    let lexer = Lexer::new(data.id.path, &source);
+    let parser = Parser::from(lexer).run(asena_grammar::file);
+    let tree = parser.build_tree();
+
+    let annotator = Annotator::new(VirtualFile::from(tree.data));
+
+    Arc::new(annotator.annotations())
+}
@@ -28,7 +28,7 @@ impl SyntaxHighlight for Token {
             | ClassKeyword | InstanceKeyword | CaseKeyword | WhereKeyword | MatchKeyword
             | UseKeyword => HighlightColor::HardKeyword,
             Int8 | UInt8 | Int16 | UInt16 | Int32 | UInt32 | Int64 | UInt64 | Int128 | UInt128
-            | Float32 | Float64 => HighlightColor::Number,
+            | Float32 | Float64 | OmegaUnicode => HighlightColor::Number,
             LeftBracket | RightBracket | LeftBrace | RightBrace | LeftParen | RightParen
             | Comma | Semi | Colon | Dot => HighlightColor::Delimitator,
             LambdaUnicode | ForallUnicode | PiUnicode | SigmaUnicode => {
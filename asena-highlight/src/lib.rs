@@ -7,10 +7,11 @@ use asena_leaf::node::{Child, Tree};
 use asena_leaf::token::token_set::HasTokens;
 use asena_leaf::token::Token;
 
-use asena_span::{Loc, Spanned};
+use asena_span::{Loc, Spanned, TextRange};
 
 use colored::Colorize;
 use im::HashMap;
+use itertools::Itertools;
 
 pub trait SyntaxHighlight {
     fn highlight(&self) -> HighlightColor;
@@ -91,6 +92,30 @@ impl Annotator {
         self.original.ast().annotate(&mut self);
         self.to_string()
     }
+
+    /// The color of every token of the file, sorted by their position. The semantic colors
+    /// takes precedence over the syntax ones.
+    pub fn annotations(mut self) -> Vec<(Loc, HighlightColor)> {
+        self.original.ast().annotate(&mut self);
+        self.original
+            .contents
+            .tokens()
+            .into_iter()
+            .filter(|token| {
+                let range = &token.span.range;
+                matches!(range, TextRange::Actual(_)) && range.start() < range.end()
+            })
+            .map(|token| {
+                let color = match self.buf.get(&token.span) {
+                    Some(color) => *color,
+                    None => token.value.highlight(),
+                };
+
+                (token.span, color)
+            })
+            .sorted_by_key(|(loc, _)| loc.range.start())
+            .collect()
+    }
 }
 
 impl Display for Annotator {
@@ -98,10 +123,11 @@ impl Display for Annotator {
         for child in self.original.contents.tokens() {
             write!(f, "{}", child.full_text.before_whitespace)?;
             if let Some(color) = self.buf.get(&child.location()) {
-                write!(f, "{}", colorize(*color, child.value))?;
+                write!(f, "{}", colorize(*color, child.value.clone()))?;
             } else {
                 write!(f, "{}", child.value.full_text.code)?;
             }
+            write!(f, "{}", child.full_text.after_whitespace)?;
         }
         Ok(())
     }
@@ -126,7 +152,8 @@ impl Display for VirtualFile {
 impl Renderer for Token {
     fn render(&self, _ctx: &RenderContext, f: &mut Formatter) -> Result {
         write!(f, "{}", self.full_text.before_whitespace)?;
-        write!(f, "{}", colorize(self.highlight(), self.clone()))
+        write!(f, "{}", colorize(self.highlight(), self.clone()))?;
+        write!(f, "{}", self.full_text.after_whitespace)
     }
 }
 
@@ -166,6 +193,8 @@ pub fn colorize(color: HighlightColor, token: Token) -> colored::ColoredString {
     }
 }
 
+pub mod db;
+
 mod highlight;
 mod semantic;
 
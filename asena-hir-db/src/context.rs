@@ -0,0 +1,695 @@
+//! The typing context of the declarations: the locals in scope, and the type expected at each
+//! position. It's known without inference, from the types written in the source, and it's shared
+//! by the typed holes, the hover, the completions and the inlay hints.
+
+use asena_ast_db::vfs::VfsFile;
+use asena_hir::{
+    expr::{
+        data::{HirBranch, HirCallee},
+        HirExpr, HirExprKind,
+    },
+    hir_type::{
+        data::{HirTypeArgument, HirTypeFunction},
+        HirType, HirTypeKind,
+    },
+    pattern::{HirPattern, HirPatternKind},
+    stmt::{HirStmtKind, HirStmtValue},
+    top_level::{data::HirParameterKind, HirBindingGroup, HirTopLevelKind},
+    value::{instr::HirInstr, monads::HirMonad, HirValue, HirValueExpr, HirValueKind},
+    HirLoc, Name,
+};
+use asena_span::{Loc, TextRange};
+use itertools::Itertools;
+
+use crate::{
+    db::HirDatabase,
+    hole::HirHole,
+    info::{HirHint, HirHintKind},
+    usage::collect_names,
+};
+
+/// A global function, or constructor, that can fill a hole.
+#[derive(Debug, Clone)]
+pub struct HirGlobal {
+    pub name: Name,
+    pub parameters: Vec<Option<HirType>>,
+    pub parameter_names: Vec<Option<Name>>,
+    pub return_type: Option<HirType>,
+}
+
+impl HirGlobal {
+    /// Pretty prints the type of the global, like `Int -> Int -> Bool`.
+    pub fn display(&self, db: &dyn HirDatabase) -> String {
+        let display = |value: &Option<HirType>| match value {
+            Some(value) if is_pi(db, *value) => format!("({})", display_type(db, *value)),
+            Some(value) => display_type(db, *value),
+            None => "_".into(),
+        };
+
+        self.parameters
+            .iter()
+            .chain([&self.return_type])
+            .map(display)
+            .join(" -> ")
+    }
+
+    /// If the global can be used where `expected` is expected, after being applied to its
+    /// arguments.
+    pub fn fits(&self, db: &dyn HirDatabase, expected: HirType) -> bool {
+        self.return_type
+            .is_some_and(|value| fits(db, expected, value))
+    }
+}
+
+/// The global functions and constructors visible in the file: the ones of its package, and of
+/// the dependencies.
+pub fn globals(db: &dyn HirDatabase, file: VfsFile) -> Vec<HirGlobal> {
+    let pkg = db.lookup_intern_vfs_file(file).pkg;
+    let data = db.lookup_intern_package(pkg);
+
+    let mut globals = vec![];
+    for pkg in data.dependencies.iter().copied().chain([pkg]) {
+        for file in pkg.files(db).iter() {
+            for def in db.hir_file_defs(*file) {
+                collect_globals(db, db.lookup_intern_top_level(def).kind, &mut globals);
+            }
+        }
+    }
+
+    globals
+}
+
+/// The type of the innermost expression at the `offset`, when it can be known without inference:
+/// the annotated values, the typed locals, and the globals applied to all their arguments.
+pub fn type_at(db: &dyn HirDatabase, file: VfsFile, offset: usize) -> Option<HirType> {
+    let mut collector = ContextCollector::new(db, file, Some(offset));
+    collector.file(file);
+    collector.at_cursor.and_then(|(_, _, value)| value)
+}
+
+/// Pretty prints the type, like it's written in the source.
+pub fn display_type(db: &dyn HirDatabase, value: HirType) -> String {
+    match db.lookup_intern_type(value).kind {
+        HirTypeKind::Error => "{error}".into(),
+        HirTypeKind::Unit => "()".into(),
+        HirTypeKind::This => "Self".into(),
+        HirTypeKind::Name(name) => db.lookup_intern_name(name.name),
+        HirTypeKind::App(app) => match (app.callee, app.arguments.as_slice()) {
+            (HirTypeFunction::Pi, [parameter, HirTypeArgument::Type(codomain)]) => {
+                let parameter = match parameter {
+                    HirTypeArgument::Error => "{error}".into(),
+                    HirTypeArgument::Type(domain) if is_pi(db, *domain) => {
+                        format!("({})", display_type(db, *domain))
+                    }
+                    HirTypeArgument::Type(domain) => display_type(db, *domain),
+                    HirTypeArgument::Named(name, domain) => format!(
+                        "({} : {})",
+                        db.lookup_intern_name(*name),
+                        display_type(db, *domain)
+                    ),
+                };
+
+                format!("{parameter} -> {}", display_type(db, *codomain))
+            }
+            (HirTypeFunction::Type(callee), arguments) => {
+                let arguments = arguments.iter().map(|argument| match argument {
+                    HirTypeArgument::Type(argument) | HirTypeArgument::Named(_, argument) => {
+                        match db.lookup_intern_type(*argument).kind {
+                            HirTypeKind::App(_) => format!("({})", display_type(db, *argument)),
+                            _ => display_type(db, *argument),
+                        }
+                    }
+                    HirTypeArgument::Error => "{error}".into(),
+                });
+
+                std::iter::once(display_type(db, callee))
+                    .chain(arguments)
+                    .join(" ")
+            }
+            _ => "{error}".into(),
+        },
+    }
+}
+
+fn is_pi(db: &dyn HirDatabase, value: HirType) -> bool {
+    split_pi(db, value).is_some()
+}
+
+/// Splits the function type into its domain and codomain.
+pub(crate) fn split_pi(db: &dyn HirDatabase, value: HirType) -> Option<(HirType, HirType)> {
+    split_named_pi(db, value).map(|(_, domain, codomain)| (domain, codomain))
+}
+
+/// Splits the function type into the name of its parameter, if it's named, its domain and its
+/// codomain.
+pub(crate) fn split_named_pi(
+    db: &dyn HirDatabase,
+    value: HirType,
+) -> Option<(Option<Name>, HirType, HirType)> {
+    let HirTypeKind::App(app) = db.lookup_intern_type(value).kind else {
+        return None;
+    };
+
+    let HirTypeFunction::Pi = app.callee else {
+        return None;
+    };
+
+    match app.arguments.as_slice() {
+        [HirTypeArgument::Type(domain), HirTypeArgument::Type(codomain)] => {
+            Some((None, *domain, *codomain))
+        }
+        [HirTypeArgument::Named(name, domain), HirTypeArgument::Type(codomain)] => {
+            Some((Some(*name), *domain, *codomain))
+        }
+        _ => None,
+    }
+}
+
+/// If a value of the type `candidate` can be used where `expected` is expected. The type
+/// variables of the candidate are generic, so they fit any type.
+fn fits(db: &dyn HirDatabase, expected: HirType, candidate: HirType) -> bool {
+    let expected = db.lookup_intern_type(expected).kind;
+    let candidate = db.lookup_intern_type(candidate).kind;
+
+    match (expected, candidate) {
+        (HirTypeKind::Error, _) | (_, HirTypeKind::Error) => false,
+        (_, HirTypeKind::Name(name)) if !name.is_constructor => true,
+        (HirTypeKind::Unit, HirTypeKind::Unit) => true,
+        (HirTypeKind::This, HirTypeKind::This) => true,
+        (HirTypeKind::Name(expected), HirTypeKind::Name(candidate)) => {
+            expected.name == candidate.name
+        }
+        (HirTypeKind::App(expected), HirTypeKind::App(candidate)) => {
+            let callee = match (expected.callee, candidate.callee) {
+                (HirTypeFunction::Pi, HirTypeFunction::Pi) => true,
+                (HirTypeFunction::Type(expected), HirTypeFunction::Type(candidate)) => {
+                    fits(db, expected, candidate)
+                }
+                _ => false,
+            };
+
+            callee
+                && expected.arguments.len() == candidate.arguments.len()
+                && expected
+                    .arguments
+                    .iter()
+                    .zip(candidate.arguments.iter())
+                    .all(|arguments| match arguments {
+                        (
+                            HirTypeArgument::Type(expected) | HirTypeArgument::Named(_, expected),
+                            HirTypeArgument::Type(candidate) | HirTypeArgument::Named(_, candidate),
+                        ) => fits(db, *expected, *candidate),
+                        _ => false,
+                    })
+        }
+        _ => false,
+    }
+}
+
+fn collect_globals(db: &dyn HirDatabase, def: HirTopLevelKind, out: &mut Vec<HirGlobal>) {
+    let group = |group: &HirBindingGroup| {
+        let (parameters, parameter_names) = group
+            .signature
+            .parameters
+            .iter()
+            .filter_map(|parameter| match parameter {
+                HirParameterKind::Explicit(data) => Some((data.parameter_type, Some(data.name))),
+                _ => None,
+            })
+            .unzip();
+
+        HirGlobal {
+            name: group.signature.name,
+            parameters,
+            parameter_names,
+            return_type: group.signature.return_type,
+        }
+    };
+
+    match def {
+        HirTopLevelKind::Error => {}
+        HirTopLevelKind::BindingGroup(ref decl) => out.push(group(decl)),
+        HirTopLevelKind::Struct(ref decl) => out.extend(decl.groups.iter().map(group)),
+        HirTopLevelKind::Instance(ref decl) => out.extend(decl.groups.iter().map(group)),
+        HirTopLevelKind::Trait(ref decl) => out.extend(decl.groups.values().map(group)),
+        HirTopLevelKind::Enum(ref decl) => {
+            out.extend(decl.groups.iter().map(group));
+
+            for variant in decl.variants.values() {
+                let mut parameters = vec![];
+                let mut parameter_names = vec![];
+                let mut value = variant.variant_type;
+                while let Some((name, domain, codomain)) = split_named_pi(db, value) {
+                    parameters.push(Some(domain));
+                    parameter_names.push(name);
+                    value = codomain;
+                }
+
+                out.push(HirGlobal {
+                    name: variant.name,
+                    parameters,
+                    parameter_names,
+                    return_type: Some(value),
+                });
+            }
+        }
+    }
+}
+
+/// Walks the declarations, keeping track of the locals in scope, and the type expected at each
+/// position. It collects the holes, the inlay hints, the lambdas, and the context at the cursor.
+pub(crate) struct ContextCollector<'db> {
+    db: &'db dyn HirDatabase,
+    globals: Vec<HirGlobal>,
+    locals: Vec<(Name, Option<HirType>)>,
+    pub(crate) holes: Vec<HirHole>,
+    cursor: Option<usize>,
+
+    /// The innermost expression at the cursor, with the length of its span, and its type.
+    pub(crate) at_cursor: Option<(usize, HirHole, Option<HirType>)>,
+
+    /// The types of the bindings without annotations, and the names of the parameters of the
+    /// arguments, shown as inlay hints.
+    pub(crate) hints: Vec<HirHint>,
+
+    /// The lambdas, with the types of their parameters.
+    pub(crate) lambdas: Vec<(Loc, Vec<Option<HirType>>)>,
+}
+
+impl<'db> ContextCollector<'db> {
+    pub(crate) fn new(db: &'db dyn HirDatabase, file: VfsFile, cursor: Option<usize>) -> Self {
+        Self {
+            db,
+            globals: globals(db, file),
+            locals: vec![],
+            holes: vec![],
+            cursor,
+            at_cursor: None,
+            hints: vec![],
+            lambdas: vec![],
+        }
+    }
+
+    /// Collects the holes of all the declarations of the file.
+    pub(crate) fn file(&mut self, file: VfsFile) {
+        let db = self.db;
+        for def in db.hir_file_defs(file) {
+            match db.lookup_intern_top_level(def).kind {
+                HirTopLevelKind::Error => {}
+                HirTopLevelKind::BindingGroup(group) => self.group(&group),
+                HirTopLevelKind::Enum(decl) => {
+                    for group in decl.groups.iter() {
+                        self.group(group);
+                    }
+                }
+                HirTopLevelKind::Struct(decl) => {
+                    for group in decl.groups.iter() {
+                        self.group(group);
+                    }
+                }
+                HirTopLevelKind::Instance(decl) => {
+                    for group in decl.groups.iter() {
+                        self.group(group);
+                    }
+                }
+                HirTopLevelKind::Trait(decl) => {
+                    for group in decl.groups.values() {
+                        self.group(group);
+                    }
+                }
+            }
+        }
+    }
+
+    fn group(&mut self, group: &HirBindingGroup) {
+        for declaration in group.declarations.iter() {
+            // The patterns only match the explicit parameters, the implicit ones aren't passed.
+            for (pattern, parameter) in declaration
+                .patterns
+                .iter()
+                .zip(group.signature.explicit_parameters())
+            {
+                let parameter_type = match parameter {
+                    HirParameterKind::Explicit(data) => data.parameter_type,
+                    _ => None,
+                };
+
+                self.bind(*pattern, parameter_type);
+            }
+
+            self.value(declaration.value, group.signature.return_type);
+            self.locals.clear();
+        }
+    }
+
+    /// Binds the variables of the pattern, only a name pattern has the type of the value.
+    fn bind(&mut self, pattern: HirPattern, value_type: Option<HirType>) {
+        if let HirPatternKind::Name(ref name) = self.db.lookup_intern_pattern(pattern).kind {
+            self.locals.push((name.name, value_type));
+            return;
+        }
+
+        let mut names = vec![];
+        collect_names(self.db, pattern, &mut names);
+        self.locals
+            .extend(names.into_iter().map(|name| (name, None)));
+    }
+
+    fn scoped(&mut self, f: impl FnOnce(&mut Self)) {
+        let len = self.locals.len();
+        f(self);
+        self.locals.truncate(len);
+    }
+
+    fn value(&mut self, value: HirValue, expected: Option<HirType>) {
+        let data = self.db.lookup_intern_value(value);
+
+        match data.kind {
+            HirValueKind::Error | HirValueKind::Unit => {}
+            HirValueKind::Expr(HirValueExpr(expr)) => self.expr(expr, expected),
+            HirValueKind::Block(block) => self.scoped(|this| {
+                let mut instructions = block.instructions.clone();
+
+                // The last expression statement is the value of the block too, so it should not
+                // be visited twice.
+                if let Some(last) = instructions.last() {
+                    let last = this.db.lookup_intern_stmt(*last);
+                    if let HirStmtKind::Value(HirStmtValue(value)) = last.kind {
+                        if value == block.value {
+                            instructions.pop();
+                        }
+                    }
+                }
+
+                for instruction in instructions {
+                    match this.db.lookup_intern_stmt(instruction).kind {
+                        HirStmtKind::Error => {}
+                        HirStmtKind::Ask(stmt) => {
+                            this.value(stmt.value, None);
+                            this.bind(stmt.pattern, None);
+                        }
+                        HirStmtKind::Let(stmt) => {
+                            let annotation = this.annotation(stmt.value);
+                            this.value(stmt.value, None);
+
+                            let value_type = match annotation {
+                                Some(value) => Some(value),
+                                None => {
+                                    let value = this.synthesize(stmt.value);
+                                    if let Some(value) = value {
+                                        this.hint_type(stmt.pattern, value);
+                                    }
+                                    value
+                                }
+                            };
+
+                            this.bind(stmt.pattern, value_type);
+                        }
+                        HirStmtKind::Return(stmt) => this.value(stmt.value, expected),
+                        HirStmtKind::Value(HirStmtValue(value)) => this.value(value, None),
+                    }
+                }
+
+                this.value(block.value, expected);
+            }),
+            HirValueKind::Monad(HirMonad::PureUnit) => {}
+            HirValueKind::Monad(HirMonad::Pure(value)) => self.value(value, None),
+            HirValueKind::Monad(HirMonad::Bind(name, value, then)) => {
+                self.value(value, None);
+                self.scoped(|this| {
+                    this.locals.push((name, None));
+                    this.value(then, expected);
+                });
+            }
+            HirValueKind::Instr(instr) => match instr {
+                HirInstr::Null | HirInstr::Variable(_) => {}
+                HirInstr::Let(name, value) => {
+                    self.value(value, None);
+                    self.locals.push((name, None));
+                }
+                HirInstr::ObjectClone(value) | HirInstr::ObjectDrop(value) => {
+                    self.value(value, expected)
+                }
+                HirInstr::Block(block) => self.scoped(|this| {
+                    for instruction in block.instructions {
+                        this.value(instruction, None);
+                    }
+
+                    this.value(block.value, expected);
+                }),
+            },
+        }
+    }
+
+    fn expr(&mut self, expr: HirExpr, expected: Option<HirType>) {
+        let data = self.db.lookup_intern_expr(expr);
+        self.visit_cursor(expr, &data.span, expected);
+
+        match data.kind {
+            HirExprKind::Help(help) => {
+                let candidates = self.candidates(expected);
+                self.holes.push(HirHole {
+                    span: data.span,
+                    expected,
+                    locals: self.locals.clone(),
+                    candidates,
+                });
+
+                self.value(help.value, None);
+            }
+            HirExprKind::Group(group) => self.value(group.value, expected),
+            HirExprKind::Ann(ann) => self.value(ann.value, Some(ann.against)),
+            HirExprKind::Lam(lam) => self.scoped(|this| {
+                let mut expected = expected;
+                let mut domains = vec![];
+                for parameter in lam.parameters {
+                    let (domain, codomain) = match expected.and_then(|e| split_pi(this.db, e)) {
+                        Some((domain, codomain)) => (Some(domain), Some(codomain)),
+                        None => (None, None),
+                    };
+
+                    this.locals.push((parameter, domain));
+                    domains.push(domain);
+                    expected = codomain;
+                }
+
+                this.lambdas.push((data.span.original.clone(), domains));
+                this.value(lam.value, expected);
+            }),
+            HirExprKind::Array(array) => {
+                for item in array.items {
+                    self.value(item, None);
+                }
+            }
+            HirExprKind::Call(call) => {
+                let mut parameters = vec![];
+                let mut names = vec![];
+                if let HirCallee::Value(callee) = call.callee {
+                    if let Some(global) = self.global_of(callee) {
+                        parameters = global.parameters.clone();
+                        names = global.parameter_names.clone();
+                    }
+
+                    self.value(callee, None);
+                }
+
+                for (i, argument) in call.arguments.into_iter().enumerate() {
+                    if let Some(name) = names.get(i).copied().flatten() {
+                        self.hint_parameter(argument, name);
+                    }
+
+                    self.value(argument, parameters.get(i).copied().flatten());
+                }
+
+                if let Some(dsl) = call.as_dsl {
+                    self.scoped(|this| {
+                        for parameter in dsl.parameters {
+                            this.locals.push((parameter, None));
+                        }
+
+                        this.value(dsl.value, None);
+                    });
+                }
+            }
+            HirExprKind::Match(expr) => {
+                self.value(expr.scrutinee, None);
+                for case in expr.cases {
+                    self.scoped(|this| {
+                        this.bind(case.pattern, None);
+                        match case.value {
+                            HirBranch::Error => {}
+                            HirBranch::Expr(value) | HirBranch::Block(value) => {
+                                this.value(value, expected)
+                            }
+                        }
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Stores the context of the expression, if it's the innermost one at the cursor.
+    fn visit_cursor(&mut self, expr: HirExpr, span: &HirLoc, expected: Option<HirType>) {
+        let Some(offset) = self.cursor else {
+            return;
+        };
+
+        let range = &span.original.range;
+        if !matches!(range, TextRange::Actual(_)) || offset < range.start() || offset > range.end()
+        {
+            return;
+        }
+
+        let len = range.end() - range.start();
+        if self
+            .at_cursor
+            .as_ref()
+            .is_some_and(|(best, _, _)| *best < len)
+        {
+            return;
+        }
+
+        let hole = HirHole {
+            span: span.clone(),
+            expected,
+            locals: self.locals.clone(),
+            candidates: self.candidates(expected),
+        };
+
+        self.at_cursor = Some((len, hole, self.synthesize_expr(expr)));
+    }
+
+    /// The type of the annotated value, like `let x = y : Int`.
+    fn annotation(&self, value: HirValue) -> Option<HirType> {
+        let HirValueKind::Expr(HirValueExpr(expr)) = self.db.lookup_intern_value(value).kind else {
+            return None;
+        };
+
+        match self.db.lookup_intern_expr(expr).kind {
+            HirExprKind::Ann(ann) => Some(ann.against),
+            _ => None,
+        }
+    }
+
+    /// The global function of the callee, if it's a reference to a global function, that is not
+    /// shadowed by a local.
+    fn global_of(&self, callee: HirValue) -> Option<&HirGlobal> {
+        let HirValueKind::Expr(HirValueExpr(expr)) = self.db.lookup_intern_value(callee).kind
+        else {
+            return None;
+        };
+        let HirExprKind::Reference(reference) = self.db.lookup_intern_expr(expr).kind else {
+            return None;
+        };
+
+        if self.locals.iter().any(|(name, _)| *name == reference.name) {
+            return None;
+        }
+
+        self.globals
+            .iter()
+            .find(|global| global.name == reference.name)
+    }
+
+    /// The type of the value, when it can be known without inference: the annotated values, the
+    /// typed locals, and the globals applied to all their arguments.
+    fn synthesize(&self, value: HirValue) -> Option<HirType> {
+        let HirValueKind::Expr(HirValueExpr(expr)) = self.db.lookup_intern_value(value).kind else {
+            return None;
+        };
+
+        self.synthesize_expr(expr)
+    }
+
+    fn synthesize_expr(&self, expr: HirExpr) -> Option<HirType> {
+        match self.db.lookup_intern_expr(expr).kind {
+            HirExprKind::Ann(ann) => Some(ann.against),
+            HirExprKind::Group(group) => self.synthesize(group.value),
+            HirExprKind::Reference(reference) => {
+                let local = self
+                    .locals
+                    .iter()
+                    .rev()
+                    .find(|(name, _)| *name == reference.name);
+                if let Some((_, value)) = local {
+                    return *value;
+                }
+
+                let global = self
+                    .globals
+                    .iter()
+                    .find(|global| global.name == reference.name)?;
+
+                global
+                    .parameters
+                    .is_empty()
+                    .then_some(global.return_type)
+                    .flatten()
+            }
+            HirExprKind::Call(call) => {
+                let HirCallee::Value(callee) = call.callee else {
+                    return None;
+                };
+                let global = self.global_of(callee)?;
+
+                (global.parameters.len() == call.arguments.len())
+                    .then_some(global.return_type)
+                    .flatten()
+            }
+            _ => None,
+        }
+    }
+
+    /// Shows the type of the binding, if it's a name pattern.
+    fn hint_type(&mut self, pattern: HirPattern, value: HirType) {
+        let data = self.db.lookup_intern_pattern(pattern);
+        if let HirPatternKind::Name(_) = data.kind {
+            self.hints.push(HirHint {
+                loc: data.span.original,
+                kind: HirHintKind::Type(value),
+            });
+        }
+    }
+
+    /// Shows the name of the parameter before the argument, unless the argument is a reference
+    /// with the same name.
+    fn hint_parameter(&mut self, argument: HirValue, name: Name) {
+        let data = self.db.lookup_intern_value(argument);
+        if let HirValueKind::Expr(HirValueExpr(expr)) = data.kind {
+            if let HirExprKind::Reference(reference) = self.db.lookup_intern_expr(expr).kind {
+                if reference.name == name {
+                    return;
+                }
+            }
+        }
+
+        self.hints.push(HirHint {
+            loc: data.span.original,
+            kind: HirHintKind::Parameter(name),
+        });
+    }
+
+    /// The locals and globals that fit the expected type, the globals fit if their return type
+    /// fits, as they can be applied to their arguments.
+    fn candidates(&self, expected: Option<HirType>) -> Vec<Name> {
+        let Some(expected) = expected else {
+            return vec![];
+        };
+
+        let locals = self
+            .locals
+            .iter()
+            .rev()
+            .filter(|(_, value)| value.is_some_and(|value| fits(self.db, expected, value)))
+            .map(|(name, _)| *name);
+
+        let globals = self
+            .globals
+            .iter()
+            .filter(|global| global.fits(self.db, expected))
+            .map(|global| global.name);
+
+        locals.chain(globals).unique().collect()
+    }
+}
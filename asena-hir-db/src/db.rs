@@ -1,3 +1,4 @@
+use crate::hole::HirHole;
 use asena_ast_db::{db::AstDatabase, package::Package, vfs::VfsFile};
 use asena_ast_lowering::db::AstLowerrer;
 use asena_ast_resolver::db::AstResolverDatabase;
@@ -24,6 +25,15 @@ pub trait HirDatabase:
     #[salsa::invoke(crate::rc::rc)]
     fn hir_rc(&self, declaration: AstParam<HirBindingGroup>) -> HirBindingGroup;
 
+    #[salsa::invoke(crate::erase::erase)]
+    fn hir_erase(&self, declaration: AstParam<HirBindingGroup>) -> HirBindingGroup;
+
+    #[salsa::invoke(crate::usage::usage)]
+    fn hir_usage(&self, def: HirTopLevel) -> ();
+
+    #[salsa::invoke(crate::hole::holes)]
+    fn hir_holes(&self, file: VfsFile) -> Vec<HirHole>;
+
     fn hir_file_defs(&self, file: VfsFile) -> HashSet<HirTopLevel>;
 
     fn hir_defs(&self, pkg: Package) -> HashSet<HirTopLevel>;
@@ -55,6 +65,10 @@ fn hir_file_defs(db: &dyn HirDatabase, file: VfsFile) -> HashSet<HirTopLevel> {
     let ast = db.ast_resolved_file(ast.into());
     let hir = db.hir_file(ast.into());
 
+    for def in hir.declarations.iter() {
+        db.hir_usage(*def);
+    }
+
     hir.declarations.into_iter().collect()
 }
 
@@ -64,6 +78,7 @@ fn hir_defs(db: &dyn HirDatabase, pkg: Package) -> HashSet<HirTopLevel> {
     for file in pkg.files(db).iter() {
         let file = db.hir_loceval(*file);
         let groups = db.hir_file_defs(file);
+        db.hir_holes(file);
 
         defs.extend(groups);
     }
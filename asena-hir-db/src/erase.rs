@@ -0,0 +1,42 @@
+use asena_hir::top_level::{data::HirParameterKind, HirBindingGroup};
+use asena_leaf::ast::AstParam;
+
+use crate::db::HirDatabase;
+
+/// Erases the parameters with zero multiplicity, and their patterns in the declarations, as they
+/// are only used at the type level.
+pub fn erase(_db: &dyn HirDatabase, declaration: AstParam<HirBindingGroup>) -> HirBindingGroup {
+    let mut group = declaration.data;
+    let erased = group.signature.erased_parameters();
+    if erased.is_empty() {
+        return group;
+    }
+
+    // The implicit parameters are kept, as the erased positions only count the explicit ones.
+    let mut position = 0;
+    group.signature.parameters.retain(|parameter| {
+        if matches!(parameter, HirParameterKind::Implicit(_)) {
+            return true;
+        }
+
+        position += 1;
+        !erased.contains(&(position - 1))
+    });
+
+    group.declarations = group
+        .declarations
+        .into_iter()
+        .map(|mut declaration| {
+            declaration.patterns = std::mem::take(&mut declaration.patterns)
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| !erased.contains(i))
+                .map(|(_, pattern)| pattern)
+                .collect();
+
+            declaration
+        })
+        .collect();
+
+    group
+}
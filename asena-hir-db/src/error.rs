@@ -0,0 +1,35 @@
+use asena_report::{DiagnosticKind, InternalError};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum HirError {
+    #[error("linear value `{0}` is dropped, it must be used exactly once")]
+    LinearValueDroppedError(String),
+
+    #[error("linear value `{0}` is used more than once")]
+    LinearValueUsedTwiceError(String),
+
+    #[error("erased value `{0}` can not be used at runtime")]
+    ErasedValueUsedError(String),
+}
+
+impl HirError {
+    pub fn discriminant(&self) -> u8 {
+        // SAFETY: Because `Self` is marked `repr(u8)`, its layout is a `repr(C)` `union`
+        // between `repr(C)` structs, each of which has the `u8` discriminant as its first
+        // field, so we can read the discriminant without offsetting the pointer.
+        unsafe { *<*const _>::from(self).cast::<u8>() }
+    }
+}
+
+impl InternalError for HirError {
+    fn code(&self) -> u16 {
+        // The type checking errors are `E05xx`, they are listed in `docs/diagnostics.md`.
+        500 + self.discriminant() as u16
+    }
+
+    fn kind(&self) -> DiagnosticKind {
+        DiagnosticKind::TypeError
+    }
+}
@@ -0,0 +1,96 @@
+//! Typed holes: every `?` expression reports the type expected at its position, the locals in
+//! scope, and the global functions that can fill it.
+//!
+//! There is no type inference yet, so the expected types are the ones written in the source: the
+//! return type of the declaration, the parameter types of a known callee, and the annotations.
+
+use asena_ast_db::{package::HasDiagnostic, vfs::VfsFile};
+use asena_hir::{hir_type::HirType, HirLoc, Name};
+use asena_report::{DiagnosticKind, InternalError, WithError};
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::{
+    context::{display_type, ContextCollector},
+    db::HirDatabase,
+};
+
+/// A `?` expression, with the context needed to fill it.
+#[derive(Hash, Clone, Debug, PartialEq, Eq)]
+pub struct HirHole {
+    pub span: HirLoc,
+    pub expected: Option<HirType>,
+    pub locals: Vec<(Name, Option<HirType>)>,
+    pub candidates: Vec<Name>,
+}
+
+/// The information diagnostic of a hole, it's not an error, so the hole can be used to ask the
+/// compiler while writing the code.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("found hole of type `{expected}`\n  locals: {locals}\n  candidates: {candidates}")]
+pub struct HoleInfo {
+    pub expected: String,
+    pub locals: String,
+    pub candidates: String,
+}
+
+impl InternalError for HoleInfo {
+    fn kind(&self) -> DiagnosticKind {
+        DiagnosticKind::Info
+    }
+}
+
+/// The holes of the file, each one is reported as an information diagnostic of its package.
+pub fn holes(db: &dyn HirDatabase, file: VfsFile) -> Vec<HirHole> {
+    let mut collector = ContextCollector::new(db, file, None);
+    collector.file(file);
+
+    for hole in collector.holes.iter() {
+        hole.span.clone().fail(describe(db, hole)).push(db);
+    }
+
+    collector.holes
+}
+
+/// The context of the innermost expression at the `offset`, like it was a hole: the type
+/// expected at the position, the locals in scope, and the candidates that fit. It's used to rank
+/// the completions.
+pub fn hole_at(db: &dyn HirDatabase, file: VfsFile, offset: usize) -> Option<HirHole> {
+    let mut collector = ContextCollector::new(db, file, Some(offset));
+    collector.file(file);
+    collector.at_cursor.map(|(_, hole, _)| hole)
+}
+
+/// Builds the diagnostic of the hole, with the types pretty printed.
+pub fn describe(db: &dyn HirDatabase, hole: &HirHole) -> HoleInfo {
+    let display = |value: Option<HirType>| match value {
+        Some(value) => display_type(db, value),
+        None => "_".into(),
+    };
+
+    let locals = hole
+        .locals
+        .iter()
+        .map(|(name, value)| format!("{} : {}", db.lookup_intern_name(*name), display(*value)))
+        .join(", ");
+
+    let candidates = hole
+        .candidates
+        .iter()
+        .map(|name| db.lookup_intern_name(*name))
+        .join(", ");
+
+    HoleInfo {
+        expected: display(hole.expected),
+        locals: if locals.is_empty() {
+            "none".into()
+        } else {
+            locals
+        },
+        candidates: if candidates.is_empty() {
+            "none".into()
+        } else {
+            candidates
+        },
+    }
+}
@@ -0,0 +1,412 @@
+//! The information shown by the editor about the code: the signatures and the documentation of
+//! the definitions, the signatures of the calls being written, and the inlay hints with the types
+//! of the bindings, and the names of the parameters of the arguments.
+
+use std::ops::Range;
+
+use asena_ast::{App, AsenaListener, Expr, Lam};
+use asena_ast_db::vfs::VfsFile;
+use asena_ast_resolver::references::AstReferenceKind;
+use asena_hir::{
+    hir_type::HirType,
+    top_level::{
+        data::{HirDoc, HirMultiplicity, HirParameterKind, HirSignature},
+        HirBindingGroup, HirTopLevelKind,
+    },
+    Name,
+};
+use asena_leaf::ast::Located;
+use asena_span::{Loc, TextRange};
+use itertools::Itertools;
+
+use crate::{
+    context::{display_type, split_named_pi, ContextCollector},
+    db::HirDatabase,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HirHintKind {
+    /// The type of a binding without annotation, shown after it.
+    Type(HirType),
+
+    /// The name of the parameter of an argument, shown before it.
+    Parameter(Name),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HirHint {
+    pub loc: Loc,
+    pub kind: HirHintKind,
+}
+
+/// The signature and the documentation of a definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HirDefInfo {
+    pub signature: String,
+    pub docs: Vec<String>,
+}
+
+/// A parameter of the signature shown while writing a call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HirParameterInfo {
+    /// The byte range of the parameter in the label of the signature.
+    pub range: Range<usize>,
+
+    /// If the parameter is passed in the calls, the implicit parameters aren't.
+    pub is_explicit: bool,
+}
+
+/// The signature of the called global, and the parameter of the argument at the cursor, if there
+/// is a parameter for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HirSignatureHelp {
+    pub label: String,
+    pub parameters: Vec<HirParameterInfo>,
+    pub docs: Vec<String>,
+    pub active_parameter: Option<usize>,
+}
+
+/// The inlay hints of the file: the types of the `let` bindings and of the lambda parameters,
+/// when they are known, and the names of the parameters of the arguments of the calls.
+pub fn hints(db: &dyn HirDatabase, file: VfsFile) -> Vec<HirHint> {
+    let mut collector = ContextCollector::new(db, file, None);
+    collector.file(file);
+
+    let ast = db.ast(file);
+    let ast = db.infix_commands(ast.into());
+    let ast = db.ordered_prec(ast.into());
+    let ast = db.ast_resolved_file(ast.into());
+
+    let mut finder = LamFinder {
+        lambdas: &collector.lambdas,
+        hints: vec![],
+    };
+    finder.listens(ast.declarations());
+
+    let mut hints = collector.hints;
+    hints.extend(finder.hints);
+    hints.sort_by_key(|hint| (hint.loc.range.start(), hint.loc.range.end()));
+    hints
+}
+
+/// Finds the signature, and the documentation of the global with the `name`, in the package of
+/// the file, or in its dependencies.
+pub fn def_info(db: &dyn HirDatabase, file: VfsFile, name: &str) -> Option<HirDefInfo> {
+    let (definition, docs) = find_definition(db, file, name)?;
+    let signature = match definition {
+        HirDefinition::Signature(ref signature) => display_signature(db, signature),
+        HirDefinition::Variant(_, variant_type) => {
+            format!("{name} : {}", display_type(db, variant_type))
+        }
+    };
+
+    Some(HirDefInfo { signature, docs })
+}
+
+/// The signature of the call that encloses the `offset`, with the parameter of the argument at
+/// the offset. The offset can be in the spaces after the call, where the next argument is
+/// written, like in `Cons 10 |`.
+pub fn signature_help(
+    db: &dyn HirDatabase,
+    file: VfsFile,
+    offset: usize,
+) -> Option<HirSignatureHelp> {
+    let source = db.source(file);
+
+    let ast = db.ast(file);
+    let ast = db.infix_commands(ast.into());
+    let ast = db.ordered_prec(ast.into());
+    let ast = db.ast_resolved_file(ast.into());
+
+    let mut finder = CallFinder {
+        source: &source,
+        offset,
+        call: None,
+    };
+    finder.listens(ast.declarations());
+
+    let references = db.ast_references(file);
+    let (callee, arguments) = match finder.call {
+        Some((_, callee, arguments)) => (callee, arguments),
+        None => {
+            // The first argument isn't written yet, so there is no call, only the callee followed
+            // by spaces, like in `Cons |`.
+            let reference = references
+                .iter()
+                .filter(|reference| reference.kind == AstReferenceKind::Read)
+                .filter(|reference| reference.loc.range.end() < offset)
+                .find(|reference| reaches(&source, &reference.loc, offset))?;
+
+            (reference.loc.clone(), vec![])
+        }
+    };
+
+    // The callee can have many references, like `xs.map`, the last one is the called function.
+    let def = references
+        .iter()
+        .filter(|reference| {
+            callee.range.start() <= reference.loc.range.start()
+                && reference.loc.range.end() <= callee.range.end()
+        })
+        .max_by_key(|reference| reference.loc.range.start())?
+        .def;
+    let name = db.lookup_intern_def(def).name.to_string();
+
+    let (definition, docs) = find_definition(db, file, &name)?;
+    let (label, parameters) = match definition {
+        HirDefinition::Signature(ref signature) => signature_label(db, signature),
+        HirDefinition::Variant(name, variant_type) => variant_label(db, name, variant_type),
+    };
+
+    // The implicit parameters aren't passed in the calls, so the arguments are matched with the
+    // explicit ones.
+    let argument = arguments
+        .iter()
+        .filter(|argument| argument.range.end() < offset)
+        .count();
+    let active_parameter = parameters
+        .iter()
+        .positions(|parameter| parameter.is_explicit)
+        .nth(argument);
+
+    Some(HirSignatureHelp {
+        label,
+        parameters,
+        docs,
+        active_parameter,
+    })
+}
+
+/// Pretty prints the signature, like it's written in the source, like
+/// `If [f : Bool -> Set] (cond : Bool) : f cond`.
+pub fn display_signature(db: &dyn HirDatabase, signature: &HirSignature) -> String {
+    signature_label(db, signature).0
+}
+
+/// The declaration of a global, that has a signature, or that is a variant of an enum.
+enum HirDefinition {
+    Signature(HirSignature),
+    Variant(Name, HirType),
+}
+
+/// Finds the declaration, and the documentation of the global with the `name`, in the package of
+/// the file, or in its dependencies.
+fn find_definition(
+    db: &dyn HirDatabase,
+    file: VfsFile,
+    name: &str,
+) -> Option<(HirDefinition, Vec<String>)> {
+    let pkg = db.lookup_intern_vfs_file(file).pkg;
+    let data = db.lookup_intern_package(pkg);
+
+    for pkg in data.dependencies.iter().copied().chain([pkg]) {
+        for file in pkg.files(db).iter() {
+            for def in db.hir_file_defs(*file) {
+                let def = db.lookup_intern_top_level(def);
+                let texts = |docs: &[HirDoc]| docs.iter().map(|doc| doc.text.clone()).collect_vec();
+                let docs = texts(&def.docs);
+                let is_named =
+                    |signature: &HirSignature| db.lookup_intern_name(signature.name) == name;
+                let found = |signature: &HirSignature, docs: Vec<String>| {
+                    (HirDefinition::Signature(signature.clone()), docs)
+                };
+                let group_found = |mut groups: &mut dyn Iterator<Item = &HirBindingGroup>| {
+                    Iterator::find(&mut groups, |group| is_named(&group.signature))
+                        .map(|group| found(&group.signature, texts(&group.docs)))
+                };
+
+                let found = match def.kind {
+                    HirTopLevelKind::Error => None,
+                    HirTopLevelKind::BindingGroup(ref group) if is_named(&group.signature) => {
+                        Some(found(&group.signature, docs))
+                    }
+                    HirTopLevelKind::BindingGroup(_) => None,
+                    HirTopLevelKind::Enum(ref decl) if is_named(&decl.signature) => {
+                        Some(found(&decl.signature, docs))
+                    }
+                    HirTopLevelKind::Enum(ref decl) => decl
+                        .variants
+                        .values()
+                        .find(|variant| db.lookup_intern_name(variant.name) == name)
+                        .map(|variant| {
+                            let definition =
+                                HirDefinition::Variant(variant.name, variant.variant_type);
+                            (definition, texts(&variant.docs))
+                        })
+                        .or_else(|| group_found(&mut decl.groups.iter())),
+                    HirTopLevelKind::Struct(ref decl) if is_named(&decl.signature) => {
+                        Some(found(&decl.signature, docs))
+                    }
+                    HirTopLevelKind::Struct(ref decl) => group_found(&mut decl.groups.iter()),
+                    HirTopLevelKind::Trait(ref decl) if is_named(&decl.signature) => {
+                        Some(found(&decl.signature, docs))
+                    }
+                    HirTopLevelKind::Trait(ref decl) => group_found(&mut decl.groups.values()),
+                    HirTopLevelKind::Instance(ref decl) => group_found(&mut decl.groups.iter()),
+                };
+
+                if found.is_some() {
+                    return found;
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// The pretty printed signature, with the ranges of its parameters.
+fn signature_label(
+    db: &dyn HirDatabase,
+    signature: &HirSignature,
+) -> (String, Vec<HirParameterInfo>) {
+    let parameter = |parameter: &HirParameterKind| {
+        let (data, open, close) = match parameter {
+            HirParameterKind::Error => return "{error}".to_string(),
+            HirParameterKind::This => return "self".to_string(),
+            HirParameterKind::Explicit(data) => (data, "(", ")"),
+            HirParameterKind::Implicit(data) => (data, "[", "]"),
+        };
+
+        let multiplicity = match data.multiplicity {
+            HirMultiplicity::Zero => "0 ",
+            HirMultiplicity::One => "1 ",
+            HirMultiplicity::Many => "",
+        };
+        let name = db.lookup_intern_name(data.name);
+
+        match data.parameter_type {
+            Some(value) => {
+                let value = display_type(db, value);
+                format!("{open}{multiplicity}{name} : {value}{close}")
+            }
+            None => format!("{multiplicity}{name}"),
+        }
+    };
+
+    let mut label = db.lookup_intern_name(signature.name);
+    let mut parameters = vec![];
+    for kind in signature.parameters.iter() {
+        let is_explicit = matches!(kind, HirParameterKind::Explicit(_));
+        push_parameter(&mut label, &mut parameters, parameter(kind), is_explicit);
+    }
+
+    if let Some(return_type) = signature.return_type {
+        label.push_str(&format!(" : {}", display_type(db, return_type)));
+    }
+
+    (label, parameters)
+}
+
+/// The signature of the variant, with the parameters of its type, like
+/// `Cons (head : a) (tail : List a) : List a`.
+fn variant_label(
+    db: &dyn HirDatabase,
+    name: Name,
+    value: HirType,
+) -> (String, Vec<HirParameterInfo>) {
+    let mut label = db.lookup_intern_name(name);
+    let mut parameters = vec![];
+
+    let mut value = value;
+    while let Some((name, domain, codomain)) = split_named_pi(db, value) {
+        let domain = display_type(db, domain);
+        let text = match name {
+            Some(name) => format!("({} : {domain})", db.lookup_intern_name(name)),
+            None => format!("({domain})"),
+        };
+
+        push_parameter(&mut label, &mut parameters, text, true);
+        value = codomain;
+    }
+
+    label.push_str(&format!(" : {}", display_type(db, value)));
+
+    (label, parameters)
+}
+
+fn push_parameter(
+    label: &mut String,
+    parameters: &mut Vec<HirParameterInfo>,
+    text: String,
+    is_explicit: bool,
+) {
+    label.push(' ');
+    parameters.push(HirParameterInfo {
+        range: label.len()..label.len() + text.len(),
+        is_explicit,
+    });
+    label.push_str(&text);
+}
+
+/// If the offset is in the location, or in the spaces after it, in the same line.
+fn reaches(source: &str, loc: &Loc, offset: usize) -> bool {
+    let range = &loc.range;
+    if !matches!(range, TextRange::Actual(_)) || offset < range.start() {
+        return false;
+    }
+
+    offset <= range.end()
+        || source
+            .get(range.end()..offset)
+            .is_some_and(|spaces| spaces.chars().all(|c| c == ' ' || c == '\t'))
+}
+
+/// Finds the innermost call that encloses the offset, with the location of its callee, and of
+/// its arguments. The spine of the applications is flattened, so `Cons 10 Nil` is the call of
+/// `Cons` with two arguments.
+struct CallFinder<'a> {
+    source: &'a str,
+    offset: usize,
+    call: Option<(Loc, Loc, Vec<Loc>)>,
+}
+
+impl AsenaListener for CallFinder<'_> {
+    fn enter_app(&mut self, value: App) {
+        let location = value.location().into_owned();
+        if !reaches(self.source, &location, self.offset) {
+            return;
+        }
+
+        let length = |loc: &Loc| loc.range.end() - loc.range.start();
+        if let Some((ref call, _, _)) = self.call {
+            if length(call) <= length(&location) {
+                return;
+            }
+        }
+
+        let mut arguments = vec![value.argument().location().into_owned()];
+        let mut callee = value.callee();
+        while let Expr::App(app) = callee {
+            arguments.push(app.argument().location().into_owned());
+            callee = app.callee();
+        }
+        arguments.reverse();
+
+        self.call = Some((location, callee.location().into_owned(), arguments));
+    }
+}
+
+/// Finds the parameters of the lambdas, to show the types of the parameters after them.
+struct LamFinder<'a> {
+    lambdas: &'a [(Loc, Vec<Option<HirType>>)],
+    hints: Vec<HirHint>,
+}
+
+impl AsenaListener for LamFinder<'_> {
+    fn enter_lam(&mut self, value: Lam) {
+        let location = value.location();
+        let Some((_, domains)) = self.lambdas.iter().find(|(loc, _)| *loc == *location) else {
+            return;
+        };
+
+        for (parameter, domain) in value.parameters().iter().zip(domains.iter()) {
+            if let Some(domain) = domain {
+                self.hints.push(HirHint {
+                    loc: parameter.location().into_owned(),
+                    kind: HirHintKind::Type(*domain),
+                });
+            }
+        }
+    }
+}
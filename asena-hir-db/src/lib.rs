@@ -1,6 +1,15 @@
 #![feature(trait_upcasting)]
 
+pub mod context;
 pub mod db;
+pub mod erase;
+pub mod error;
+pub mod hole;
+pub mod info;
 pub mod loceval;
 pub mod mbind;
 pub mod rc;
+pub mod usage;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,246 @@
+use asena_ast_db::{
+    db::{AstDatabase, AstDatabaseStorage},
+    package::Package,
+    testing::load_main,
+};
+use asena_ast_lowering::db::AstLowerrerStorage;
+use asena_ast_resolver::db::AstResolverStorage;
+use asena_hir::{
+    interner::{HirInterner, HirStorage},
+    pattern::HirPatternKind,
+    top_level::{
+        data::{HirDoc, HirParameterKind},
+        HirTopLevelKind,
+    },
+};
+use asena_prec::db::PrecStorage;
+use asena_report::{BoxInternalError, Diagnostic};
+
+use crate::db::{HirDatabase, HirDatabaseStorage};
+
+#[salsa::database(
+    PrecStorage,
+    AstDatabaseStorage,
+    HirDatabaseStorage,
+    AstLowerrerStorage,
+    AstResolverStorage,
+    HirStorage
+)]
+struct TestDatabase {
+    storage: salsa::Storage<TestDatabase>,
+}
+
+impl salsa::Database for TestDatabase {}
+
+/// Lowers a package with `source` as its main module, running the checks over its declarations.
+fn check(source: &str) -> (TestDatabase, Package) {
+    let mut db = TestDatabase {
+        storage: Default::default(),
+    };
+    let (pkg, _) = load_main(&mut db, source);
+    db.hir_defs(pkg);
+
+    (db, pkg)
+}
+
+fn diagnostics(db: &TestDatabase, pkg: Package) -> Vec<Diagnostic<BoxInternalError>> {
+    db.lookup_intern_package(pkg).errors.read().unwrap().clone()
+}
+
+fn messages(db: &TestDatabase, pkg: Package) -> Vec<String> {
+    diagnostics(db, pkg)
+        .iter()
+        .map(|diagnostic| diagnostic.message.to_string())
+        .collect()
+}
+
+#[test]
+fn usage_reports_the_misused_linear_and_erased_parameters() {
+    let source = "twice (1 a : Nat) : Nat\n\
+                  twice a = add a a\n\
+                  \n\
+                  drop (1 b : Nat) : Nat\n\
+                  drop b = Zero\n\
+                  \n\
+                  leak (0 c : Nat) : Nat\n\
+                  leak c = c\n\
+                  \n\
+                  once (1 d : Nat) : Nat\n\
+                  once d = d\n";
+    let (db, pkg) = check(source);
+    let messages = messages(&db, pkg);
+
+    let reported = |message: &str| messages.iter().any(|found| found.contains(message));
+    assert!(reported("`a` is used more than once"), "{messages:#?}");
+    assert!(reported("`b` is dropped"), "{messages:#?}");
+    assert!(reported("erased value `c`"), "{messages:#?}");
+    assert!(!reported("`d`"), "{messages:#?}");
+}
+
+#[test]
+fn usage_reports_the_dropped_value_in_the_equation_that_drops_it() {
+    let source = "keep (1 b : Nat) (n : Nat) : Nat\n\
+                  keep b Zero = b\n\
+                  keep b (Succ n) = n\n";
+    let (db, pkg) = check(source);
+
+    let dropped = diagnostics(&db, pkg)
+        .into_iter()
+        .filter(|diagnostic| diagnostic.message.to_string().contains("`b` is dropped"))
+        .map(|diagnostic| diagnostic.message.span.range.start())
+        .collect::<Vec<_>>();
+
+    assert_eq!(dropped, [source.rfind("b (Succ").unwrap()]);
+}
+
+#[test]
+fn erase_keeps_the_implicit_parameters_of_a_generic_function() {
+    let source = "pick [a: Set] (0 t : Set) (1 x : a) : a\n\
+                  pick t x = x\n";
+    let (db, pkg) = check(source);
+
+    let group = db.hir_find_fn(pkg, "pick".into()).unwrap();
+    let group = db.hir_erase(group.into());
+
+    let parameters = group
+        .signature
+        .parameters
+        .iter()
+        .map(|parameter| match parameter {
+            HirParameterKind::Explicit(data) | HirParameterKind::Implicit(data) => {
+                db.lookup_intern_name(data.name)
+            }
+            _ => String::new(),
+        })
+        .collect::<Vec<_>>();
+    let patterns = group
+        .declarations
+        .iter()
+        .flat_map(|declaration| declaration.patterns.iter())
+        .map(|pattern| match db.lookup_intern_pattern(*pattern).kind {
+            HirPatternKind::Name(pattern) => db.lookup_intern_name(pattern.name),
+            _ => String::new(),
+        })
+        .collect::<Vec<_>>();
+
+    // The linear `x` is checked against its own pattern, not against the one of `t`.
+    let messages = messages(&db, pkg);
+    let reported = |name: &str| messages.iter().any(|found| found.contains(name));
+    assert!(!reported("`x`") && !reported("`t`"), "{messages:#?}");
+    assert_eq!(parameters, ["a", "x"]);
+    assert_eq!(patterns, ["x"]);
+}
+
+#[test]
+fn lowering_reports_the_invalid_multiplicities() {
+    let source = "pick (2 w : Nat) (1.0 v : Nat) (1 x : Nat) : Nat\n\
+                  pick w v x = x\n";
+    let (db, pkg) = check(source);
+
+    // The quantities are only reported by the lowering, the parser reads any number.
+    let invalid = diagnostics(&db, pkg)
+        .into_iter()
+        .filter(|diagnostic| {
+            diagnostic
+                .message
+                .to_string()
+                .contains("invalid multiplicity")
+        })
+        .map(|diagnostic| diagnostic.message.span.range.start())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        invalid,
+        [source.find("(2 w").unwrap(), source.find("(1.0 v").unwrap()]
+    );
+}
+
+#[test]
+fn signature_bodies_match_only_the_explicit_parameters() {
+    let source = "pick [a: Set] (x : a) : a {\n\
+                  \x20 x\n\
+                  }\n";
+    let (db, pkg) = check(source);
+
+    // The implicit `a` isn't passed in the calls, so the body has no pattern for it.
+    let group = db.hir_find_fn(pkg, "pick".into()).unwrap();
+    let patterns = group
+        .declarations
+        .iter()
+        .flat_map(|declaration| declaration.patterns.iter())
+        .map(|pattern| match db.lookup_intern_pattern(*pattern).kind {
+            HirPatternKind::Name(pattern) => db.lookup_intern_name(pattern.name),
+            _ => String::new(),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(patterns, ["x"]);
+}
+
+#[test]
+fn holes_type_the_locals_with_the_explicit_parameters() {
+    let source = "pick [a: Set] (x : a) : a\n\
+                  pick x = ? x\n";
+    let (db, pkg) = check(source);
+    let messages = messages(&db, pkg);
+
+    let hole = messages
+        .iter()
+        .find(|message| message.starts_with("found hole"))
+        .unwrap();
+    assert!(hole.contains("locals: x : a\n"), "{hole}");
+}
+
+#[test]
+fn docs_are_lowered_for_the_groups_and_the_variants() {
+    let source = "/// The flags.\n\
+                  enum Flag {\n\
+                  \x20 /// It's set.\n\
+                  \x20 On : Flag,\n\
+                  \x20 Off : Flag\n\
+                  }\n\
+                  \n\
+                  /// Flips the flag.\n\
+                  /// It's its own inverse.\n\
+                  flip : Flag -> Flag\n\
+                  \n\
+                  /// The equations are documented by the signature.\n\
+                  flip f = f\n\
+                  \n\
+                  // A plain comment.\n\
+                  /// Without a signature.\n\
+                  off = Off\n";
+    let (db, pkg) = check(source);
+    let texts = |docs: &[HirDoc]| docs.iter().map(|doc| doc.text.clone()).collect::<Vec<_>>();
+
+    let flip = db.hir_find_fn(pkg, "flip".into()).unwrap();
+    assert_eq!(
+        texts(&flip.docs),
+        ["Flips the flag.", "It's its own inverse."]
+    );
+
+    let off = db.hir_find_fn(pkg, "off".into()).unwrap();
+    assert_eq!(texts(&off.docs), ["Without a signature."]);
+
+    let (enum_docs, variants) = db
+        .hir_defs(pkg)
+        .into_iter()
+        .find_map(|def| {
+            let data = db.lookup_intern_top_level(def);
+            match data.kind {
+                HirTopLevelKind::Enum(decl) => Some((data.docs, decl.variants)),
+                _ => None,
+            }
+        })
+        .unwrap();
+    assert_eq!(texts(&enum_docs), ["The flags."]);
+
+    let variant_docs = |name: &str| {
+        let (_, variant) = variants
+            .iter()
+            .find(|(variant, _)| db.lookup_intern_name(**variant) == name)
+            .unwrap();
+        texts(&variant.docs)
+    };
+    assert_eq!(variant_docs("On"), ["It's set."]);
+    assert_eq!(variant_docs("Off"), Vec::<String>::new());
+}
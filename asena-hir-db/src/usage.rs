@@ -0,0 +1,318 @@
+//! Checks the usage of the parameters with multiplicities: the linear values must be used exactly
+//! once in every branch, and the erased values can not be used at runtime.
+
+use asena_ast_db::package::HasDiagnostic;
+use asena_hir::{
+    expr::{
+        data::{HirBranch, HirCallee},
+        HirExpr, HirExprKind,
+    },
+    pattern::{HirPattern, HirPatternKind},
+    stmt::{HirStmtKind, HirStmtValue},
+    top_level::{data::HirMultiplicity, HirBindingGroup, HirTopLevel, HirTopLevelKind},
+    value::{instr::HirInstr, monads::HirMonad, HirValue, HirValueExpr, HirValueKind},
+    HirLoc, Name,
+};
+use asena_report::WithError;
+
+use crate::{db::HirDatabase, error::HirError::*};
+
+/// How many times a variable is used. The branches can use a variable a different number of
+/// times, so it's a range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Usage {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl Usage {
+    pub const NONE: Usage = Usage { min: 0, max: 0 };
+    pub const ONCE: Usage = Usage { min: 1, max: 1 };
+
+    /// The usage of a value followed by another.
+    pub fn then(self, other: Usage) -> Usage {
+        Usage {
+            min: self.min.saturating_add(other.min),
+            max: self.max.saturating_add(other.max),
+        }
+    }
+
+    /// The usage of a value, or another, like the branches of a match.
+    pub fn or(self, other: Usage) -> Usage {
+        Usage {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// The usage of a value that can be evaluated any number of times, like the body of a
+    /// lambda.
+    pub fn repeated(self) -> Usage {
+        Usage {
+            min: self.min,
+            max: if self.max == 0 { 0 } else { usize::MAX },
+        }
+    }
+}
+
+pub fn usage(db: &dyn HirDatabase, def: HirTopLevel) {
+    let data = db.lookup_intern_top_level(def);
+
+    match data.kind {
+        HirTopLevelKind::Error => {}
+        HirTopLevelKind::BindingGroup(group) => check_group(db, &group),
+        HirTopLevelKind::Enum(decl) => {
+            for group in decl.groups.iter() {
+                check_group(db, group);
+            }
+        }
+        HirTopLevelKind::Struct(decl) => {
+            for group in decl.groups.iter() {
+                check_group(db, group);
+            }
+        }
+        HirTopLevelKind::Instance(decl) => {
+            for group in decl.groups.iter() {
+                check_group(db, group);
+            }
+        }
+        HirTopLevelKind::Trait(decl) => {
+            for group in decl.groups.values() {
+                check_group(db, group);
+            }
+        }
+    }
+}
+
+/// Checks the variables bound by the patterns of the parameters that are not unrestricted, in
+/// every declaration of the group.
+fn check_group(db: &dyn HirDatabase, group: &HirBindingGroup) {
+    // The patterns only match the explicit parameters, the implicit ones aren't passed.
+    for (i, parameter) in group.signature.explicit_parameters().enumerate() {
+        let multiplicity = parameter.multiplicity();
+        if multiplicity == HirMultiplicity::Many {
+            continue;
+        }
+
+        for declaration in group.declarations.iter() {
+            let Some(pattern) = declaration.patterns.get(i) else {
+                continue;
+            };
+
+            // The dropped values are reported at the pattern of the parameter, in the equation
+            // that drops them.
+            let location = db.lookup_intern_pattern(*pattern).span;
+
+            let mut names = vec![];
+            collect_names(db, *pattern, &mut names);
+
+            for name in names {
+                let mut counter = UsageCounter::new(db, name);
+                let usage = counter.value(declaration.value);
+                let references = counter.references;
+                let name = db.lookup_intern_name(name);
+
+                match multiplicity {
+                    HirMultiplicity::Zero if usage.max > 0 => {
+                        references[0]
+                            .clone()
+                            .fail(ErasedValueUsedError(name))
+                            .push(db);
+                    }
+                    HirMultiplicity::One if usage.max > 1 => {
+                        let reference = references.get(1).unwrap_or(&references[0]);
+                        reference
+                            .clone()
+                            .fail(LinearValueUsedTwiceError(name))
+                            .push(db);
+                    }
+                    HirMultiplicity::One if usage.min == 0 => {
+                        location
+                            .clone()
+                            .fail(LinearValueDroppedError(name))
+                            .push(db);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Collects the variables bound by the pattern.
+pub(crate) fn collect_names(db: &dyn HirDatabase, pattern: HirPattern, out: &mut Vec<Name>) {
+    match db.lookup_intern_pattern(pattern).kind {
+        HirPatternKind::Name(pattern) => out.push(pattern.name),
+        HirPatternKind::Constructor(constructor) => {
+            for argument in constructor.arguments {
+                collect_names(db, argument, out);
+            }
+        }
+        HirPatternKind::List(list) => {
+            for item in list.items {
+                collect_names(db, item, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Counts the usages of a variable in a value, stopping when the variable is shadowed.
+struct UsageCounter<'db> {
+    db: &'db dyn HirDatabase,
+    name: Name,
+    references: Vec<HirLoc>,
+}
+
+impl<'db> UsageCounter<'db> {
+    fn new(db: &'db dyn HirDatabase, name: Name) -> Self {
+        Self {
+            db,
+            name,
+            references: vec![],
+        }
+    }
+
+    fn value(&mut self, value: HirValue) -> Usage {
+        let data = self.db.lookup_intern_value(value);
+
+        match data.kind {
+            HirValueKind::Error | HirValueKind::Unit => Usage::NONE,
+            HirValueKind::Expr(HirValueExpr(expr)) => self.expr(expr),
+            HirValueKind::Block(block) => {
+                let mut instructions = block.instructions.clone();
+
+                // The last expression statement is the value of the block too, so it should not
+                // be counted twice.
+                if let Some(last) = instructions.last() {
+                    let last = self.db.lookup_intern_stmt(*last);
+                    if let HirStmtKind::Value(HirStmtValue(value)) = last.kind {
+                        if value == block.value {
+                            instructions.pop();
+                        }
+                    }
+                }
+
+                let mut usage = Usage::NONE;
+                for instruction in instructions {
+                    match self.db.lookup_intern_stmt(instruction).kind {
+                        HirStmtKind::Error => {}
+                        HirStmtKind::Ask(stmt) => {
+                            usage = usage.then(self.value(stmt.value));
+                            if self.binds(stmt.pattern) {
+                                return usage;
+                            }
+                        }
+                        HirStmtKind::Let(stmt) => {
+                            usage = usage.then(self.value(stmt.value));
+                            if self.binds(stmt.pattern) {
+                                return usage;
+                            }
+                        }
+                        HirStmtKind::Return(stmt) => return usage.then(self.value(stmt.value)),
+                        HirStmtKind::Value(HirStmtValue(value)) => {
+                            usage = usage.then(self.value(value));
+                        }
+                    }
+                }
+
+                usage.then(self.value(block.value))
+            }
+            HirValueKind::Monad(HirMonad::PureUnit) => Usage::NONE,
+            HirValueKind::Monad(HirMonad::Pure(value)) => self.value(value),
+            HirValueKind::Monad(HirMonad::Bind(name, value, then)) => {
+                let usage = self.value(value);
+                if name == self.name {
+                    return usage;
+                }
+
+                usage.then(self.value(then))
+            }
+            HirValueKind::Instr(instr) => match instr {
+                HirInstr::Null => Usage::NONE,
+                HirInstr::Variable(name) if name == self.name => {
+                    self.references.push(data.span);
+                    Usage::ONCE
+                }
+                HirInstr::Variable(_) => Usage::NONE,
+                HirInstr::Let(_, value)
+                | HirInstr::ObjectClone(value)
+                | HirInstr::ObjectDrop(value) => self.value(value),
+                HirInstr::Block(block) => {
+                    let mut usage = Usage::NONE;
+                    for instruction in block.instructions {
+                        usage = usage.then(self.value(instruction));
+                    }
+
+                    usage.then(self.value(block.value))
+                }
+            },
+        }
+    }
+
+    fn expr(&mut self, expr: HirExpr) -> Usage {
+        let data = self.db.lookup_intern_expr(expr);
+
+        match data.kind {
+            HirExprKind::Error | HirExprKind::Unit | HirExprKind::This => Usage::NONE,
+            HirExprKind::Literal(_) => Usage::NONE,
+            HirExprKind::Reference(reference) if reference.name == self.name => {
+                self.references.push(data.span);
+                Usage::ONCE
+            }
+            HirExprKind::Reference(_) => Usage::NONE,
+            HirExprKind::Group(group) => self.value(group.value),
+            HirExprKind::Help(help) => self.value(help.value),
+            HirExprKind::Ann(ann) => self.value(ann.value),
+            HirExprKind::Lam(lam) if lam.parameters.contains(&self.name) => Usage::NONE,
+            HirExprKind::Lam(lam) => self.value(lam.value).repeated(),
+            HirExprKind::Array(array) => {
+                let mut usage = Usage::NONE;
+                for item in array.items {
+                    usage = usage.then(self.value(item));
+                }
+
+                usage
+            }
+            HirExprKind::Call(call) => {
+                let mut usage = match call.callee {
+                    HirCallee::Value(callee) => self.value(callee),
+                    _ => Usage::NONE,
+                };
+
+                for argument in call.arguments {
+                    usage = usage.then(self.value(argument));
+                }
+
+                match call.as_dsl {
+                    Some(dsl) if !dsl.parameters.contains(&self.name) => {
+                        usage.then(self.value(dsl.value).repeated())
+                    }
+                    _ => usage,
+                }
+            }
+            HirExprKind::Match(expr) => {
+                let usage = self.value(expr.scrutinee);
+                let cases = expr
+                    .cases
+                    .iter()
+                    .map(|case| match case.value {
+                        _ if self.binds(case.pattern) => Usage::NONE,
+                        HirBranch::Error => Usage::NONE,
+                        HirBranch::Expr(value) | HirBranch::Block(value) => self.value(value),
+                    })
+                    .reduce(Usage::or)
+                    .unwrap_or(Usage::NONE);
+
+                usage.then(cases)
+            }
+        }
+    }
+
+    /// If the pattern binds the variable again, shadowing it.
+    fn binds(&self, pattern: HirPattern) -> bool {
+        let mut names = vec![];
+        collect_names(self.db, pattern, &mut names);
+        names.contains(&self.name)
+    }
+}
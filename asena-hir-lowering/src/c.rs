@@ -0,0 +1,1218 @@
+//! Portable C backend, it emits a single C translation unit from the HIR of a package, that should
+//! be linked against the `asena-runtime` static library. It's an alternative to the LLVM backend,
+//! for the platforms where LLVM is not available.
+//!
+//! Every value is an `asena_object_t *`, and the functions are lowered to C functions with a
+//! parameter for each pattern. The partial applications, lambdas and unknown callees go through
+//! the runtime closures.
+
+use std::{collections::BTreeMap, fmt::Write, sync::Arc};
+
+use asena_ast_db::package::Package;
+use asena_hir::{
+    expr::{
+        data::{HirBranch, HirCallee, HirMatchCase},
+        HirExpr, HirExprCall, HirExprKind, HirExprMatch,
+    },
+    hir_type::{
+        data::{HirTypeArgument, HirTypeFunction},
+        HirType, HirTypeKind,
+    },
+    literal::{HirISign, HirISize, HirLiteral},
+    pattern::{HirPattern, HirPatternKind},
+    stmt::{HirStmt, HirStmtKind, HirStmtValue},
+    top_level::{HirBindingGroup, HirTopLevelKind},
+    value::{instr::HirInstr, monads::HirMonad, HirValue, HirValueExpr, HirValueKind},
+    HirLoc, Name,
+};
+use asena_runtime::ffi::C_HEADER;
+use itertools::Itertools;
+
+use crate::{db::LlirDatabase, line_of, LlirConfig, LlirErr};
+
+/// The entry points looked up, in order, to generate the C `main` function.
+pub const ENTRY_POINTS: &[&str] = &["Main", "main"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CPackage {
+    pub name: String,
+    pub source: String,
+}
+
+pub struct CLowering<'db> {
+    pub db: &'db dyn LlirDatabase,
+    pub config: LlirConfig,
+
+    callables: BTreeMap<String, Callable>,
+    lambdas: Vec<(String, String)>,
+    frame: Frame,
+    counter: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Callable {
+    name: String,
+    symbol: String,
+    arity: usize,
+    /// The positions of the erased arguments in the calls, they are not passed to the function.
+    erased: Vec<usize>,
+    body: CallableBody,
+    span: HirLoc,
+}
+
+impl Callable {
+    /// The number of arguments in the source, including the erased ones.
+    fn source_arity(&self) -> usize {
+        self.arity + self.erased.len()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum CallableBody {
+    Group(HirBindingGroup),
+    Constructor,
+}
+
+/// The state of the C function being emitted.
+#[derive(Default)]
+struct Frame {
+    body: String,
+    locals: Vec<(String, String)>,
+    indent: usize,
+}
+
+/// Lowers the package into C, the `main` function runs the `entry` binding, or the first of the
+/// [ENTRY_POINTS] when there is no entry.
+pub fn c_package(
+    db: &dyn LlirDatabase,
+    pkg: Package,
+    config: LlirConfig,
+    entry: Option<String>,
+) -> Result<Arc<CPackage>, LlirErr> {
+    let data = db.lookup_intern_package(pkg);
+
+    // Discovery step, the declarations of the package shadow the ones of the dependencies, like
+    // the standard library.
+    let mut lowering = CLowering::new(db, config);
+    let packages = data.dependencies.iter().copied().chain([pkg]);
+    for pkg in packages {
+        for def in db.hir_defs(pkg) {
+            let def = db.lookup_intern_top_level(def);
+            lowering.discover(def.kind, def.span);
+        }
+    }
+
+    let main = match entry {
+        Some(entry) => lowering
+            .callables
+            .get(&entry)
+            .cloned()
+            .ok_or_else(|| LlirErr::EntryNotFound(entry, data.name.clone()))?,
+        None => ENTRY_POINTS
+            .iter()
+            .find_map(|name| lowering.callables.get(*name).cloned())
+            .ok_or_else(|| LlirErr::MainNotFound(data.name.clone()))?,
+    };
+
+    Ok(Arc::new(CPackage {
+        name: data.name.clone(),
+        source: lowering.emit(&main),
+    }))
+}
+
+impl<'db> CLowering<'db> {
+    pub fn new(db: &'db dyn LlirDatabase, config: LlirConfig) -> Self {
+        Self {
+            db,
+            config,
+            callables: BTreeMap::new(),
+            lambdas: vec![],
+            frame: Frame::default(),
+            counter: 0,
+        }
+    }
+
+    /// Registers the functions and constructors of the top level declaration, the instances and
+    /// traits are not supported yet, as there is no dictionary passing.
+    pub fn discover(&mut self, kind: HirTopLevelKind, span: HirLoc) {
+        match kind {
+            HirTopLevelKind::Error => {}
+            HirTopLevelKind::Instance(_) | HirTopLevelKind::Trait(_) => {
+                log::debug!("the C backend does not support instances and traits yet");
+            }
+            HirTopLevelKind::BindingGroup(group) => self.discover_group(group, &span),
+            HirTopLevelKind::Struct(data) => {
+                for group in data.groups {
+                    self.discover_group(group, &span);
+                }
+            }
+            HirTopLevelKind::Enum(data) => {
+                for variant in data.variants.values() {
+                    let name = self.db.lookup_intern_name(variant.name);
+                    let arity = self.type_arity(variant.variant_type);
+
+                    self.callables.insert(
+                        name.clone(),
+                        Callable {
+                            symbol: format!("asena_ctor_{}", mangle(&name)),
+                            name,
+                            arity,
+                            erased: vec![],
+                            body: CallableBody::Constructor,
+                            span: span.clone(),
+                        },
+                    );
+                }
+
+                for group in data.groups {
+                    self.discover_group(group, &span);
+                }
+            }
+        }
+    }
+
+    fn discover_group(&mut self, group: HirBindingGroup, span: &HirLoc) {
+        // Signatures without body are external functions, they are resolved by the runtime.
+        if group.declarations.is_empty() {
+            return;
+        }
+
+        let erased = group.signature.erased_parameters();
+        let group = self.db.hir_rc(group.into());
+        let group = self.db.hir_erase(group.into());
+        let name = self.db.lookup_intern_name(group.signature.name);
+        // The curried parameters of the return type are parameters too, like the ones of
+        // `add : Nat -> Nat -> Nat`. The equations can have more patterns than the signature, like
+        // the ones without signature, so every pattern gets a parameter.
+        let signature_arity = group.signature.explicit_parameters().count()
+            + group
+                .signature
+                .return_type
+                .map_or(0, |return_type| self.type_arity(return_type));
+        let arity = group
+            .declarations
+            .iter()
+            .map(|declaration| declaration.patterns.len())
+            .fold(signature_arity, usize::max);
+
+        self.callables.insert(
+            name.clone(),
+            Callable {
+                symbol: format!("asena_fn_{}", mangle(&name)),
+                name,
+                arity,
+                erased,
+                body: CallableBody::Group(group),
+                span: span.clone(),
+            },
+        );
+    }
+
+    /// Emits the translation unit, with `main` as the entry point.
+    fn emit(mut self, main: &Callable) -> String {
+        let callables = self.callables.values().cloned().collect_vec();
+
+        let mut prototypes = String::new();
+        let mut definitions = String::new();
+        for callable in callables.iter() {
+            writeln!(prototypes, "static {};", signature(callable)).unwrap();
+            writeln!(
+                prototypes,
+                "static {};",
+                closure_signature(&thunk(callable))
+            )
+            .unwrap();
+
+            definitions.push_str(&self.callable(callable));
+        }
+
+        let mut out = String::new();
+        writeln!(out, "/* Generated by the asena C backend, do not edit. */").unwrap();
+        writeln!(out).unwrap();
+        out.push_str(C_HEADER);
+        writeln!(out).unwrap();
+        out.push_str(&prototypes);
+        for (symbol, _) in self.lambdas.iter() {
+            writeln!(out, "static {};", closure_signature(symbol)).unwrap();
+        }
+        writeln!(out).unwrap();
+        for (_, definition) in self.lambdas.iter() {
+            out.push_str(definition);
+        }
+        out.push_str(&definitions);
+
+        let arguments = (0..main.arity).map(|_| "asena_rt_unit()").join(", ");
+        writeln!(out, "int main(int argc, char **argv) {{").unwrap();
+        writeln!(out, "  asena_rt_init(argc, argv);").unwrap();
+        writeln!(out, "  {}({arguments});", main.symbol).unwrap();
+        writeln!(out, "  return asena_rt_exit();").unwrap();
+        writeln!(out, "}}").unwrap();
+
+        out
+    }
+
+    fn callable(&mut self, callable: &Callable) -> String {
+        let parameters = (0..callable.arity).map(|i| format!("a{i}")).collect_vec();
+
+        let mut out = String::new();
+        if let Some(directive) = self.line_directive(&callable.span) {
+            writeln!(out, "{directive}").unwrap();
+        }
+        writeln!(out, "static {} {{", signature(callable)).unwrap();
+        match callable.body {
+            CallableBody::Constructor => {
+                let name = c_string(&callable.name);
+                let fields = c_array(&parameters);
+
+                writeln!(
+                    out,
+                    "  return asena_rt_construct({name}, {}, {fields});",
+                    parameters.len()
+                )
+                .unwrap();
+            }
+            CallableBody::Group(ref group) => {
+                self.frame = Frame {
+                    indent: 1,
+                    ..Frame::default()
+                };
+
+                let declarations = group
+                    .declarations
+                    .iter()
+                    .sorted_by_key(|declaration| self.value_order(declaration.value))
+                    .cloned()
+                    .collect_vec();
+
+                for declaration in declarations {
+                    let next = self.fresh("next");
+
+                    self.block(|this| {
+                        for (pattern, parameter) in declaration.patterns.iter().zip(&parameters) {
+                            this.pattern(*pattern, parameter, &next);
+                        }
+
+                        // The equations with less patterns than parameters evaluate to a
+                        // function, that is applied to the remaining arguments.
+                        let value = this.value(declaration.value);
+                        let remaining = parameters
+                            .get(declaration.patterns.len()..)
+                            .unwrap_or_default();
+                        let value = match remaining.len() {
+                            0 => value,
+                            length => this.temp(&format!(
+                                "asena_rt_apply({value}, {length}, {})",
+                                c_array(remaining)
+                            )),
+                        };
+                        this.line(&format!("return {value};"));
+                    });
+                    self.label(&next);
+                }
+
+                let message = format!("non-exhaustive patterns in `{}`", callable.name);
+                self.line(&format!("return asena_rt_panic({});", c_string(&message)));
+
+                out.push_str(&std::mem::take(&mut self.frame).body);
+            }
+        }
+        writeln!(out, "}}").unwrap();
+        writeln!(out).unwrap();
+
+        let arguments = (0..callable.source_arity())
+            .filter(|i| !callable.erased.contains(i))
+            .map(|i| format!("args[{i}]"))
+            .join(", ");
+        writeln!(out, "static {} {{", closure_signature(&thunk(callable))).unwrap();
+        writeln!(out, "  (void) env;").unwrap();
+        if callable.arity == 0 {
+            writeln!(out, "  (void) args;").unwrap();
+        }
+        writeln!(out, "  return {}({arguments});", callable.symbol).unwrap();
+        writeln!(out, "}}").unwrap();
+        writeln!(out).unwrap();
+
+        out
+    }
+
+    fn value(&mut self, value: HirValue) -> String {
+        let data = self.db.lookup_intern_value(value);
+
+        match data.kind {
+            HirValueKind::Error => self.panic("invalid value"),
+            HirValueKind::Unit => self.temp("asena_rt_unit()"),
+            HirValueKind::Expr(HirValueExpr(expr)) => self.expr(expr),
+            HirValueKind::Block(block) => self.scoped(|this| {
+                let mut instructions = block.instructions.clone();
+
+                // The last expression statement is the value of the block too, so it should not
+                // be evaluated twice.
+                if let Some(last) = instructions.last() {
+                    let last = this.db.lookup_intern_stmt(*last);
+                    if let HirStmtKind::Value(HirStmtValue(value)) = last.kind {
+                        if value == block.value {
+                            instructions.pop();
+                        }
+                    }
+                }
+
+                for instruction in instructions {
+                    this.stmt(instruction);
+                }
+
+                this.value(block.value)
+            }),
+            HirValueKind::Monad(HirMonad::PureUnit) => self.temp("asena_rt_unit()"),
+            HirValueKind::Monad(HirMonad::Pure(value)) => self.value(value),
+            HirValueKind::Monad(HirMonad::Bind(name, value, then)) => {
+                let value = self.value(value);
+
+                self.scoped(|this| {
+                    this.bind(name, &value);
+                    this.value(then)
+                })
+            }
+            HirValueKind::Instr(HirInstr::Null) => self.temp("asena_rt_unit()"),
+            HirValueKind::Instr(HirInstr::Let(name, value)) => {
+                let value = self.value(value);
+                self.bind(name, &value);
+                self.temp("asena_rt_unit()")
+            }
+            HirValueKind::Instr(HirInstr::Variable(name)) => self.reference(name),
+            HirValueKind::Instr(HirInstr::Block(block)) => self.scoped(|this| {
+                for instruction in block.instructions {
+                    this.value(instruction);
+                }
+
+                this.value(block.value)
+            }),
+            HirValueKind::Instr(HirInstr::ObjectClone(value)) => {
+                let value = self.value(value);
+                self.temp(&format!("asena_rt_clone({value})"))
+            }
+            HirValueKind::Instr(HirInstr::ObjectDrop(value)) => {
+                let value = self.value(value);
+                self.temp(&format!("asena_rt_drop({value})"))
+            }
+        }
+    }
+
+    fn stmt(&mut self, stmt: HirStmt) {
+        let data = self.db.lookup_intern_stmt(stmt);
+
+        match data.kind {
+            HirStmtKind::Error => {
+                self.panic("invalid statement");
+            }
+            // The effects are run eagerly, so asking a value is the same as binding it.
+            HirStmtKind::Ask(stmt) => {
+                let value = self.value(stmt.value);
+                self.irrefutable(stmt.pattern, &value);
+            }
+            HirStmtKind::Let(stmt) => {
+                let value = self.value(stmt.value);
+                self.irrefutable(stmt.pattern, &value);
+            }
+            HirStmtKind::Return(stmt) => {
+                let value = self.value(stmt.value);
+                self.line(&format!("return {value};"));
+            }
+            HirStmtKind::Value(HirStmtValue(value)) => {
+                self.value(value);
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: HirExpr) -> String {
+        let data = self.db.lookup_intern_expr(expr);
+
+        match data.kind {
+            HirExprKind::Error => self.panic("invalid expression"),
+            HirExprKind::Unit => self.temp("asena_rt_unit()"),
+            HirExprKind::This => self.local("self"),
+            HirExprKind::Group(group) => self.value(group.value),
+            HirExprKind::Literal(literal) => self.literal(literal.0),
+            HirExprKind::Reference(reference) => self.reference(reference.name),
+            HirExprKind::Call(call) => self.call(call),
+            HirExprKind::Match(expr) => self.r#match(expr),
+            HirExprKind::Help(_) => self.panic("reached a typed hole"),
+            HirExprKind::Ann(ann) => self.value(ann.value),
+            HirExprKind::Lam(lam) => self.lambda(&lam.parameters, lam.value),
+            HirExprKind::Array(array) => {
+                let items = array
+                    .items
+                    .into_iter()
+                    .map(|item| self.value(item))
+                    .collect_vec();
+
+                self.temp(&format!(
+                    "asena_rt_array({}, {})",
+                    items.len(),
+                    c_array(&items)
+                ))
+            }
+        }
+    }
+
+    fn literal(&mut self, literal: HirLiteral) -> String {
+        match literal {
+            HirLiteral::Error => self.panic("invalid literal"),
+            HirLiteral::Int(value, HirISize::U1, _) => {
+                self.temp(&format!("asena_rt_bool({value})"))
+            }
+            HirLiteral::Int(value, _, HirISign::Signed) => {
+                self.temp(&format!("asena_rt_int(INT64_C({}))", value as i64))
+            }
+            HirLiteral::Int(value, _, HirISign::Unsigned) => {
+                self.temp(&format!("asena_rt_int(INT64_C({value}))"))
+            }
+            HirLiteral::Decimal(_, decimal) => {
+                let value = format!("{}.{}", decimal.integer, decimal.decimal);
+                self.temp(&format!("asena_rt_float({value})"))
+            }
+            HirLiteral::String(string) => {
+                self.temp(&format!("asena_rt_string({})", c_string(&string.value)))
+            }
+        }
+    }
+
+    fn reference(&mut self, name: Name) -> String {
+        let name = self.db.lookup_intern_name(name);
+
+        self.local(&name)
+    }
+
+    /// Resolves the name, in order: locals, functions and constructors of the package, and the
+    /// runtime builtins.
+    fn local(&mut self, name: &str) -> String {
+        if let Some((_, local)) = self.frame.locals.iter().rev().find(|(n, _)| n == name) {
+            return local.clone();
+        }
+
+        match self.resolve(name).cloned() {
+            Some(callable) if callable.source_arity() == 0 => {
+                self.temp(&format!("{}()", callable.symbol))
+            }
+            Some(callable) => self.temp(&format!(
+                "asena_rt_closure({}, {}, 0, NULL)",
+                thunk(&callable),
+                callable.source_arity()
+            )),
+            None => self.temp(&format!("asena_rt_global({})", c_string(name))),
+        }
+    }
+
+    fn call(&mut self, call: HirExprCall) -> String {
+        let operator = match call.callee {
+            HirCallee::Add | HirCallee::IAdd => "asena_rt_add",
+            HirCallee::Sub | HirCallee::ISub => "asena_rt_sub",
+            HirCallee::Mul | HirCallee::IMul => "asena_rt_mul",
+            HirCallee::Div | HirCallee::IDiv => "asena_rt_div",
+            HirCallee::Do => {
+                let mut last = None;
+                for argument in call.arguments {
+                    last = Some(self.value(argument));
+                }
+
+                return last.unwrap_or_else(|| self.temp("asena_rt_unit()"));
+            }
+            HirCallee::Value(callee) => return self.apply(callee, call),
+        };
+
+        let arguments = call
+            .arguments
+            .into_iter()
+            .map(|argument| self.value(argument))
+            .collect_vec();
+
+        match arguments.as_slice() {
+            [lhs, rhs] => self.temp(&format!("{operator}({lhs}, {rhs})")),
+            _ => self.panic("invalid number of operands"),
+        }
+    }
+
+    /// Applies the callee, flattening the application spine. If the head is a known function,
+    /// and there is enough arguments, it's called directly, without allocating closures.
+    fn apply(&mut self, callee: HirValue, call: HirExprCall) -> String {
+        let mut head = callee;
+        let mut spine = call.arguments;
+        while let Some(HirExprCall {
+            callee: HirCallee::Value(callee),
+            arguments,
+            as_dsl: None,
+        }) = self.as_call(head)
+        {
+            spine = arguments.into_iter().chain(spine).collect();
+            head = callee;
+        }
+
+        let known = self.as_reference(head).and_then(|name| {
+            let is_local = self.frame.locals.iter().any(|(local, _)| *local == name);
+            if is_local {
+                return None;
+            }
+
+            self.resolve(&name).cloned()
+        });
+
+        let callee = match known {
+            Some(ref callable)
+                if callable.source_arity() > 0 && spine.len() >= callable.source_arity() =>
+            {
+                // The erased arguments are only used at the type level, so they are not even
+                // evaluated.
+                spine = spine
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(i, _)| !callable.erased.contains(i))
+                    .map(|(_, argument)| argument)
+                    .collect();
+
+                None
+            }
+            _ => Some(self.value(head)),
+        };
+
+        let mut arguments = spine
+            .into_iter()
+            .map(|argument| self.value(argument))
+            .collect_vec();
+
+        if let Some(dsl) = call.as_dsl {
+            arguments.push(self.lambda(&dsl.parameters, dsl.value));
+        }
+
+        let (callee, arguments) = match (callee, known) {
+            (Some(callee), _) => (callee, arguments),
+            (None, Some(callable)) => {
+                let rest = arguments.split_off(callable.arity);
+                let callee = self.temp(&format!("{}({})", callable.symbol, arguments.join(", ")));
+
+                (callee, rest)
+            }
+            (None, None) => unreachable!(),
+        };
+
+        if arguments.is_empty() {
+            return callee;
+        }
+
+        self.temp(&format!(
+            "asena_rt_apply({callee}, {}, {})",
+            arguments.len(),
+            c_array(&arguments)
+        ))
+    }
+
+    fn r#match(&mut self, expr: HirExprMatch) -> String {
+        let scrutinee = self.value(expr.scrutinee);
+        let result = self.fresh("t");
+        let done = self.fresh("matched");
+
+        self.line(&format!("asena_object_t *{result} = NULL;"));
+
+        let cases = expr
+            .cases
+            .into_iter()
+            .sorted_by_key(|case| self.case_order(case))
+            .collect_vec();
+
+        for case in cases {
+            let next = self.fresh("case");
+
+            self.block(|this| {
+                this.pattern(case.pattern, &scrutinee, &next);
+
+                let value = match case.value {
+                    HirBranch::Error => this.panic("invalid branch"),
+                    HirBranch::Expr(value) | HirBranch::Block(value) => this.value(value),
+                };
+
+                this.line(&format!("{result} = {value};"));
+                this.line(&format!("goto {done};"));
+            });
+            self.label(&next);
+        }
+
+        let message = c_string("non-exhaustive patterns in match");
+        self.line(&format!("{result} = asena_rt_panic({message});"));
+        self.label(&done);
+
+        result
+    }
+
+    /// Lifts the lambda to a C function, capturing every local in the scope.
+    fn lambda(&mut self, parameters: &[Name], value: HirValue) -> String {
+        let symbol = self.fresh("asena_lambda_");
+        let captured = self.frame.locals.clone();
+
+        let outer = std::mem::replace(
+            &mut self.frame,
+            Frame {
+                indent: 1,
+                ..Frame::default()
+            },
+        );
+
+        if captured.is_empty() {
+            self.line("(void) env;");
+        }
+        if parameters.is_empty() {
+            self.line("(void) args;");
+        }
+        for (i, (name, _)) in captured.iter().enumerate() {
+            self.bind_local(name, &format!("env[{i}]"));
+        }
+        for (i, name) in parameters.iter().enumerate() {
+            self.bind(*name, &format!("args[{i}]"));
+        }
+
+        let value = self.value(value);
+        self.line(&format!("return {value};"));
+
+        let frame = std::mem::replace(&mut self.frame, outer);
+        let definition = format!(
+            "static {} {{\n{}}}\n\n",
+            closure_signature(&symbol),
+            frame.body
+        );
+        self.lambdas.push((symbol.clone(), definition));
+
+        let env = captured.into_iter().map(|(_, local)| local).collect_vec();
+
+        self.temp(&format!(
+            "asena_rt_closure({symbol}, {}, {}, {})",
+            parameters.len(),
+            env.len(),
+            c_array(&env)
+        ))
+    }
+
+    /// Emits the tests of the pattern against the `scrutinee`, jumping to `fail` if it does not
+    /// match, and binds the variables of the pattern.
+    fn pattern(&mut self, pattern: HirPattern, scrutinee: &str, fail: &str) {
+        let data = self.db.lookup_intern_pattern(pattern);
+
+        match data.kind {
+            HirPatternKind::Error => self.line(&format!("goto {fail};")),
+            HirPatternKind::Wildcard | HirPatternKind::Spread | HirPatternKind::Unit => {}
+            HirPatternKind::This => self.bind_local("self", scrutinee),
+            HirPatternKind::Name(pattern) => self.bind(pattern.name, scrutinee),
+            HirPatternKind::Literal(literal) => {
+                let test = match literal.0 {
+                    HirLiteral::Error => "0".to_string(),
+                    HirLiteral::Int(value, _, HirISign::Signed) => {
+                        format!("asena_rt_eq_int({scrutinee}, INT64_C({}))", value as i64)
+                    }
+                    HirLiteral::Int(value, _, HirISign::Unsigned) => {
+                        format!("asena_rt_eq_int({scrutinee}, INT64_C({value}))")
+                    }
+                    HirLiteral::Decimal(_, decimal) => format!(
+                        "asena_rt_eq_float({scrutinee}, {}.{})",
+                        decimal.integer, decimal.decimal
+                    ),
+                    HirLiteral::String(string) => format!(
+                        "asena_rt_eq_string({scrutinee}, {})",
+                        c_string(&string.value)
+                    ),
+                };
+
+                self.line(&format!("if (!{test}) goto {fail};"));
+            }
+            HirPatternKind::Constructor(constructor) => {
+                let name = self.db.lookup_intern_name(constructor.constructor_name);
+                let name = self
+                    .resolve(&name)
+                    .map(|callable| callable.name.clone())
+                    .unwrap_or(name);
+
+                self.line(&format!(
+                    "if (!asena_rt_is_constructor({scrutinee}, {}, {})) goto {fail};",
+                    c_string(&name),
+                    constructor.arguments.len()
+                ));
+
+                for (i, argument) in constructor.arguments.into_iter().enumerate() {
+                    let field = self.temp(&format!("asena_rt_field({scrutinee}, {i})"));
+                    self.pattern(argument, &field, fail);
+                }
+            }
+            HirPatternKind::List(list) => {
+                let spread = list.items.iter().position(|item| {
+                    let data = self.db.lookup_intern_pattern(*item);
+                    matches!(data.kind, HirPatternKind::Spread)
+                });
+
+                let items = match spread {
+                    Some(spread) => {
+                        let test = format!("asena_rt_length({scrutinee}) < {spread}");
+                        self.line(&format!("if ({test}) goto {fail};"));
+                        &list.items[..spread]
+                    }
+                    None => {
+                        let length = list.items.len();
+                        let test = format!("asena_rt_length({scrutinee}) != {length}");
+                        self.line(&format!("if ({test}) goto {fail};"));
+                        &list.items[..]
+                    }
+                };
+
+                for (i, item) in items.iter().enumerate() {
+                    let field = self.temp(&format!("asena_rt_field({scrutinee}, {i})"));
+                    self.pattern(*item, &field, fail);
+                }
+            }
+        }
+    }
+
+    /// Binds a pattern that is expected to match, like in a `let` statement, panicking at runtime
+    /// if it does not.
+    fn irrefutable(&mut self, pattern: HirPattern, value: &str) {
+        let refuted = self.fresh("refuted");
+        let bound = self.fresh("bound");
+
+        self.pattern(pattern, value, &refuted);
+        self.line(&format!("goto {bound};"));
+        self.label(&refuted);
+
+        let message = c_string("refutable pattern in binding");
+        self.line(&format!("asena_rt_panic({message});"));
+        self.label(&bound);
+    }
+
+    fn bind(&mut self, name: Name, value: &str) {
+        let name = self.db.lookup_intern_name(name);
+
+        self.bind_local(&name, value);
+    }
+
+    fn bind_local(&mut self, name: &str, value: &str) {
+        let local = format!("{}_{}", self.fresh("l"), mangle(name));
+
+        self.line(&format!("asena_object_t *{local} = {value};"));
+        self.frame.locals.push((name.to_string(), local));
+    }
+
+    /// Runs `f` in a new scope, the locals bound inside of it are dropped at its end.
+    fn scoped<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let locals = self.frame.locals.len();
+        let value = f(self);
+        self.frame.locals.truncate(locals);
+        value
+    }
+
+    /// Emits a C block, with its own scope.
+    fn block<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.line("{");
+        self.frame.indent += 1;
+        let value = self.scoped(f);
+        self.frame.indent -= 1;
+        self.line("}");
+        value
+    }
+
+    fn resolve(&self, name: &str) -> Option<&Callable> {
+        self.callables.get(name).or_else(|| {
+            let local_name = name.rsplit('.').next()?;
+
+            self.callables.get(local_name)
+        })
+    }
+
+    fn as_call(&self, value: HirValue) -> Option<HirExprCall> {
+        match self.db.lookup_intern_expr(self.as_expr(value)?).kind {
+            HirExprKind::Call(call) => Some(call),
+            _ => None,
+        }
+    }
+
+    fn as_reference(&self, value: HirValue) -> Option<String> {
+        match self.db.lookup_intern_expr(self.as_expr(value)?).kind {
+            HirExprKind::Reference(reference) => Some(self.db.lookup_intern_name(reference.name)),
+            _ => None,
+        }
+    }
+
+    /// Unwraps the blocks without instructions, and the groups, that are created by the AST
+    /// lowering around every expression.
+    fn as_expr(&self, value: HirValue) -> Option<HirExpr> {
+        match self.db.lookup_intern_value(value).kind {
+            HirValueKind::Block(block) if block.instructions.is_empty() => {
+                self.as_expr(block.value)
+            }
+            HirValueKind::Expr(HirValueExpr(expr)) => match self.db.lookup_intern_expr(expr).kind {
+                HirExprKind::Group(group) => self.as_expr(group.value),
+                _ => Some(expr),
+            },
+            _ => None,
+        }
+    }
+
+    fn type_arity(&self, value: HirType) -> usize {
+        match self.db.lookup_intern_type(value).kind {
+            HirTypeKind::App(app) if matches!(app.callee, HirTypeFunction::Pi) => {
+                match app.arguments.last() {
+                    Some(HirTypeArgument::Type(codomain)) => 1 + self.type_arity(*codomain),
+                    Some(HirTypeArgument::Named(_, codomain)) => 1 + self.type_arity(*codomain),
+                    _ => 1,
+                }
+            }
+            _ => 0,
+        }
+    }
+
+    /// The declarations and cases are stored in hash sets, so the source order is recovered
+    /// through their locations.
+    fn value_order(&self, value: HirValue) -> usize {
+        self.db
+            .lookup_intern_value(value)
+            .span
+            .original
+            .range
+            .start()
+    }
+
+    fn case_order(&self, case: &HirMatchCase) -> (usize, usize) {
+        let pattern = self.db.lookup_intern_pattern(case.pattern);
+        let value = match case.value {
+            HirBranch::Error => 0,
+            HirBranch::Expr(value) | HirBranch::Block(value) => self.value_order(value),
+        };
+
+        (pattern.span.original.range.start(), value)
+    }
+
+    fn line_directive(&self, span: &HirLoc) -> Option<String> {
+        if !self.config.debug {
+            return None;
+        }
+
+        let path = span.original.file.as_ref()?;
+        let line = line_of(self.db, span)?;
+
+        Some(format!(
+            "#line {line} {}",
+            c_string(&path.to_string_lossy())
+        ))
+    }
+
+    fn panic(&mut self, message: &str) -> String {
+        self.temp(&format!("asena_rt_panic({})", c_string(message)))
+    }
+
+    fn temp(&mut self, value: &str) -> String {
+        let name = self.fresh("t");
+        self.line(&format!("asena_object_t *{name} = {value};"));
+        name
+    }
+
+    fn fresh(&mut self, prefix: &str) -> String {
+        self.counter += 1;
+        format!("{prefix}{}", self.counter)
+    }
+
+    fn label(&mut self, label: &str) {
+        self.line(&format!("{label}:;"));
+    }
+
+    fn line(&mut self, line: &str) {
+        let indent = "  ".repeat(self.frame.indent);
+        writeln!(self.frame.body, "{indent}{line}").unwrap();
+    }
+}
+
+fn signature(callable: &Callable) -> String {
+    let parameters = match callable.arity {
+        0 => "void".to_string(),
+        arity => (0..arity)
+            .map(|i| format!("asena_object_t *a{i}"))
+            .join(", "),
+    };
+
+    format!("asena_object_t *{}({parameters})", callable.symbol)
+}
+
+fn closure_signature(symbol: &str) -> String {
+    format!("asena_object_t *{symbol}(asena_object_t *const *env, asena_object_t *const *args)")
+}
+
+fn thunk(callable: &Callable) -> String {
+    format!("{}_thunk", callable.symbol)
+}
+
+fn c_array(items: &[String]) -> String {
+    if items.is_empty() {
+        return "NULL".into();
+    }
+
+    format!("(asena_object_t *[]){{{}}}", items.join(", "))
+}
+
+/// Escapes the string as a C string literal, the bytes that are not printable ASCII are written
+/// as octal escapes, to not depend on the encoding of the C compiler.
+pub fn c_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for byte in value.bytes() {
+        match byte {
+            b'"' => out.push_str("\\\""),
+            b'\\' => out.push_str("\\\\"),
+            b'\n' => out.push_str("\\n"),
+            b'\t' => out.push_str("\\t"),
+            b'\r' => out.push_str("\\r"),
+            b'?' => out.push_str("\\?"), // avoids trigraphs
+            0x20..=0x7e => out.push(byte as char),
+            _ => write!(out, "\\{byte:03o}").unwrap(),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Mangles the name into a valid C identifier: the underscores are doubled, and the other
+/// characters that are not ASCII alphanumeric are written as `_<hex>_`.
+pub fn mangle(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            '_' => out.push_str("__"),
+            c if c.is_ascii_alphanumeric() => out.push(c),
+            c => write!(out, "_{:X}_", c as u32).unwrap(),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use asena_ast_db::{db::AstDatabaseStorage, testing::load_main};
+    use asena_ast_lowering::db::AstLowerrerStorage;
+    use asena_ast_resolver::db::AstResolverStorage;
+    use asena_hir::interner::HirStorage;
+    use asena_hir_db::db::HirDatabaseStorage;
+    use asena_prec::db::PrecStorage;
+
+    use super::*;
+    use crate::db::LlirStorage;
+
+    #[salsa::database(
+        PrecStorage,
+        AstDatabaseStorage,
+        HirDatabaseStorage,
+        AstLowerrerStorage,
+        AstResolverStorage,
+        HirStorage,
+        LlirStorage
+    )]
+    struct TestDatabase {
+        storage: salsa::Storage<TestDatabase>,
+    }
+
+    impl salsa::Database for TestDatabase {}
+
+    /// Lowers a package into C, with `source` as its main module.
+    fn emit(source: &str, config: LlirConfig) -> String {
+        let mut db = TestDatabase {
+            storage: Default::default(),
+        };
+        let (pkg, _) = load_main(&mut db, source);
+
+        db.c_package(pkg, config, None).unwrap().source.clone()
+    }
+
+    #[test]
+    fn mangle_writes_the_names_as_c_identifiers() {
+        assert_eq!(mangle("twice"), "twice");
+        assert_eq!(mangle("plus_one"), "plus__one");
+        assert_eq!(mangle("IO.println"), "IO_2E_println");
+        assert_eq!(mangle("x'"), "x_27_");
+        assert_eq!(mangle("λ"), "_3BB_");
+
+        // The underscores are doubled, so the names can't be mistaken for the escapes.
+        assert_ne!(mangle("a_2E_b"), mangle("a.b"));
+    }
+
+    #[test]
+    fn c_string_escapes_the_literals() {
+        assert_eq!(c_string("hello"), r#""hello""#);
+        assert_eq!(c_string("say \"hi\"\n"), r#""say \"hi\"\n""#);
+        assert_eq!(c_string("a\\b\tc\r"), r#""a\\b\tc\r""#);
+        assert_eq!(c_string("??="), r#""\?\?=""#);
+        assert_eq!(c_string("é"), r#""\303\251""#);
+        assert_eq!(c_string("\0\x7f"), r#""\000\177""#);
+    }
+
+    #[test]
+    fn c_backend_emits_the_functions_of_the_package() {
+        let source = "enum Peano {\n  Z,\n  S : (prev: Peano) -> Peano,\n}\n\
+                      \n\
+                      sum : Peano -> Peano -> Peano\n\
+                      sum Z     m = m\n\
+                      sum (S n) m = S (sum n m)\n\
+                      \n\
+                      plus_one : Peano -> Peano\n\
+                      plus_one n = S n\n\
+                      \n\
+                      plus : Peano -> Peano -> Peano\n\
+                      plus = sum\n\
+                      \n\
+                      Main {\n  IO.println \"one? 1\"\n}\n";
+        let emitted = emit(source, LlirConfig::default());
+
+        // The functions of the standard library are emitted too, only the ones of the package are
+        // compared.
+        let definitions = [
+            "static asena_object_t *asena_fn_Main(",
+            "static asena_object_t *asena_fn_plus(",
+            "static asena_object_t *asena_fn_plus_thunk(",
+            "static asena_object_t *asena_fn_plus__one(",
+            "int main(",
+        ]
+        .iter()
+        .map(|signature| c_definition(&emitted, signature))
+        .collect::<String>();
+
+        assert_eq!(renumber(&definitions), EMITTED_C);
+    }
+
+    #[test]
+    fn line_directives_count_the_lines_before_the_span() {
+        // The bytes of `ω` are more than its characters, so the offsets aren't character counts.
+        let source = "omega : String\n\
+                      omega = \"ωωωωωωωωωωωωωωωωωωωωωωωωωωωωωωωωωωωωωωωω\"\n\
+                      \n\
+                      plus_one : Nat -> Nat\n\
+                      plus_one n = Succ n\n\
+                      \n\
+                      Main {\n  IO.println omega\n}\n";
+        let config = LlirConfig {
+            debug: true,
+            ..Default::default()
+        };
+        let emitted = emit(source, config);
+
+        let directive = "#line 4 \"<Local>/Main.ase\"\n\
+                         static asena_object_t *asena_fn_plus__one(";
+        assert!(emitted.contains(directive), "{emitted}");
+    }
+
+    #[test]
+    fn c_backend_gives_a_parameter_to_every_pattern() {
+        // `twice` has no signature, and `first` has more patterns than its signature.
+        let source = "twice f x = f (f x)\n\
+                      \n\
+                      first : Nat -> Nat\n\
+                      first n m = n\n\
+                      \n\
+                      Main {\n  IO.println \"twice\"\n}\n";
+        let emitted = emit(source, LlirConfig::default());
+
+        let twice = c_definition(&emitted, "static asena_object_t *asena_fn_twice(");
+        assert!(
+            twice.starts_with(
+                "static asena_object_t *asena_fn_twice(asena_object_t *a0, asena_object_t *a1) {"
+            ),
+            "{twice}"
+        );
+
+        let first = c_definition(&emitted, "static asena_object_t *asena_fn_first(");
+        assert!(
+            first.starts_with(
+                "static asena_object_t *asena_fn_first(asena_object_t *a0, asena_object_t *a1) {"
+            ),
+            "{first}"
+        );
+    }
+
+    /// The emitted C of the package, `plus` has no patterns, so it's applied to the arguments of
+    /// its signature.
+    const EMITTED_C: &str = r#"static asena_object_t *asena_fn_Main(void) {
+  {
+    asena_object_t *t0 = asena_rt_global("IO.println");
+    asena_object_t *t1 = asena_rt_string("one\? 1");
+    asena_object_t *t2 = asena_rt_apply(t0, 1, (asena_object_t *[]){t1});
+    return t2;
+  }
+  next3:;
+  return asena_rt_panic("non-exhaustive patterns in `Main`");
+}
+static asena_object_t *asena_fn_plus(asena_object_t *a0, asena_object_t *a1) {
+  {
+    asena_object_t *t4 = asena_rt_closure(asena_fn_sum_thunk, 2, 0, NULL);
+    asena_object_t *t5 = asena_rt_apply(t4, 2, (asena_object_t *[]){a0, a1});
+    return t5;
+  }
+  next6:;
+  return asena_rt_panic("non-exhaustive patterns in `plus`");
+}
+static asena_object_t *asena_fn_plus_thunk(asena_object_t *const *env, asena_object_t *const *args) {
+  (void) env;
+  return asena_fn_plus(args[0], args[1]);
+}
+static asena_object_t *asena_fn_plus__one(asena_object_t *a0) {
+  {
+    asena_object_t *l7_n = a0;
+    asena_object_t *t8 = asena_ctor_S(l7_n);
+    return t8;
+  }
+  next9:;
+  return asena_rt_panic("non-exhaustive patterns in `plus_one`");
+}
+int main(int argc, char **argv) {
+  asena_rt_init(argc, argv);
+  asena_fn_Main();
+  return asena_rt_exit();
+}
+"#;
+
+    /// The definition of the C function, from its signature to its closing brace.
+    fn c_definition(source: &str, signature: &str) -> String {
+        let mut definition = String::new();
+        let lines = source
+            .lines()
+            .skip_while(|line| !(line.starts_with(signature) && line.ends_with('{')));
+        for line in lines {
+            definition.push_str(line);
+            definition.push('\n');
+            if line == "}" {
+                break;
+            }
+        }
+
+        definition
+    }
+
+    /// Numbers the temporaries, locals and labels in the order they appear, as their numbers
+    /// depend on the functions emitted before them, like the ones of the standard library.
+    fn renumber(source: &str) -> String {
+        let mut numbers = HashMap::new();
+        let mut renumber_word = |word: &str| {
+            let suffix = word.trim_start_matches(|c: char| c.is_ascii_lowercase());
+            let prefix = &word[..word.len() - suffix.len()];
+            let digits = suffix.chars().take_while(char::is_ascii_digit).count();
+            if digits == 0 || !matches!(prefix, "t" | "l" | "next" | "case" | "matched") {
+                return word.to_string();
+            }
+
+            let next = numbers.len();
+            let number = *numbers.entry(suffix[..digits].to_string()).or_insert(next);
+            format!("{prefix}{number}{}", &suffix[digits..])
+        };
+
+        let mut out = String::new();
+        let mut word = String::new();
+        for c in source.chars() {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                word.push(c);
+                continue;
+            }
+            out.push_str(&renumber_word(&word));
+            out.push(c);
+            word.clear();
+        }
+        out.push_str(&renumber_word(&word));
+        out
+    }
+}
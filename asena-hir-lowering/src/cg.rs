@@ -1,11 +1,24 @@
-use asena_ast_db::package::Package;
-use inkwell::{basic_block::BasicBlock, builder::Builder, context::Context};
+use asena_ast_db::{package::Package, vfs::VfsFile};
+use asena_hir::{top_level::HirBindingGroup, HirLoc};
+use inkwell::{
+    basic_block::BasicBlock,
+    builder::Builder,
+    context::Context,
+    debug_info::{
+        debug_metadata_version, AsDIScope, DICompileUnit, DIFlags, DIFlagsConstants, DISubprogram,
+        DWARFEmissionKind, DWARFSourceLanguage, DebugInfoBuilder,
+    },
+    module::{FlagBehavior, Module},
+    types::BasicMetadataTypeEnum,
+    AddressSpace,
+};
 
-use crate::db::LlirDatabase;
+use crate::{db::LlirDatabase, line_of, LlirConfig};
 
 pub struct CgLowering<'db, 'ctx> {
     pub db: &'db dyn LlirDatabase,
     pub pkg: Package,
+    pub config: LlirConfig,
 
     pub ctx: &'ctx Context,
     pub builder: Builder<'ctx>,
@@ -13,11 +26,24 @@ pub struct CgLowering<'db, 'ctx> {
     pub bb: Option<BasicBlock<'ctx>>,
 }
 
+/// The DWARF debug info of a module, the module has a compile unit for its file.
+pub struct CgDebugInfo<'ctx> {
+    pub builder: DebugInfoBuilder<'ctx>,
+    pub unit: DICompileUnit<'ctx>,
+    pub optimized: bool,
+}
+
 impl<'db, 'ctx> CgLowering<'db, 'ctx> {
-    pub fn new(db: &'db dyn LlirDatabase, pkg: Package, ctx: &'ctx Context) -> Self {
+    pub fn new(
+        db: &'db dyn LlirDatabase,
+        pkg: Package,
+        config: LlirConfig,
+        ctx: &'ctx Context,
+    ) -> Self {
         Self {
             db,
             pkg,
+            config,
             ctx,
             builder: ctx.create_builder(),
             bb: None,
@@ -27,4 +53,185 @@ impl<'db, 'ctx> CgLowering<'db, 'ctx> {
     pub fn bb(&self) -> BasicBlock<'ctx> {
         self.bb.unwrap()
     }
+
+    /// Creates the module of the file, with a function for each binding group. The bodies are not
+    /// lowered yet, so the functions are declarations, and the debug info has no line locations
+    /// or local variables.
+    pub fn file_module(&self, file: VfsFile, groups: &[(HirBindingGroup, HirLoc)]) -> Module<'ctx> {
+        let data = self.db.lookup_intern_vfs_file(file);
+        let module = self.ctx.create_module(&data.name);
+        let debug_info = self.config.debug.then(|| self.debug_info(&module, file));
+
+        let object = self.ctx.i8_type().ptr_type(AddressSpace::default());
+        for (group, span) in groups {
+            let name = self.db.lookup_intern_name(group.signature.name);
+            let arity = group.signature.explicit_parameters().count();
+            let parameters = vec![BasicMetadataTypeEnum::from(object); arity];
+            let function = module.add_function(&name, object.fn_type(&parameters, false), None);
+
+            if let Some(debug_info) = &debug_info {
+                let line = line_of(self.db, span).unwrap_or_default() as u32;
+                function.set_subprogram(debug_info.subprogram(&name, arity, line));
+            }
+        }
+
+        if let Some(debug_info) = debug_info {
+            debug_info.builder.finalize();
+        }
+
+        module
+    }
+
+    /// Creates the compile unit of the file, the [DWARFSourceLanguage::C] is used, as there is no
+    /// language code for asena, and the debuggers know how to step through C.
+    fn debug_info(&self, module: &Module<'ctx>, file: VfsFile) -> CgDebugInfo<'ctx> {
+        let path = self.db.lookup_intern_vfs_file(file).id.path;
+        let filename = path.file_name().unwrap_or_default().to_string_lossy();
+        let directory = path.parent().unwrap_or(&path).to_string_lossy();
+
+        let version = self
+            .ctx
+            .i32_type()
+            .const_int(debug_metadata_version() as u64, false);
+        module.add_basic_value_flag("Debug Info Version", FlagBehavior::Warning, version);
+
+        let (builder, unit) = module.create_debug_info_builder(
+            true,
+            DWARFSourceLanguage::C,
+            &filename,
+            &directory,
+            "asena",
+            self.config.optimize,
+            "",
+            0,
+            "",
+            DWARFEmissionKind::Full,
+            0,
+            false,
+            false,
+            "",
+            "",
+        );
+
+        CgDebugInfo {
+            builder,
+            unit,
+            optimized: self.config.optimize,
+        }
+    }
+}
+
+impl<'ctx> CgDebugInfo<'ctx> {
+    /// The subprogram of a binding group, its parameters and return value are runtime objects.
+    /// It's not a definition, as the function is only declared.
+    pub fn subprogram(&self, name: &str, arity: usize, line: u32) -> DISubprogram<'ctx> {
+        let object = self
+            .builder
+            .create_basic_type("asena_object_t", 64, DW_ATE_ADDRESS, DIFlags::PUBLIC)
+            .unwrap()
+            .as_type();
+        let file = self.unit.get_file();
+        let subroutine_type = self.builder.create_subroutine_type(
+            file,
+            Some(object),
+            &vec![object; arity],
+            DIFlags::PUBLIC,
+        );
+
+        self.builder.create_function(
+            self.unit.as_debug_info_scope(),
+            name,
+            None,
+            file,
+            line,
+            subroutine_type,
+            false,
+            false,
+            line,
+            DIFlags::PUBLIC,
+            self.optimized,
+        )
+    }
+}
+
+/// The DWARF encoding of the pointers.
+const DW_ATE_ADDRESS: u32 = 0x01;
+
+#[cfg(test)]
+mod tests {
+    use asena_ast_db::{db::AstDatabaseStorage, testing::load_main};
+    use asena_ast_lowering::db::AstLowerrerStorage;
+    use asena_ast_resolver::db::AstResolverStorage;
+    use asena_hir::interner::HirStorage;
+    use asena_hir_db::db::HirDatabaseStorage;
+    use asena_prec::db::PrecStorage;
+
+    use crate::db::LlirStorage;
+
+    use super::*;
+
+    #[salsa::database(
+        PrecStorage,
+        AstDatabaseStorage,
+        HirDatabaseStorage,
+        AstLowerrerStorage,
+        AstResolverStorage,
+        HirStorage,
+        LlirStorage
+    )]
+    struct TestDatabase {
+        storage: salsa::Storage<TestDatabase>,
+    }
+
+    impl salsa::Database for TestDatabase {}
+
+    /// The IR of the main module of a package, with `source` as its main module.
+    fn main_module(source: &str, config: LlirConfig) -> String {
+        let mut db = TestDatabase {
+            storage: Default::default(),
+        };
+        let (pkg, _) = load_main(&mut db, source);
+
+        db.llir_package(pkg, config)
+            .unwrap()
+            .modules
+            .iter()
+            .find(|module| module.contains("@plus_one("))
+            .cloned()
+            .unwrap()
+    }
+
+    #[test]
+    fn debug_info_has_a_compile_unit_and_a_subprogram_for_each_group() {
+        let source = "one : Nat\n\
+                      one = Succ Zero\n\
+                      \n\
+                      plus_one : Nat -> Nat\n\
+                      plus_one n = Succ n\n";
+        let config = LlirConfig {
+            debug: true,
+            ..Default::default()
+        };
+        let module = main_module(source, config);
+
+        assert!(
+            module.contains("!DICompileUnit(language: DW_LANG_C"),
+            "{module}"
+        );
+        assert!(
+            module.contains("!DIFile(filename: \"Main.ase\", directory: \"<Local>\")"),
+            "{module}"
+        );
+
+        let subprogram = module
+            .lines()
+            .find(|line| line.contains("!DISubprogram(name: \"plus_one\""))
+            .unwrap_or_else(|| panic!("{module}"));
+        assert!(subprogram.contains("line: 4,"), "{subprogram}");
+        assert!(module.contains("!DISubprogram(name: \"one\""), "{module}");
+
+        // Without the flag, the modules have no debug info.
+        let module = main_module(source, LlirConfig::default());
+        assert!(!module.contains("!DICompileUnit"), "{module}");
+    }
 }
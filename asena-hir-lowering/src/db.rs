@@ -2,38 +2,83 @@ use std::sync::Arc;
 
 use asena_ast_db::package::Package;
 use asena_hir_db::db::HirDatabase;
-use inkwell::context::Context;
 
-use crate::{cg::CgLowering, LlirConfig, LlirErr};
+use crate::{c::CPackage, LlirConfig, LlirErr};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct LlirPackage;
+/// The LLVM IR of a package, with a module for each of its files.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LlirPackage {
+    pub modules: Vec<String>,
+}
 
 #[salsa::query_group(LlirStorage)]
 pub trait LlirDatabase: HirDatabase {
     fn llir_package(&self, pkg: Package, config: LlirConfig) -> Result<Arc<LlirPackage>, LlirErr>;
+
+    #[salsa::invoke(crate::c::c_package)]
+    fn c_package(
+        &self,
+        pkg: Package,
+        config: LlirConfig,
+        entry: Option<String>,
+    ) -> Result<Arc<CPackage>, LlirErr>;
 }
 
+#[cfg(feature = "llvm")]
 fn llir_package(
     db: &dyn LlirDatabase,
     pkg: Package,
-    _config: LlirConfig,
+    config: LlirConfig,
 ) -> Result<Arc<LlirPackage>, LlirErr> {
-    // Discovery step
-    let mut defs = db.hir_defs(pkg);
+    use std::collections::BTreeMap;
+
+    use asena_hir::top_level::HirTopLevelKind;
+    use inkwell::context::Context;
+
+    use crate::cg::CgLowering;
 
+    // Discovery step, the binding groups are grouped by their files, as each file is a module.
     let data = db.lookup_intern_package(pkg);
-    for data in data.dependencies {
-        let local_defs = db.hir_defs(data);
-        defs.extend(local_defs);
+    let packages = data.dependencies.iter().copied().chain([pkg]);
+    let mut files = BTreeMap::<_, Vec<_>>::new();
+    for pkg in packages {
+        for def in db.hir_defs(pkg) {
+            let def = db.lookup_intern_top_level(def);
+            let Some(file) = def.span.file else {
+                continue;
+            };
+            let groups = match def.kind {
+                HirTopLevelKind::BindingGroup(group) => vec![group],
+                HirTopLevelKind::Struct(data) => data.groups.into_iter().collect(),
+                HirTopLevelKind::Enum(data) => data.groups.into_iter().collect(),
+                HirTopLevelKind::Error
+                | HirTopLevelKind::Instance(_)
+                | HirTopLevelKind::Trait(_) => {
+                    vec![]
+                }
+            };
+
+            let entry = files.entry(file).or_default();
+            entry.extend(groups.into_iter().map(|group| (group, def.span.clone())));
+        }
     }
 
     let ctx = Context::create();
-    let _cg = CgLowering::new(db, pkg, &ctx);
+    let cg = CgLowering::new(db, pkg, config, &ctx);
+    let modules = files
+        .into_iter()
+        .map(|(file, groups)| cg.file_module(file, &groups))
+        .map(|module| module.print_to_string().to_string())
+        .collect();
 
-    // let main = db
-    //     .hir_find_fn(pkg, "main".into())
-    //     .ok_or(LlirErr::MainNotFound(data.name))?;
+    Ok(Arc::new(LlirPackage { modules }))
+}
 
-    Ok(Arc::new(LlirPackage))
+#[cfg(not(feature = "llvm"))]
+fn llir_package(
+    _db: &dyn LlirDatabase,
+    _pkg: Package,
+    _config: LlirConfig,
+) -> Result<Arc<LlirPackage>, LlirErr> {
+    Err(LlirErr::BackendUnavailable("llvm"))
 }
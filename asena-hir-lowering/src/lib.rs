@@ -1,5 +1,11 @@
+use asena_ast_db::manifest::BackendOptions;
+use asena_hir::HirLoc;
 use thiserror::Error;
 
+use crate::db::LlirDatabase;
+
+pub mod c;
+#[cfg(feature = "llvm")]
 pub mod cg;
 pub mod db;
 
@@ -10,6 +16,12 @@ pub enum LlirErr {
 
     #[error("main function not found at package: '{0}'")]
     MainNotFound(String),
+
+    #[error("entry point '{0}' not found at package: '{1}'")]
+    EntryNotFound(String, String),
+
+    #[error("the {0} backend is not available in this build")]
+    BackendUnavailable(&'static str),
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -17,3 +29,26 @@ pub struct LlirConfig {
     pub optimize: bool,
     pub debug: bool,
 }
+
+/// The defaults of the package, from the `backend` options of its manifest.
+impl From<BackendOptions> for LlirConfig {
+    fn from(options: BackendOptions) -> Self {
+        Self {
+            optimize: options.optimize,
+            debug: options.debug,
+        }
+    }
+}
+
+/// The line of the start of the span, counting from one, like the debuggers.
+pub(crate) fn line_of(db: &dyn LlirDatabase, span: &HirLoc) -> Option<usize> {
+    let source = db.source(span.file?);
+    let offset = span.original.range.start();
+    let line = source
+        .get(..offset)?
+        .bytes()
+        .filter(|b| *b == b'\n')
+        .count();
+
+    Some(line + 1)
+}
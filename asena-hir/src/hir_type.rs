@@ -66,13 +66,15 @@ impl HirType {
         })
     }
 
+    /// Builds the curried function type `a -> b -> ... -> value`, with the same shape of the
+    /// lowered pi expressions: the parameter comes first, and the codomain is the last argument.
     pub fn pi(db: &dyn HirInterner, parameters: &[HirType], value: HirType) -> HirType {
-        parameters.iter().fold(value, |acc, next| {
+        parameters.iter().rev().fold(value, |acc, next| {
             let kind = HirTypeKind::from(HirTypeApp {
                 callee: HirTypeFunction::Pi,
                 arguments: vec![
-                    data::HirTypeArgument::Type(acc),
                     data::HirTypeArgument::Type(*next),
+                    data::HirTypeArgument::Type(acc),
                 ],
             });
 
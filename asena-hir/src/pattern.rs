@@ -90,7 +90,7 @@ impl HirPattern {
     }
 
     pub fn new_false(db: &dyn HirInterner) -> HirPattern {
-        let kind = HirPatternKind::from(HirPatternLiteral(HirLiteral::TRUE));
+        let kind = HirPatternKind::from(HirPatternLiteral(HirLiteral::FALSE));
 
         db.intern_pattern(HirPatternData {
             kind,
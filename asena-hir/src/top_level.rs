@@ -38,6 +38,7 @@ pub struct HirTopLevelTrait {
 pub struct HirBindingGroup {
     pub signature: data::HirSignature,
     pub declarations: im::HashSet<data::HirDeclaration>,
+    pub docs: Vec<data::HirDoc>,
 }
 
 #[derive(Default, Hash, Clone, Debug, PartialEq, Eq)]
@@ -66,15 +67,31 @@ pub mod data {
 
     use super::*;
 
+    /// A line of the `///` doc comments of a declaration, written in Markdown.
     #[derive(Default, Hash, Clone, Debug, PartialEq, Eq)]
     pub struct HirDoc {
         pub text: String,
     }
 
+    /// How many times the value of a parameter can be used at runtime.
+    #[derive(Default, Hash, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    pub enum HirMultiplicity {
+        /// Erased before the codegen, it can only be used at the type level.
+        Zero,
+
+        /// Linear, it must be used exactly once.
+        One,
+
+        /// Unrestricted.
+        #[default]
+        Many,
+    }
+
     #[derive(Hash, Clone, Debug, PartialEq, Eq)]
     pub struct HirParameterData {
         pub name: Name,
         pub parameter_type: Option<HirType>,
+        pub multiplicity: HirMultiplicity,
     }
 
     #[derive(Default, Hash, Clone, Debug, PartialEq, Eq)]
@@ -86,6 +103,17 @@ pub mod data {
         Implicit(HirParameterData),
     }
 
+    impl HirParameterKind {
+        pub fn multiplicity(&self) -> HirMultiplicity {
+            match self {
+                HirParameterKind::Error | HirParameterKind::This => HirMultiplicity::Many,
+                HirParameterKind::Explicit(data) | HirParameterKind::Implicit(data) => {
+                    data.multiplicity
+                }
+            }
+        }
+    }
+
     #[derive(Hash, Clone, Debug, PartialEq, Eq)]
     pub struct HirSignature {
         pub name: Name,
@@ -93,6 +121,27 @@ pub mod data {
         pub return_type: Option<HirType>,
     }
 
+    impl HirSignature {
+        /// The parameters passed in the calls, and matched by the patterns of the declarations,
+        /// the implicit parameters are neither passed nor matched.
+        pub fn explicit_parameters(&self) -> impl Iterator<Item = &HirParameterKind> {
+            self.parameters
+                .iter()
+                .filter(|parameter| !matches!(parameter, HirParameterKind::Implicit(_)))
+        }
+
+        /// The positions of the explicit parameters with zero multiplicity. They are the same in
+        /// the patterns of the declarations and in the arguments of the calls, where they are
+        /// erased.
+        pub fn erased_parameters(&self) -> Vec<usize> {
+            self.explicit_parameters()
+                .enumerate()
+                .filter(|(_, parameter)| parameter.multiplicity() == HirMultiplicity::Zero)
+                .map(|(i, _)| i)
+                .collect()
+        }
+    }
+
     #[derive(Hash, Clone, Debug, PartialEq, Eq)]
     pub struct HirDeclaration {
         pub patterns: Vec<HirPattern>,
@@ -103,5 +152,6 @@ pub mod data {
     pub struct HirVariant {
         pub name: Name,
         pub variant_type: HirType,
+        pub docs: Vec<HirDoc>,
     }
 }
@@ -63,6 +63,15 @@ macro_rules! ast_enum {
             }
         }
 
+        impl $crate::token::token_set::HasTokens for $name {
+            fn tokens(&self) -> Vec<asena_span::Spanned<$crate::token::Token>> {
+                match self {
+                    Self::Error => vec![],
+                    $(Self::$variant(value) => $crate::token::token_set::HasTokens::tokens(value),)*
+                }
+            }
+        }
+
         impl $crate::ast::Node for $name {
             fn new<I: Into<$crate::ast::GreenTree>>(value: I) -> Self {
                 let tree: $crate::ast::GreenTree = value.into();
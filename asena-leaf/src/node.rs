@@ -1,4 +1,4 @@
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
 
 use asena_span::Spanned;
 
@@ -149,3 +149,23 @@ impl Debug for Tree {
         Ok(())
     }
 }
+
+/// Writes the source code of the tree, with the trivia of its tokens. The printed [Tree] of a
+/// file is its source code, as long as the file has a token to hold the trivia.
+impl Display for Tree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for child in &self.children {
+            write!(f, "{}", child.value)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Child {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Child::Tree(tree) => write!(f, "{tree}"),
+            Child::Token(token) => write!(f, "{token}"),
+        }
+    }
+}
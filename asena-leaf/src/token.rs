@@ -68,6 +68,13 @@ impl Token {
     }
 }
 
+/// Writes the source code of the token, with its trivia.
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.full_text)
+    }
+}
+
 impl Named for TokenKind {}
 
 impl Display for TokenKind {
@@ -33,6 +33,7 @@ pub enum TokenKind {
     ForallUnicode, // ∀
     PiUnicode,     // Π
     SigmaUnicode,  // Σ
+    OmegaUnicode,  // ω
 
     // control symbols
     LeftBracket,  // [
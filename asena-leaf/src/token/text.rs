@@ -1,15 +1,46 @@
 use std::fmt::Display;
 
+/// The source code of a token, with its trivia: the whitespace, the comments and the skipped
+/// characters around it. Writing the texts of all the tokens, in order, gives back the source
+/// code of the file.
 #[derive(Debug, Clone, Hash, Default, PartialEq, Eq)]
 pub struct Text {
+    /// The leading trivia, from the line break after the previous token, to the token.
     pub before_whitespace: String,
     pub code: String,
+
+    /// The trailing trivia, from the token to the end of its line, without the line break. The
+    /// trailing trivia of the last token is the rest of the file.
+    pub after_whitespace: String,
+}
+
+impl Text {
+    /// The lines of the `///` doc comments right before the token, in its leading trivia,
+    /// without the slashes. The other comments end the doc comments before them.
+    pub fn docs(&self) -> Vec<String> {
+        let mut docs = vec![];
+        for line in self.before_whitespace.lines().map(str::trim) {
+            if line.is_empty() {
+                continue;
+            }
+
+            match line.strip_prefix("///") {
+                Some(doc) if !doc.starts_with('/') => {
+                    docs.push(doc.strip_prefix(' ').unwrap_or(doc).to_string());
+                }
+                _ => docs.clear(),
+            }
+        }
+
+        docs
+    }
 }
 
 impl Display for Text {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.before_whitespace)?;
         write!(f, "{}", self.code)?;
+        write!(f, "{}", self.after_whitespace)?;
         Ok(())
     }
 }
@@ -34,7 +34,15 @@ pub fn lexer<'a>() -> impl Parser<'a, &'a str, TokenSet, LexError<'a>> {
     let num = text::int(10)
         .then(just('.').then(text::digits(10)).or_not())
         .slice()
-        .map(|value: &str| Token::new(Float64, value))
+        .map(|value: &str| match value {
+            _ if value.contains('.') => Token::new(Float64, value),
+            // The integers are lexed into the narrowest literal that fits them, and the ones that
+            // don't fit in any are read as decimals.
+            _ if value.parse::<i32>().is_ok() => Token::new(Int32, value),
+            _ if value.parse::<i64>().is_ok() => Token::new(Int64, value),
+            _ if value.parse::<i128>().is_ok() => Token::new(Int128, value),
+            _ => Token::new(Float64, value),
+        })
         .labelled("number"); // TODO: implement another float/integer variants
 
     let string = just('"')
@@ -57,11 +65,6 @@ pub fn lexer<'a>() -> impl Parser<'a, &'a str, TokenSet, LexError<'a>> {
         })
         .labelled("symbol");
 
-    let comment = just("//")
-        .then(any().and_is(just('\n').not()).repeated())
-        .padded()
-        .labelled("comment");
-
     let semi = just(";")
         .repeated()
         .at_least(1)
@@ -72,7 +75,15 @@ pub fn lexer<'a>() -> impl Parser<'a, &'a str, TokenSet, LexError<'a>> {
         .to(Token::new(LambdaUnicode, "λ"))
         .or(just("∀").to(Token::new(ForallUnicode, "∀")))
         .or(just("Π").to(Token::new(PiUnicode, "Π")))
-        .or(just("Σ").to(Token::new(SigmaUnicode, "Σ")));
+        .or(just("Σ").to(Token::new(SigmaUnicode, "Σ")))
+        .or(just("ω")
+            // `ω` is only the multiplicity, when it isn't the start of an identifier, like `ωx`
+            .then_ignore(
+                any()
+                    .filter(|c: &char| c.is_alphanumeric() || *c == '_')
+                    .not(),
+            )
+            .to(Token::new(OmegaUnicode, "ω")));
 
     let token = control_lexer()
         .or(semi)
@@ -82,18 +93,31 @@ pub fn lexer<'a>() -> impl Parser<'a, &'a str, TokenSet, LexError<'a>> {
         .or(string)
         .or(ident_lexer());
 
-    token
+    // The trivia is skipped after each token, so the sources with only trivia have no tokens,
+    // instead of an error at their end.
+    let tokens = token
         .map_with_span(|tok, span| (tok, span))
-        .padded_by(comment.repeated())
-        .padded()
+        .then_ignore(trivia_lexer())
         // If we encounter an error, skip and attempt to lex the next character as a token instead
         .recover_with(skip_then_retry_until(any().ignored(), end()))
         .repeated()
-        .collect()
+        .collect();
+
+    trivia_lexer().ignore_then(tokens)
+}
+
+/// The whitespace and the comments between the tokens.
+fn trivia_lexer<'a>() -> impl Parser<'a, &'a str, (), LexError<'a>> {
+    let comment = just("//")
+        .then(any().and_is(just('\n').not()).repeated())
+        .padded()
+        .labelled("comment");
+
+    comment.repeated().padded()
 }
 
 fn control_lexer<'a>() -> impl Parser<'a, &'a str, Token, LexError<'a>> {
-    one_of("()[]{},.")
+    one_of("()[]{},.?")
         .map(|control: char| match control {
             '[' => Token::new(LeftBracket, "["),
             ']' => Token::new(RightBracket, "]"),
@@ -104,6 +128,7 @@ fn control_lexer<'a>() -> impl Parser<'a, &'a str, Token, LexError<'a>> {
             ',' => Token::new(Comma, ","),
             ':' => Token::new(Colon, ":"),
             '.' => Token::new(Dot, "."),
+            '?' => Token::new(HelpSymbol, "?"),
             // This code is unreachable, because its matched by the [one_of]
             // functions
             _ => panic!("unreachable"),
@@ -140,20 +165,43 @@ fn ident_lexer<'a>() -> impl Parser<'a, &'a str, Token, LexError<'a>> {
         .labelled("keyword")
 }
 
+/// Attaches the trivia between the tokens to them, so the tokens hold the entire source code.
+/// The trivia between two tokens is split in the first line break: the rest of the line of the
+/// previous token is its trailing trivia, and the next lines are the leading trivia of the next
+/// token.
 fn map_full_text(code: &str, mut token_set: TokenSet) -> Vec<(Token, SimpleSpan)> {
     let mut i = 0;
-    for (token, span) in token_set.iter_mut() {
+    for index in 0..token_set.len() {
+        let span = token_set[index].1;
         let whitespace = &code[i..span.start];
-        let text = &code[span.start..span.end];
-        token.full_text = Text {
-            before_whitespace: whitespace.into(),
-            code: text.into(),
+        let split = match index {
+            0 => 0,
+            _ => whitespace.find(is_line_break).unwrap_or(whitespace.len()),
+        };
+
+        if index > 0 {
+            token_set[index - 1].0.full_text.after_whitespace = whitespace[..split].into();
+        }
+
+        token_set[index].0.full_text = Text {
+            before_whitespace: whitespace[split..].into(),
+            code: code[span.start..span.end].into(),
+            after_whitespace: String::new(),
         };
         i = span.end;
     }
+
+    if let Some((token, _)) = token_set.last_mut() {
+        token.full_text.after_whitespace = code[i..].into();
+    }
+
     token_set
 }
 
+fn is_line_break(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
+
 impl<'a> Lexer<'a> {
     /// Creates a new [Lexer] based in a source code
     pub fn new<I: Into<Option<PathBuf>>>(path: I, code: &'a str) -> Self {
@@ -2,8 +2,8 @@ use thiserror::Error;
 
 use asena_leaf::named::Named;
 use asena_leaf::node::kind::TokenKind;
-use asena_report::InternalError;
-use asena_span::Spanned;
+use asena_report::{quickfix, InternalError, Quickfix};
+use asena_span::{Loc, Spanned};
 
 pub type Result<T, E = Spanned<ParseError>> = std::result::Result<T, E>;
 
@@ -226,14 +226,35 @@ impl ParseError {
         // field, so we can read the discriminant without offsetting the pointer.
         unsafe { *<*const _>::from(self).cast::<u8>() }
     }
+
+    /// The fixes of the error found at the `loc`, they can be applied by the editor, or by the
+    /// `asena fix` command.
+    pub fn quickfixes(&self, loc: &Loc) -> Vec<Quickfix> {
+        use asena_report::{Insert, Remove};
+
+        match self {
+            Self::UnicodeError(TokenKind::LambdaUnicode, "lambda") => {
+                quickfix!(before, loc, [Remove("λ".into()), Insert("\\".into())]).to_vec()
+            }
+            Self::UeselessSemiWarning => quickfix!(before, loc, [Remove(";".into())]).to_vec(),
+            Self::UselessCommaWarning => quickfix!(before, loc, [Remove(",".into())]).to_vec(),
+            _ => vec![],
+        }
+    }
 }
 
 impl InternalError for ParseError {
     fn code(&self) -> u16 {
-        self.discriminant() as u16
+        // The parsing errors are `E01xx`, they are listed in `docs/diagnostics.md`.
+        100 + self.discriminant() as u16
     }
 
     fn kind(&self) -> asena_report::DiagnosticKind {
-        asena_report::DiagnosticKind::Error
+        match self {
+            Self::UeselessSemiWarning | Self::UselessCommaWarning => {
+                asena_report::DiagnosticKind::Unnecessary
+            }
+            _ => asena_report::DiagnosticKind::Error,
+        }
     }
 }
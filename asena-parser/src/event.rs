@@ -2,9 +2,9 @@ use std::fmt::Debug;
 
 use crate::builder::EventBuilder;
 use asena_drop_bomb::DropBomb;
-use asena_leaf::node::{Child, Tree, TreeKind};
+use asena_leaf::node::{Child, Token, Tree, TreeKind};
 use asena_report::{Diagnostic, Report};
-use asena_span::{Loc, Spanned};
+use asena_span::{Loc, Span, Spanned};
 
 use super::error::ParseError;
 use super::Parser;
@@ -29,6 +29,7 @@ pub struct RedTree {
 impl<'a> Parser<'a> {
     pub fn build_tree(mut self) -> RedTree {
         let event_debugger = EventBuilder::new(self.events.clone());
+        let has_tokens = !self.tokens.is_empty();
         let mut tokens = self.tokens.into_iter();
         let mut events = self.events;
         let mut stack = vec![];
@@ -108,9 +109,49 @@ impl<'a> Parser<'a> {
                 .push(Diagnostic::new(Spanned::new((0..0).into(), error)))
         }
 
-        if let Some(token) = tokens.next() {
+        // The trees that weren't closed are closed in the end of the file, and the tokens that
+        // weren't consumed are kept in an error tree, so the tree still holds the entire source
+        // code.
+        while stack.len() > 1 {
+            let tree = stack.pop().unwrap();
+            let parent = stack.last_mut().unwrap();
+            parent
+                .value
+                .children
+                .push(tree.replace(Child::Tree(tree.value.clone())));
+        }
+
+        let mut tokens = tokens.peekable();
+        if let Some(token) = tokens.peek() {
             let error = ParseError::StreamStillContainElements(token.kind);
-            self.errors.push(Diagnostic::new(token.swap(error)));
+            self.errors.push(Diagnostic::new(token.clone().swap(error)));
+
+            let mut error = token.clone().swap(Tree::new(TreeKind::Error));
+            for token in tokens {
+                error.span = error.span.on(token.span.clone());
+                error
+                    .value
+                    .children
+                    .push(token.replace(Child::Token(token.value().clone())));
+            }
+
+            let tree = stack.last_mut().unwrap();
+            tree.value
+                .children
+                .push(error.replace(Child::Tree(error.value.clone())));
+        }
+
+        // The source code without tokens, that only has whitespace and comments, is kept in the
+        // EOF token, so the tree still holds the entire source code.
+        if !has_tokens && !self.source.is_empty() {
+            let end = self.source.len();
+            let mut eof = Token::eof();
+            eof.full_text.before_whitespace = self.source.into();
+
+            let tree = stack.last_mut().unwrap();
+            tree.value
+                .children
+                .push(Spanned::new((end..end).into(), Child::Token(eof)));
         }
 
         let tree = stack.pop().unwrap();
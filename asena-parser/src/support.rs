@@ -140,27 +140,28 @@ impl<'a> Parser<'a> {
             return;
         }
 
-        let error = self.build_error(ParseError::ExpectedTokenError(kind));
-        self.errors.push(Diagnostic::new(error));
+        let error = self.build_diagnostic(ParseError::ExpectedTokenError(kind));
+        self.errors.push(error);
     }
 
     pub fn warning(&mut self, error: ParseError) -> Option<MarkClosed> {
-        let error = self.build_error(error);
-        let mut error = Diagnostic::new(error);
-        error.kind = DiagnosticKind::Warning;
+        let mut error = self.build_diagnostic(error);
+        if error.kind != DiagnosticKind::Unnecessary {
+            error.kind = DiagnosticKind::Warning;
+        }
         self.errors.push(error);
         None
     }
 
     pub fn report(&mut self, error: ParseError) -> Option<MarkClosed> {
         if self.eof() {
-            let error = self.build_error(error);
-            self.errors.push(Diagnostic::new(error));
+            let error = self.build_diagnostic(error);
+            self.errors.push(error);
             return None;
         }
         let mark = self.open();
-        let error = self.build_error(error);
-        self.errors.push(Diagnostic::new(error));
+        let error = self.build_diagnostic(error);
+        self.errors.push(error);
         self.advance();
 
         Some(self.close(mark, TreeKind::Error))
@@ -223,4 +224,12 @@ impl<'a> Parser<'a> {
     fn build_error(&self, error: ParseError) -> Spanned<ParseError> {
         self.peek().into_owned().swap(error)
     }
+
+    /// Builds the diagnostic of the error at the current token, with its quickfixes.
+    fn build_diagnostic(&self, error: ParseError) -> Diagnostic<ParseError> {
+        let error = self.build_error(error);
+        let quickfixes = error.value.quickfixes(&error.span);
+
+        Diagnostic::new(error).with_quickfixes(quickfixes)
+    }
 }
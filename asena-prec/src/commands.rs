@@ -2,6 +2,8 @@ use std::ops::{Deref, DerefMut};
 use std::sync::{Arc, RwLock};
 
 use asena_ast_db::commands::CommandHandler;
+use asena_ast_db::db::AstDatabase;
+use asena_ast_db::package::Package;
 use asena_derive::*;
 
 use asena_ast::command::Result;
@@ -14,17 +16,25 @@ use crate::PrecDatabase;
 
 pub struct InfixHandler<'db> {
     pub db: &'db dyn PrecDatabase,
+    pub package: Package,
+    pub prec_table: PrecTable,
 }
 
 impl<'db> InfixHandler<'db> {
-    pub fn new(db: &'db dyn PrecDatabase) -> Self {
-        Self { db }
+    /// Creates the handler of the commands of a file of the package, its errors are reported to
+    /// the package.
+    pub fn new(db: &'db dyn PrecDatabase, package: Package, prec_table: PrecTable) -> Self {
+        Self {
+            db,
+            package,
+            prec_table,
+        }
     }
 }
 
 impl<'db> Reports for InfixHandler<'db> {
     fn errors(&self) -> Arc<RwLock<Vec<Diagnostic<asena_report::BoxInternalError>>>> {
-        todo!()
+        self.db.lookup_intern_package(self.package).errors
     }
 }
 
@@ -43,68 +53,13 @@ impl<'db> CommandHandler for InfixHandler<'db> {
             entry = Entry::new(FunctionId::new(&name), Assoc::Right, order);
         }
 
-        let prec_table = self.db.prec_table();
-        let mut prec_table = prec_table.write().unwrap();
+        let mut prec_table = self.prec_table.write().unwrap();
         prec_table.insert(FunctionId::new(&name), entry);
 
         Ok(())
     }
 }
 
-/// The default precedence table for Asena Language in the Standard Library.
-///   - `->`, `=>`
-///   - `^`, `>>`, `<<`, `|`, `&`
-///   - `>`, `>=`, `<=`, `<`
-///   - `==`, `!=`
-///   - `||`, `&&`
-///   - `$`, `%`, `=>>`, `@`
-///   - `^^`
-///   - `*`, `/`
-///   - `+`, `-`
-pub fn default_prec_table() -> HashMap<FunctionId, Entry> {
-    let mut table = HashMap::new();
-
-    // `^`, `>>`, `<<`, `|`, `&`
-    table.insert("^".into(), Entry::new("==", Assoc::Right, 9));
-    table.insert("|".into(), Entry::new("|", Assoc::Right, 9));
-    table.insert("&".into(), Entry::new("&", Assoc::Right, 9));
-    table.insert(">>".into(), Entry::new(">>", Assoc::Right, 9));
-    table.insert("<<".into(), Entry::new("<<", Assoc::Right, 9));
-
-    // `>`, `>=`, `<=`, `<`
-    table.insert(">".into(), Entry::new("<", Assoc::Right, 8));
-    table.insert("<".into(), Entry::new("<", Assoc::Right, 8));
-    table.insert(">=".into(), Entry::new(">=", Assoc::Right, 8));
-    table.insert("<=".into(), Entry::new("<=", Assoc::Right, 8));
-
-    // `==`, `!=`
-    table.insert("==".into(), Entry::new("==", Assoc::Right, 7));
-    table.insert("!=".into(), Entry::new("!=", Assoc::Right, 7));
-
-    // `||`, `&&`
-    table.insert("||".into(), Entry::new("||", Assoc::Right, 6));
-    table.insert("&&".into(), Entry::new("&&", Assoc::Right, 6));
-
-    // `$`, `%`, `=>>`, `@`
-    table.insert("$".into(), Entry::new("$", Assoc::Right, 5));
-    table.insert("%".into(), Entry::new("%", Assoc::Right, 5));
-    table.insert("@".into(), Entry::new("@", Assoc::Right, 5));
-    table.insert("=>>".into(), Entry::new("=>>", Assoc::Right, 5));
-
-    // `^^`
-    table.insert("^^".into(), Entry::new("^^", Assoc::Right, 4));
-
-    // `*`, `/`
-    table.insert("*".into(), Entry::new("*", Assoc::Right, 2));
-    table.insert("/".into(), Entry::new("/", Assoc::Right, 2));
-
-    // `+`, `-`
-    table.insert("+".into(), Entry::new("+", Assoc::Right, 1));
-    table.insert("-".into(), Entry::new("-", Assoc::Right, 1));
-
-    table
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Assoc {
     Right,
@@ -128,7 +83,9 @@ impl Entry {
     }
 }
 
-#[derive(Debug, Clone)]
+/// The precedence table, it's filled by the `infixl` and `infixr` commands, the default operators
+/// are declared by the prelude of the standard library.
+#[derive(Default, Debug, Clone)]
 pub struct PrecTable(Arc<RwLock<HashMap<FunctionId, Entry>>>);
 
 impl PartialEq for PrecTable {
@@ -139,12 +96,6 @@ impl PartialEq for PrecTable {
 
 impl Eq for PrecTable {}
 
-impl Default for PrecTable {
-    fn default() -> Self {
-        Self(Arc::new(RwLock::new(default_prec_table())))
-    }
-}
-
 impl DerefMut for PrecTable {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.0
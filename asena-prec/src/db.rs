@@ -1,5 +1,6 @@
+use asena_ast_db::stdlib::{std_module, PRELUDE_MODULE};
 use asena_ast_db::{commands::CommandHandlerEval, db::AstDatabase};
-use asena_leaf::ast::AstParam;
+use asena_leaf::ast::{AstParam, Located};
 
 use super::*;
 
@@ -11,8 +12,15 @@ pub trait PrecDatabase: AstDatabase {
     fn infix_commands(&self, file: AstParam<AsenaFile>) -> AsenaFile;
 }
 
-fn prec_table(_db: &dyn PrecDatabase) -> PrecTable {
-    PrecTable::default()
+fn prec_table(db: &dyn PrecDatabase) -> PrecTable {
+    let prec_table = PrecTable::default();
+    if let Some(prelude) = std_module(db, PRELUDE_MODULE) {
+        let mut handler = InfixHandler::new(db, db.std_package(), prec_table.clone());
+        let eval = CommandHandlerEval::new(db, &mut handler);
+        db.ast(prelude).walks(eval);
+    }
+
+    prec_table
 }
 
 fn ordered_prec(db: &dyn PrecDatabase, file: AstParam<AsenaFile>) -> AsenaFile {
@@ -20,7 +28,8 @@ fn ordered_prec(db: &dyn PrecDatabase, file: AstParam<AsenaFile>) -> AsenaFile {
 }
 
 fn infix_commands(db: &dyn PrecDatabase, file: AstParam<AsenaFile>) -> AsenaFile {
-    let mut handler = InfixHandler::new(db);
+    let package = db.package_of(file.location().into_owned());
+    let mut handler = InfixHandler::new(db, package, db.prec_table());
     let eval = CommandHandlerEval::new(db, &mut handler);
     file.data.walks(eval)
 }
@@ -20,6 +20,47 @@ pub enum DiagnosticKind {
     LoweringError = 14,
     Context = 15,
     BuildError = 16,
+
+    /// Code that can be removed, like useless semicolons, the editors fade it out.
+    Unnecessary = 17,
+}
+
+/// The severity of a diagnostic, like the ones of the language server protocol. The compiler
+/// fails if there's any error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+impl Severity {
+    /// The name of the severity, like `error`, as it's printed by the compiler.
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+            Severity::Hint => "hint",
+        }
+    }
+}
+
+impl DiagnosticKind {
+    /// The severity the diagnostics of the kind are reported with, by the compiler and by the
+    /// language server.
+    pub fn severity(self) -> Severity {
+        match self {
+            DiagnosticKind::Info | DiagnosticKind::Context | DiagnosticKind::Meta => Severity::Info,
+            DiagnosticKind::Tip => Severity::Hint,
+            DiagnosticKind::Warning
+            | DiagnosticKind::Deprecated
+            | DiagnosticKind::Lint
+            | DiagnosticKind::Unnecessary => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -28,6 +69,7 @@ pub struct Diagnostic<T> {
     pub code: u16,
     pub message: Spanned<T>,
     pub children: Vec<Diagnostic<T>>,
+    pub quickfixes: Vec<Quickfix>,
 }
 
 pub trait WithError {
@@ -40,6 +82,16 @@ impl<T: Located> WithError for T {
     }
 }
 
+impl<T> Diagnostic<T> {
+    /// The code as it's shown to the user, like `E0301`, if the diagnostic has a code.
+    pub fn code_name(&self) -> Option<String> {
+        match self.code {
+            0 => None,
+            code => Some(format!("E{code:04}")),
+        }
+    }
+}
+
 impl<E: InternalError> Diagnostic<E> {
     pub fn new(error: Spanned<E>) -> Self {
         Self {
@@ -47,6 +99,7 @@ impl<E: InternalError> Diagnostic<E> {
             code: error.code(),
             message: error,
             children: vec![],
+            quickfixes: vec![],
         }
     }
 
@@ -64,10 +117,32 @@ impl<E: InternalError> Diagnostic<E> {
             code: message.code(),
             message,
             children: vec![],
+            quickfixes: vec![],
         });
 
         self
     }
+
+    /// Attaches the fixes to the diagnostic, they can be applied by the editor, or by the
+    /// `asena fix` command.
+    pub fn with_quickfixes(mut self, quickfixes: impl IntoIterator<Item = Quickfix>) -> Self {
+        self.quickfixes.extend(quickfixes);
+        self
+    }
+
+    /// Boxes the error of the diagnostic, and of its children.
+    pub fn boxed(self) -> Diagnostic<BoxInternalError>
+    where
+        E: Send + Sync + 'static,
+    {
+        Diagnostic {
+            kind: self.kind,
+            code: self.code,
+            message: Spanned::new(self.message.span, BoxInternalError::new(self.message.value)),
+            children: self.children.into_iter().map(Diagnostic::boxed).collect(),
+            quickfixes: self.quickfixes,
+        }
+    }
 }
 
 impl<T: InternalError> Eq for Diagnostic<T> {}
@@ -81,6 +156,7 @@ impl<T: InternalError> PartialEq for Diagnostic<T> {
             && self.message.value.kind() == other.message.value.kind()
             && self.message.value.to_string() == other.message.value.to_string()
             && self.children == other.children
+            && self.quickfixes == other.quickfixes
     }
 }
 
@@ -97,5 +173,6 @@ impl<T: InternalError> Hash for Diagnostic<T> {
         code.hash(state);
         kind.hash(state);
         self.children.hash(state);
+        self.quickfixes.hash(state);
     }
 }
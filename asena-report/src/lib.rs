@@ -30,11 +30,6 @@ pub trait Reports {
     {
         let errors = self.errors();
         let mut errors = errors.try_write().unwrap();
-        errors.push(Diagnostic {
-            message: diagnostic.message.map(BoxInternalError::new),
-            kind: diagnostic.kind,
-            code: diagnostic.code,
-            children: vec![],
-        });
+        errors.push(diagnostic.boxed());
     }
 }
@@ -19,6 +19,90 @@ pub struct Quickfix {
     pub message: Vec<Fragment>,
 }
 
+/// A change of the source, that replaces the text between `start` and `end` with `text`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct QuickfixEdit {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+impl Quickfix {
+    /// The edit of the fix: if there is a removed fragment, the text at the location is replaced
+    /// by the inserted fragments, otherwise they are inserted before, or after the location.
+    pub fn edit(&self) -> QuickfixEdit {
+        let start = self.loc.range.start();
+        let end = self.loc.range.end();
+        let text = self
+            .message
+            .iter()
+            .filter_map(|fragment| match fragment {
+                Insert(code) => Some(code.as_str()),
+                Remove(_) => None,
+            })
+            .collect::<String>();
+
+        if self
+            .message
+            .iter()
+            .any(|fragment| matches!(fragment, Remove(_)))
+        {
+            return QuickfixEdit { start, end, text };
+        }
+
+        let at = match self.position {
+            Position::Before => start,
+            Position::After => end,
+        };
+
+        QuickfixEdit {
+            start: at,
+            end: at,
+            text,
+        }
+    }
+}
+
+/// Applies the fixes to the source, from the end of the file, so the offsets of the remaining
+/// ones are still valid. The fixes that overlaps an already applied one are skipped, and the
+/// number of applied fixes is returned with the new source.
+pub fn apply_quickfixes<'a, I>(source: &str, fixes: I) -> (String, usize)
+where
+    I: IntoIterator<Item = &'a Quickfix>,
+{
+    let mut edits = fixes.into_iter().map(Quickfix::edit).collect::<Vec<_>>();
+    edits.sort_by(|a, b| b.cmp(a));
+    edits.dedup();
+
+    let mut source = source.to_string();
+    let mut count = 0;
+    let mut limit = usize::MAX;
+    for edit in edits {
+        if edit.end > limit || edit.end > source.len() {
+            continue;
+        }
+
+        source.replace_range(edit.start..edit.end, &edit.text);
+        limit = edit.start;
+        count += 1;
+    }
+
+    (source, count)
+}
+
+impl Display for Quickfix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, fragment) in self.message.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{fragment}")?;
+        }
+
+        Ok(())
+    }
+}
+
 impl Display for Fragment {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -31,14 +115,14 @@ impl Display for Fragment {
 #[macro_export]
 macro_rules! quickfix {
     (before, $loc:expr, [$($fragment:expr),*]) => {
-        [Quickfix {
+        [$crate::Quickfix {
             loc: $loc.clone(),
             position: $crate::Position::Before,
             message: vec![$($fragment),*],
         }]
     };
     (after, $loc:expr, [$($fragment:expr),*]) => {
-        [Quickfix {
+        [$crate::Quickfix {
             loc: $loc.clone(),
             position: $crate::Position::After,
             message: vec![$($fragment),*],
@@ -0,0 +1,47 @@
+/*
+ * Asena runtime interface, used by the code emitted by the portable C backend. The symbols are
+ * exported by the `asena-runtime` crate, that should be linked as a static library.
+ */
+
+#ifndef ASENA_H
+#define ASENA_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+typedef struct asena_object asena_object_t;
+
+typedef asena_object_t *(*asena_code_t)(asena_object_t *const *env, asena_object_t *const *args);
+
+extern void asena_rt_init(int argc, char **argv);
+extern int asena_rt_exit(void);
+
+extern asena_object_t *asena_rt_unit(void);
+extern asena_object_t *asena_rt_int(int64_t value);
+extern asena_object_t *asena_rt_bool(int value);
+extern asena_object_t *asena_rt_float(double value);
+extern asena_object_t *asena_rt_string(const char *value);
+extern asena_object_t *asena_rt_array(size_t length, asena_object_t *const *items);
+
+extern asena_object_t *asena_rt_construct(const char *name, size_t length, asena_object_t *const *fields);
+extern asena_object_t *asena_rt_field(asena_object_t *object, size_t index);
+extern ptrdiff_t asena_rt_length(asena_object_t *object);
+extern int asena_rt_is_constructor(asena_object_t *object, const char *name, size_t length);
+extern int asena_rt_eq_int(asena_object_t *object, int64_t value);
+extern int asena_rt_eq_float(asena_object_t *object, double value);
+extern int asena_rt_eq_string(asena_object_t *object, const char *value);
+
+extern asena_object_t *asena_rt_closure(asena_code_t code, size_t arity, size_t length, asena_object_t *const *env);
+extern asena_object_t *asena_rt_apply(asena_object_t *callee, size_t length, asena_object_t *const *arguments);
+extern asena_object_t *asena_rt_global(const char *name);
+
+extern asena_object_t *asena_rt_add(asena_object_t *lhs, asena_object_t *rhs);
+extern asena_object_t *asena_rt_sub(asena_object_t *lhs, asena_object_t *rhs);
+extern asena_object_t *asena_rt_mul(asena_object_t *lhs, asena_object_t *rhs);
+extern asena_object_t *asena_rt_div(asena_object_t *lhs, asena_object_t *rhs);
+
+extern asena_object_t *asena_rt_clone(asena_object_t *object);
+extern asena_object_t *asena_rt_drop(asena_object_t *object);
+extern asena_object_t *asena_rt_panic(const char *message);
+
+#endif /* ASENA_H */
@@ -16,6 +16,14 @@ pub struct Class {
 }
 
 impl Class {
+    /// Creates a class without superclasses, the name must be a nul-terminated string.
+    pub const fn primitive(name: *const c_char) -> Self {
+        Self {
+            name,
+            superclasses: ArrayList::empty(),
+        }
+    }
+
     #[inline]
     #[export_name = "_ZClass::new_instance"]
     pub fn new_instance(&self) -> *const Object {
@@ -0,0 +1,584 @@
+//! C interface of the runtime, it's the surface used by the code emitted by the portable C
+//! backend, and it's declared at `include/asena.h`.
+//!
+//! Every value is an heap allocated [Object], which `ptr` points to a [Value].
+
+#![allow(clippy::missing_safety_doc)]
+
+use std::{
+    ffi::{c_char, c_int, c_void, CStr, CString},
+    fmt::{Display, Formatter},
+    io::Write,
+    sync::OnceLock,
+};
+
+use crate::{array_list::Arguments, class::Class, Object, VTable};
+
+/// The C header declaring the runtime interface, it's embedded in the files emitted by the C
+/// backend, so they can be compiled without include paths.
+pub const C_HEADER: &str = include_str!("../include/asena.h");
+
+pub type Code = unsafe extern "C" fn(*const *mut Object, *const *mut Object) -> *mut Object;
+
+/// The value of an object. The objects in the value, like the fields of a constructor, are
+/// retained while they are stored in it, and released when it's freed.
+pub enum Value {
+    Unit,
+    Int(i64),
+    Float(f64),
+    String(CString),
+    Array(Vec<*mut Object>),
+    Data(CString, Vec<*mut Object>),
+    Closure(Closure),
+}
+
+#[derive(Clone)]
+pub struct Closure {
+    pub code: Code,
+    pub arity: usize,
+    pub env: Vec<*mut Object>,
+    pub applied: Vec<*mut Object>,
+}
+
+static ARGUMENTS: OnceLock<Vec<String>> = OnceLock::new();
+
+const UNIT_CLASS: Class = Class::primitive(cstring!(b"Unit\0"));
+const INT_CLASS: Class = Class::primitive(cstring!(b"Int\0"));
+const FLOAT_CLASS: Class = Class::primitive(cstring!(b"Float\0"));
+const STRING_CLASS: Class = Class::primitive(cstring!(b"String\0"));
+const ARRAY_CLASS: Class = Class::primitive(cstring!(b"Array\0"));
+const DATA_CLASS: Class = Class::primitive(cstring!(b"Data\0"));
+const CLOSURE_CLASS: Class = Class::primitive(cstring!(b"Closure\0"));
+
+const VALUE_VTABLE: VTable = VTable {
+    apply: value_apply,
+    to_string: value_to_string,
+};
+
+/// Returns the command line arguments the program was started with.
+pub fn arguments() -> &'static [String] {
+    ARGUMENTS.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+pub fn alloc(class: &'static Class, value: Value) -> *mut Object {
+    Box::into_raw(Box::new(Object {
+        ptr: Box::into_raw(Box::new(value)) as *mut c_void,
+        strong_count: Box::into_raw(Box::new(1)),
+        class,
+        vtable: &VALUE_VTABLE,
+    }))
+}
+
+pub unsafe fn value<'a>(object: *mut Object) -> &'a Value {
+    &*((*object).ptr as *const Value)
+}
+
+unsafe fn slice<'a>(items: *const *mut Object, length: usize) -> &'a [*mut Object] {
+    if length == 0 || items.is_null() {
+        return &[];
+    }
+
+    std::slice::from_raw_parts(items, length)
+}
+
+/// Retains the objects, as they are going to be stored in another value.
+unsafe fn retain(objects: &[*mut Object]) {
+    for object in objects {
+        asena_rt_clone(*object);
+    }
+}
+
+unsafe fn c_str<'a>(value: *const c_char) -> &'a CStr {
+    CStr::from_ptr(value)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_init(argc: c_int, argv: *const *const c_char) {
+    let arguments = (0..argc as usize)
+        .map(|i| c_str(*argv.add(i)).to_string_lossy().into_owned())
+        .collect();
+
+    let _ = ARGUMENTS.set(arguments);
+}
+
+#[no_mangle]
+pub extern "C" fn asena_rt_exit() -> c_int {
+    let _ = std::io::stdout().flush();
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn asena_rt_unit() -> *mut Object {
+    alloc(&UNIT_CLASS, Value::Unit)
+}
+
+#[no_mangle]
+pub extern "C" fn asena_rt_int(value: i64) -> *mut Object {
+    alloc(&INT_CLASS, Value::Int(value))
+}
+
+#[no_mangle]
+pub extern "C" fn asena_rt_bool(value: c_int) -> *mut Object {
+    let name = if value != 0 { "True" } else { "False" };
+
+    alloc(
+        &DATA_CLASS,
+        Value::Data(CString::new(name).unwrap(), vec![]),
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn asena_rt_float(value: f64) -> *mut Object {
+    alloc(&FLOAT_CLASS, Value::Float(value))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_string(value: *const c_char) -> *mut Object {
+    alloc(&STRING_CLASS, Value::String(c_str(value).into()))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_array(length: usize, items: *const *mut Object) -> *mut Object {
+    let items = slice(items, length).to_vec();
+    retain(&items);
+
+    alloc(&ARRAY_CLASS, Value::Array(items))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_construct(
+    name: *const c_char,
+    length: usize,
+    fields: *const *mut Object,
+) -> *mut Object {
+    let fields = slice(fields, length).to_vec();
+    retain(&fields);
+
+    alloc(&DATA_CLASS, Value::Data(c_str(name).into(), fields))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_field(object: *mut Object, index: usize) -> *mut Object {
+    match value(object) {
+        Value::Data(_, fields) | Value::Array(fields) if index < fields.len() => {
+            asena_rt_clone(fields[index])
+        }
+        _ => asena_rt_panic(cstring!(b"field access out of bounds\0")),
+    }
+}
+
+/// Returns the length of an array, or `-1` if the object is not an array.
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_length(object: *mut Object) -> isize {
+    match value(object) {
+        Value::Array(items) => items.len() as isize,
+        _ => -1,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_is_constructor(
+    object: *mut Object,
+    name: *const c_char,
+    length: usize,
+) -> c_int {
+    match value(object) {
+        Value::Data(constructor, fields) => {
+            (constructor.as_c_str() == c_str(name) && fields.len() == length) as c_int
+        }
+        _ => 0,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_eq_int(object: *mut Object, expected: i64) -> c_int {
+    match value(object) {
+        Value::Int(value) => (*value == expected) as c_int,
+        Value::Data(name, fields) if fields.is_empty() => match name.to_bytes() {
+            b"True" => (expected == 1) as c_int,
+            b"False" => (expected == 0) as c_int,
+            _ => 0,
+        },
+        _ => 0,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_eq_float(object: *mut Object, expected: f64) -> c_int {
+    match value(object) {
+        Value::Float(value) => (*value == expected) as c_int,
+        _ => 0,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_eq_string(object: *mut Object, expected: *const c_char) -> c_int {
+    match value(object) {
+        Value::String(value) => (value.as_c_str() == c_str(expected)) as c_int,
+        _ => 0,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_closure(
+    code: Code,
+    arity: usize,
+    length: usize,
+    env: *const *mut Object,
+) -> *mut Object {
+    let env = slice(env, length).to_vec();
+
+    // A closure without parameters is evaluated right away, as there is no way to apply it.
+    if arity == 0 {
+        return code(env.as_ptr(), std::ptr::null());
+    }
+
+    retain(&env);
+
+    alloc(
+        &CLOSURE_CLASS,
+        Value::Closure(Closure {
+            code,
+            arity,
+            env,
+            applied: vec![],
+        }),
+    )
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_apply(
+    callee: *mut Object,
+    length: usize,
+    arguments: *const *mut Object,
+) -> *mut Object {
+    let mut arguments = slice(arguments, length).to_vec();
+    let mut callee = callee;
+
+    // The closures returned by the applications, when there are more arguments than parameters,
+    // are owned by the application, and released after being applied.
+    let mut owned = false;
+
+    while !arguments.is_empty() {
+        let Value::Closure(closure) = value(callee) else {
+            let name = (*callee).class().name();
+
+            panic_with(&format!("apply is not implemented to type {name}"))
+        };
+
+        let missing = closure.arity - closure.applied.len();
+        if arguments.len() < missing {
+            let mut closure = closure.clone();
+            closure.applied.append(&mut arguments);
+            retain(&closure.env);
+            retain(&closure.applied);
+
+            let partial = alloc(&CLOSURE_CLASS, Value::Closure(closure));
+            if owned {
+                release(callee);
+            }
+
+            return partial;
+        }
+
+        let mut applied = closure.applied.clone();
+        applied.extend(arguments.drain(..missing));
+
+        let result = (closure.code)(closure.env.as_ptr(), applied.as_ptr());
+        if owned {
+            release(callee);
+        }
+
+        callee = result;
+        owned = true;
+    }
+
+    callee
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_global(name: *const c_char) -> *mut Object {
+    let name = c_str(name).to_string_lossy();
+    let local_name = name.rsplit('.').next().unwrap_or_default();
+
+    let code: Code = match local_name {
+        "println" => builtin_println,
+        "print" => builtin_print,
+        "readLine" => builtin_read_line,
+        "toString" => builtin_to_string,
+        "panic" => builtin_panic,
+        "todo" => builtin_todo,
+        _ => panic_with(&format!("unresolved global `{name}`")),
+    };
+
+    asena_rt_closure(code, 1, 0, std::ptr::null())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_add(lhs: *mut Object, rhs: *mut Object) -> *mut Object {
+    match (value(lhs), value(rhs)) {
+        (Value::Int(a), Value::Int(b)) => asena_rt_int(a.wrapping_add(*b)),
+        (Value::Float(a), Value::Float(b)) => asena_rt_float(a + b),
+        (Value::String(a), Value::String(b)) => {
+            let mut bytes = a.as_bytes().to_vec();
+            bytes.extend_from_slice(b.as_bytes());
+
+            alloc(&STRING_CLASS, Value::String(CString::new(bytes).unwrap()))
+        }
+        _ => panic_with("invalid operands to `+`"),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_sub(lhs: *mut Object, rhs: *mut Object) -> *mut Object {
+    match (value(lhs), value(rhs)) {
+        (Value::Int(a), Value::Int(b)) => asena_rt_int(a.wrapping_sub(*b)),
+        (Value::Float(a), Value::Float(b)) => asena_rt_float(a - b),
+        _ => panic_with("invalid operands to `-`"),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_mul(lhs: *mut Object, rhs: *mut Object) -> *mut Object {
+    match (value(lhs), value(rhs)) {
+        (Value::Int(a), Value::Int(b)) => asena_rt_int(a.wrapping_mul(*b)),
+        (Value::Float(a), Value::Float(b)) => asena_rt_float(a * b),
+        _ => panic_with("invalid operands to `*`"),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_div(lhs: *mut Object, rhs: *mut Object) -> *mut Object {
+    match (value(lhs), value(rhs)) {
+        (Value::Int(_), Value::Int(0)) => panic_with("division by zero"),
+        (Value::Int(a), Value::Int(b)) => asena_rt_int(a.wrapping_div(*b)),
+        (Value::Float(a), Value::Float(b)) => asena_rt_float(a / b),
+        _ => panic_with("invalid operands to `/`"),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_clone(object: *mut Object) -> *mut Object {
+    *(*object).strong_count += 1;
+    object
+}
+
+/// Decrements the reference count of the object, and frees it when it reaches zero.
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_drop(object: *mut Object) -> *mut Object {
+    release(object);
+    asena_rt_unit()
+}
+
+/// Decrements the reference count of the object, and frees it when it reaches zero, releasing
+/// the objects of its value too. It uses a stack instead of recursion, so long lists don't
+/// overflow the native stack.
+unsafe fn release(object: *mut Object) {
+    let mut pending = vec![object];
+
+    while let Some(object) = pending.pop() {
+        *(*object).strong_count -= 1;
+        if *(*object).strong_count != 0 {
+            continue;
+        }
+
+        let object = Box::from_raw(object);
+        let value = Box::from_raw(object.ptr as *mut Value);
+        drop(Box::from_raw(object.strong_count));
+
+        match *value {
+            Value::Array(items) | Value::Data(_, items) => pending.extend(items),
+            Value::Closure(closure) => {
+                pending.extend(closure.env);
+                pending.extend(closure.applied);
+            }
+            Value::Unit | Value::Int(_) | Value::Float(_) | Value::String(_) => {}
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn asena_rt_panic(message: *const c_char) -> *mut Object {
+    panic_with(&c_str(message).to_string_lossy())
+}
+
+/// Aborts the program with the given message, unwinding through the C frames is not allowed, so
+/// the process exits right away.
+fn panic_with(message: &str) -> ! {
+    let _ = std::io::stdout().flush();
+    eprintln!("asena: {message}");
+    std::process::exit(101)
+}
+
+unsafe extern "C" fn builtin_println(
+    _: *const *mut Object,
+    args: *const *mut Object,
+) -> *mut Object {
+    println!("{}", Shown(*args));
+    asena_rt_unit()
+}
+
+unsafe extern "C" fn builtin_print(_: *const *mut Object, args: *const *mut Object) -> *mut Object {
+    print!("{}", Shown(*args));
+    asena_rt_unit()
+}
+
+unsafe extern "C" fn builtin_read_line(
+    _: *const *mut Object,
+    _: *const *mut Object,
+) -> *mut Object {
+    let _ = std::io::stdout().flush();
+
+    let mut line = String::new();
+    if let Err(error) = std::io::stdin().read_line(&mut line) {
+        panic_with(&format!("could not read from the standard input: {error}"))
+    }
+
+    let line = line.trim_end_matches(['\n', '\r']);
+    alloc(
+        &STRING_CLASS,
+        Value::String(CString::new(line).unwrap_or_default()),
+    )
+}
+
+unsafe extern "C" fn builtin_to_string(
+    _: *const *mut Object,
+    args: *const *mut Object,
+) -> *mut Object {
+    let string = Shown(*args).to_string();
+
+    alloc(
+        &STRING_CLASS,
+        Value::String(CString::new(string).unwrap_or_default()),
+    )
+}
+
+unsafe extern "C" fn builtin_panic(_: *const *mut Object, args: *const *mut Object) -> *mut Object {
+    panic_with(&Shown(*args).to_string())
+}
+
+unsafe extern "C" fn builtin_todo(_: *const *mut Object, args: *const *mut Object) -> *mut Object {
+    panic_with(&format!("not yet implemented: {}", Shown(*args)))
+}
+
+extern "C" fn value_apply(mut this: Object, arguments: Arguments) -> Object {
+    let arguments = (0..arguments.length)
+        .map(|i| arguments.get(i).unwrap() as *const Object as *mut Object)
+        .collect::<Vec<_>>();
+
+    unsafe {
+        let callee = &mut this as *mut Object;
+        let result = asena_rt_apply(callee, arguments.len(), arguments.as_ptr());
+        if result == callee {
+            return this;
+        }
+
+        // The object is moved out of its allocation, its value is still counted by its
+        // `strong_count`, that is moved with it.
+        *Box::from_raw(result)
+    }
+}
+
+extern "C" fn value_to_string(this: Object, _arguments: Arguments) -> *const c_char {
+    let this = &this as *const Object as *mut Object;
+    let string = Shown(this).to_string();
+
+    CString::new(string).unwrap_or_default().into_raw()
+}
+
+/// Displays the value pointed by the object, strings are displayed without quotes at the top
+/// level, like `println` expects.
+struct Shown(*mut Object);
+
+struct Nested(*mut Object);
+
+impl Display for Shown {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match unsafe { value(self.0) } {
+            Value::String(value) => write!(f, "{}", value.to_string_lossy()),
+            _ => write!(f, "{}", Nested(self.0)),
+        }
+    }
+}
+
+impl Display for Nested {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match unsafe { value(self.0) } {
+            Value::Unit => write!(f, "()"),
+            Value::Int(value) => write!(f, "{value}"),
+            Value::Float(value) => write!(f, "{value}"),
+            Value::String(value) => write!(f, "{:?}", value.to_string_lossy()),
+            Value::Closure(_) => write!(f, "<closure>"),
+            Value::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", Nested(*item))?;
+                }
+                write!(f, "]")
+            }
+            Value::Data(name, fields) if fields.is_empty() => {
+                write!(f, "{}", name.to_string_lossy())
+            }
+            Value::Data(name, fields) => {
+                write!(f, "({}", name.to_string_lossy())?;
+                for field in fields {
+                    write!(f, " {}", Nested(*field))?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe fn strong_count(object: *mut Object) -> usize {
+        *(*object).strong_count
+    }
+
+    #[test]
+    fn constructors_retain_and_release_their_fields() {
+        unsafe {
+            let field = asena_rt_int(10);
+            let data = asena_rt_construct(cstring!(b"Some\0"), 1, [field].as_ptr());
+            assert_eq!(strong_count(field), 2);
+
+            let read = asena_rt_field(data, 0);
+            assert_eq!(read, field);
+            assert_eq!(strong_count(field), 3);
+
+            release(read);
+            release(data);
+            assert_eq!(strong_count(field), 1);
+            release(field);
+        }
+    }
+
+    #[test]
+    fn partial_applications_retain_their_arguments() {
+        unsafe extern "C" fn first(_: *const *mut Object, args: *const *mut Object) -> *mut Object {
+            asena_rt_clone(*args)
+        }
+
+        unsafe {
+            let closure = asena_rt_closure(first, 2, 0, std::ptr::null());
+            let argument = asena_rt_int(1);
+            let partial = asena_rt_apply(closure, 1, [argument].as_ptr());
+            assert_eq!(strong_count(argument), 2);
+
+            release(closure);
+            let result = asena_rt_apply(partial, 1, [argument].as_ptr());
+            assert_eq!(result, argument);
+
+            release(partial);
+            release(result);
+            assert_eq!(strong_count(argument), 1);
+            release(argument);
+        }
+    }
+}
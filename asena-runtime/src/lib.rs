@@ -11,6 +11,7 @@ macro_rules! cstring {
 
 pub mod array_list;
 pub mod class;
+pub mod ffi;
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 #[repr(C)]
@@ -39,6 +40,6 @@ pub struct VTable {
 }
 
 pub const ANY_CLASS: Class = Class {
-    name: cstring!(b"Any"),
+    name: cstring!(b"Any\0"),
     superclasses: ArrayList::empty(),
 };
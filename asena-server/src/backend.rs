@@ -1,46 +1,84 @@
 use std::{
     hash::Hash,
-    path::PathBuf,
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
-    },
+    panic::AssertUnwindSafe,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
 };
 
-use asena_ast_db::{
-    db::AstDatabase,
-    package::Package,
-    vfs::{FileSystem, VfsFile, VfsFileData, VfsPath},
-};
+use asena_ast_db::{db::AstDatabase, manifest::MANIFEST_FILE};
 use asena_ast_lowering::db::AstLowerrer;
 use asena_ast_resolver::db::AstResolverDatabase;
+use asena_hir_db::db::HirDatabase;
 use asena_prec::PrecDatabase;
-use asena_report::BoxInternalError;
+use asena_report::{BoxInternalError, DiagnosticKind, Severity};
+use dashmap::DashMap;
 use im::HashSet;
 use itertools::Itertools;
 use ropey::Rope;
+use salsa::{Cancelled, ParallelDatabase, Snapshot};
+use serde_json::Value;
+use tokio::sync::watch;
 use tower_lsp::{
-    jsonrpc::Result,
+    jsonrpc::{Error, ErrorCode, Result},
     lsp_types::{
-        CompletionItem, CompletionOptions, CompletionParams, CompletionResponse, Diagnostic,
-        DidChangeTextDocumentParams, DidOpenTextDocumentParams, ExecuteCommandOptions,
-        InitializeParams, InitializeResult, InitializedParams, MessageType, OneOf, Position, Range,
-        ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, Url, WorkspaceFolder,
-        WorkspaceFoldersServerCapabilities, WorkspaceServerCapabilities,
+        CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+        CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+        CallHierarchyServerCapability, CodeActionParams, CodeActionProviderCapability,
+        CodeActionResponse, CodeDescription, CodeLens, CodeLensOptions, CodeLensParams,
+        CompletionOptions, CompletionParams, CompletionResponse, CreateFilesParams,
+        DeleteFilesParams, Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity,
+        DiagnosticTag, DidChangeTextDocumentParams, DidChangeWatchedFilesParams,
+        DidChangeWatchedFilesRegistrationOptions, DidCloseTextDocumentParams,
+        DidOpenTextDocumentParams, DocumentFormattingParams, DocumentHighlight,
+        DocumentHighlightParams, DocumentRangeFormattingParams, DocumentSymbolParams,
+        DocumentSymbolResponse, ExecuteCommandOptions, ExecuteCommandParams, FileSystemWatcher,
+        FoldingRange, FoldingRangeParams, FoldingRangeProviderCapability, GotoDefinitionParams,
+        GotoDefinitionResponse, Hover, HoverParams, HoverProviderCapability, InitializeParams,
+        InitializeResult, InitializedParams, InlayHint, InlayHintParams, Location, MessageType,
+        NumberOrString, OneOf, Position, Range, ReferenceParams, Registration, RenameFilesParams,
+        RenameParams, SelectionRange, SelectionRangeParams, SelectionRangeProviderCapability,
+        SemanticTokens, SemanticTokensDeltaParams, SemanticTokensFullDeltaResult,
+        SemanticTokensFullOptions, SemanticTokensOptions, SemanticTokensParams,
+        SemanticTokensRangeParams, SemanticTokensRangeResult, SemanticTokensResult,
+        SemanticTokensServerCapabilities, ServerCapabilities, SignatureHelp, SignatureHelpOptions,
+        SignatureHelpParams, SymbolInformation, TextDocumentContentChangeEvent,
+        TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url, WorkspaceEdit,
+        WorkspaceFileOperationsServerCapabilities, WorkspaceFoldersServerCapabilities,
+        WorkspaceServerCapabilities, WorkspaceSymbolParams,
     },
     Client, LanguageServer,
 };
 
+use crate::{
+    ide_db::IdeDatabase,
+    run::RUN_COMMAND,
+    workspace::{file_path, Workspace},
+};
+
 #[derive(Debug, Clone)]
 pub struct Backend {
     pub client: Client,
-    pub db: Arc<crate::ide_db::IdeDatabase>,
-    pub workspace_ready: Arc<AtomicBool>,
-}
 
-unsafe impl Send for Backend {}
+    /// The database is only written by the changes of the documents, one at a time, the requests
+    /// are answered with snapshots of it.
+    pub db: Arc<Mutex<IdeDatabase>>,
+
+    /// Becomes `true` when all the workspaces are loaded.
+    pub workspace_ready: Arc<watch::Sender<bool>>,
 
-unsafe impl Sync for Backend {}
+    /// The text of the opened documents, kept in sync with the editor by applying the changes.
+    pub documents: Arc<DashMap<Url, Rope>>,
+
+    /// The last version of the opened documents, their diagnostics are published again with it
+    /// when the package is loaded again.
+    pub versions: Arc<DashMap<Url, i32>>,
+
+    /// The last semantic tokens sent for each document, to compute the deltas.
+    pub semantic_tokens: Arc<DashMap<Url, SemanticTokens>>,
+
+    /// The workspace folders, by their root directories.
+    pub workspaces: Arc<DashMap<PathBuf, Workspace>>,
+}
 
 #[tower_lsp::async_trait]
 impl LanguageServer for Backend {
@@ -49,9 +87,9 @@ impl LanguageServer for Backend {
             server_info: None,
             offset_encoding: None,
             capabilities: ServerCapabilities {
-                inlay_hint_provider: Some(OneOf::Left(false)),
+                inlay_hint_provider: Some(OneOf::Left(true)),
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::FULL,
+                    TextDocumentSyncKind::INCREMENTAL,
                 )),
                 completion_provider: Some(CompletionOptions {
                     resolve_provider: Some(false),
@@ -61,7 +99,7 @@ impl LanguageServer for Backend {
                     completion_item: None,
                 }),
                 execute_command_provider: Some(ExecuteCommandOptions {
-                    commands: vec![],
+                    commands: vec![RUN_COMMAND.to_string()],
                     work_done_progress_options: Default::default(),
                 }),
                 workspace: Some(WorkspaceServerCapabilities {
@@ -69,53 +107,440 @@ impl LanguageServer for Backend {
                         supported: Some(true),
                         change_notifications: Some(OneOf::Left(true)),
                     }),
-                    file_operations: None,
+                    file_operations: Some(WorkspaceFileOperationsServerCapabilities {
+                        did_create: Some(Self::file_operations()),
+                        did_rename: Some(Self::file_operations()),
+                        did_delete: Some(Self::file_operations()),
+                        ..Default::default()
+                    }),
                 }),
-                semantic_tokens_provider: None,
+                semantic_tokens_provider: Some(
+                    SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        SemanticTokensOptions {
+                            work_done_progress_options: Default::default(),
+                            legend: Self::semantic_tokens_legend(),
+                            range: Some(true),
+                            full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                        },
+                    ),
+                ),
                 definition_provider: Some(OneOf::Left(true)),
                 references_provider: Some(OneOf::Left(true)),
+                document_highlight_provider: Some(OneOf::Left(true)),
                 rename_provider: Some(OneOf::Left(true)),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                signature_help_provider: Some(SignatureHelpOptions {
+                    trigger_characters: Some(vec![" ".to_string(), "(".to_string()]),
+                    retrigger_characters: None,
+                    work_done_progress_options: Default::default(),
+                }),
+                call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+                code_lens_provider: Some(CodeLensOptions {
+                    resolve_provider: Some(false),
+                }),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                document_formatting_provider: Some(OneOf::Left(true)),
+                document_range_formatting_provider: Some(OneOf::Left(true)),
+                folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+                selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
                 ..ServerCapabilities::default()
             },
         })
     }
 
     async fn initialized(&self, _params: InitializedParams) {
-        let Ok(Some(workspaces)) = self.client.workspace_folders().await else {
-            return;
+        let watchers = DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![
+                FileSystemWatcher {
+                    glob_pattern: "**/*.ase".to_string().into(),
+                    kind: None,
+                },
+                FileSystemWatcher {
+                    glob_pattern: format!("**/{MANIFEST_FILE}").into(),
+                    kind: None,
+                },
+            ],
+        };
+        let registration = Registration {
+            id: "asena-watched-files".into(),
+            method: "workspace/didChangeWatchedFiles".into(),
+            register_options: serde_json::to_value(watchers).ok(),
         };
+        self.client
+            .register_capability(vec![registration])
+            .await
+            .ok();
 
-        for workspace in workspaces {
-            self.load_workspace(workspace).await.ok();
+        if let Ok(Some(workspaces)) = self.client.workspace_folders().await {
+            for workspace in workspaces {
+                self.load_workspace(workspace).await;
+            }
         }
+
+        self.workspace_ready.send_replace(true);
+    }
+
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        let params = params.text_document_position;
+        let items = self
+            .run(move |backend, db| {
+                backend.completions_at(db, &params.text_document.uri, params.position)
+            })
+            .await?;
+
+        Ok(Some(CompletionResponse::Array(items)))
+    }
+
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let params = params.text_document_position_params;
+
+        self.run(move |backend, db| {
+            let uri = &params.text_document.uri;
+
+            backend
+                .hole_hover(db, uri, params.position)
+                .or_else(|| backend.hover_at(db, uri, params.position))
+        })
+        .await
+    }
+
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        let params = params.text_document_position_params;
+
+        self.run(move |backend, db| {
+            backend.signature_help_at(db, &params.text_document.uri, params.position)
+        })
+        .await
+    }
+
+    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        let hints = self
+            .run(move |backend, db| {
+                backend.inlay_hints_at(db, &params.text_document.uri, params.range)
+            })
+            .await?;
+
+        Ok(Some(hints))
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        self.run(move |backend, db| {
+            let uri = &params.text_document.uri;
+            let mut actions = backend.quickfix_actions(db, uri, params.range);
+            actions.extend(backend.hole_actions(db, uri, params.range));
+
+            Some(actions)
+        })
+        .await
+    }
+
+    async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+        let lenses = self
+            .run(move |backend, db| backend.code_lenses_at(db, &params.text_document.uri))
+            .await?;
+
+        Ok(Some(lenses))
+    }
+
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<Value>> {
+        match params.command.as_str() {
+            RUN_COMMAND => {
+                let (uri, entry) =
+                    serde_json::from_value::<(Url, String)>(Value::Array(params.arguments))
+                        .map_err(|error| Error::invalid_params(error.to_string()))?;
+
+                // The program can run for a long time, so the request is answered right away,
+                // and the output is logged as it's written.
+                let backend = self.clone();
+                tokio::spawn(async move { backend.run_binding(&uri, &entry).await });
+
+                Ok(None)
+            }
+            command => Err(Error::invalid_params(format!("unknown command: {command}"))),
+        }
+    }
+
+    async fn goto_definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> Result<Option<GotoDefinitionResponse>> {
+        let params = params.text_document_position_params;
+        let location = self
+            .run(move |backend, db| {
+                backend.goto_definition_at(db, &params.text_document.uri, params.position)
+            })
+            .await?;
+
+        Ok(location.map(GotoDefinitionResponse::Scalar))
+    }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let include_declaration = params.context.include_declaration;
+        let params = params.text_document_position;
+        let locations = self
+            .run(move |backend, db| {
+                backend.references_at(
+                    db,
+                    &params.text_document.uri,
+                    params.position,
+                    include_declaration,
+                )
+            })
+            .await?;
+
+        Ok(Some(locations))
+    }
+
+    async fn document_highlight(
+        &self,
+        params: DocumentHighlightParams,
+    ) -> Result<Option<Vec<DocumentHighlight>>> {
+        let params = params.text_document_position_params;
+        let highlights = self
+            .run(move |backend, db| {
+                backend.document_highlights_at(db, &params.text_document.uri, params.position)
+            })
+            .await?;
+
+        Ok(Some(highlights))
+    }
+
+    async fn prepare_call_hierarchy(
+        &self,
+        params: CallHierarchyPrepareParams,
+    ) -> Result<Option<Vec<CallHierarchyItem>>> {
+        let params = params.text_document_position_params;
+        let items = self
+            .run(move |backend, db| {
+                backend.prepare_call_hierarchy_at(db, &params.text_document.uri, params.position)
+            })
+            .await?;
+
+        Ok(Some(items))
+    }
+
+    async fn incoming_calls(
+        &self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyIncomingCall>>> {
+        let calls = self
+            .run(move |backend, db| backend.incoming_calls_at(db, &params.item))
+            .await?;
+
+        Ok(Some(calls))
+    }
+
+    async fn outgoing_calls(
+        &self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+        let calls = self
+            .run(move |backend, db| backend.outgoing_calls_at(db, &params.item))
+            .await?;
+
+        Ok(Some(calls))
+    }
+
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        let symbols = self
+            .run(move |backend, db| backend.document_symbols_at(db, &params.text_document.uri))
+            .await?;
+
+        Ok(Some(DocumentSymbolResponse::Nested(symbols)))
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<Vec<SymbolInformation>>> {
+        let symbols = self
+            .run(move |backend, db| backend.workspace_symbols_at(db, &params.query))
+            .await?;
+
+        Ok(Some(symbols))
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let position = params.text_document_position;
+
+        self.run(move |backend, db| {
+            backend
+                .rename_at(
+                    db,
+                    &position.text_document.uri,
+                    position.position,
+                    &params.new_name,
+                )
+                .map_err(|error| Error::invalid_params(error.to_string()))
+        })
+        .await?
+    }
+
+    async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+        self.run(move |backend, _| {
+            backend.formatting_at(&params.text_document.uri, &params.options)
+        })
+        .await
+    }
+
+    async fn range_formatting(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        self.run(move |backend, _| {
+            backend.range_formatting_at(&params.text_document.uri, params.range, &params.options)
+        })
+        .await
+    }
+
+    async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
+        let ranges = self
+            .run(move |backend, db| backend.folding_ranges_at(db, &params.text_document.uri))
+            .await?;
+
+        Ok(Some(ranges))
+    }
+
+    async fn selection_range(
+        &self,
+        params: SelectionRangeParams,
+    ) -> Result<Option<Vec<SelectionRange>>> {
+        let ranges = self
+            .run(move |backend, db| {
+                backend.selection_ranges_at(db, &params.text_document.uri, &params.positions)
+            })
+            .await?;
+
+        Ok(Some(ranges))
+    }
+
+    async fn semantic_tokens_full(
+        &self,
+        params: SemanticTokensParams,
+    ) -> Result<Option<SemanticTokensResult>> {
+        let tokens = self
+            .run(move |backend, db| backend.semantic_tokens_full_at(db, &params.text_document.uri))
+            .await?;
+
+        Ok(Some(SemanticTokensResult::Tokens(tokens)))
     }
 
-    async fn completion(&self, _: CompletionParams) -> Result<Option<CompletionResponse>> {
-        Ok(Some(CompletionResponse::Array(vec![
-            CompletionItem::new_simple("Hello".into(), "World".into()),
-        ])))
+    async fn semantic_tokens_full_delta(
+        &self,
+        params: SemanticTokensDeltaParams,
+    ) -> Result<Option<SemanticTokensFullDeltaResult>> {
+        let tokens = self
+            .run(move |backend, db| {
+                backend.semantic_tokens_delta_at(
+                    db,
+                    &params.text_document.uri,
+                    &params.previous_result_id,
+                )
+            })
+            .await?;
+
+        Ok(Some(tokens))
+    }
+
+    async fn semantic_tokens_range(
+        &self,
+        params: SemanticTokensRangeParams,
+    ) -> Result<Option<SemanticTokensRangeResult>> {
+        let data = self
+            .run(move |backend, db| {
+                backend.semantic_tokens(db, &params.text_document.uri, Some(params.range))
+            })
+            .await?;
+
+        Ok(Some(SemanticTokensRangeResult::Tokens(SemanticTokens {
+            result_id: None,
+            data,
+        })))
     }
 
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        let uri = params.text_document.uri;
+        let rope = Rope::from_str(&params.text_document.text);
+        self.documents.insert(uri.clone(), rope);
+
         self.on_change(TextDocumentItem {
-            uri: params.text_document.uri,
+            uri,
             text: params.text_document.text,
             version: params.text_document.version,
         })
         .await
     }
 
-    async fn did_change(&self, mut params: DidChangeTextDocumentParams) {
-        let text = &mut params.content_changes[0].text;
+    async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        let uri = params.text_document.uri;
+        let text = {
+            let mut rope = self.documents.entry(uri.clone()).or_default();
+            for change in params.content_changes {
+                Self::apply_change(&mut rope, change);
+            }
+
+            rope.to_string()
+        };
 
         self.on_change(TextDocumentItem {
-            uri: params.text_document.uri,
-            text: std::mem::take(text),
+            uri,
+            text,
             version: params.text_document.version,
         })
         .await
     }
 
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let uri = params.text_document.uri;
+        self.documents.remove(&uri);
+        self.versions.remove(&uri);
+        self.semantic_tokens.remove(&uri);
+
+        let _ = self
+            .write(move |db| {
+                if let Some(vfs_file) = Self::file(db, Path::new(uri.path())) {
+                    vfs_file.close(db);
+                }
+            })
+            .await;
+    }
+
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        self.watched_files_changed(params.changes).await;
+    }
+
+    async fn did_create_files(&self, params: CreateFilesParams) {
+        for file in params.files {
+            if let Some(path) = file_path(&file.uri) {
+                self.create_path(path).await;
+            }
+        }
+    }
+
+    async fn did_rename_files(&self, params: RenameFilesParams) {
+        for file in params.files {
+            if let Some(old_path) = file_path(&file.old_uri) {
+                self.delete_path(old_path).await;
+            }
+            if let Some(new_path) = file_path(&file.new_uri) {
+                self.create_path(new_path).await;
+            }
+        }
+    }
+
+    async fn did_delete_files(&self, params: DeleteFilesParams) {
+        for file in params.files {
+            if let Some(path) = file_path(&file.uri) {
+                self.delete_path(path).await;
+            }
+        }
+    }
+
     async fn shutdown(&self) -> Result<()> {
         Ok(())
     }
@@ -128,38 +553,40 @@ pub struct TextDocumentItem {
 }
 
 impl Backend {
+    /// Writes the new text of the document to the database, and publishes its diagnostics. The
+    /// diagnostics of the previous changes, that are still being computed, are cancelled.
     pub async fn on_change(&self, params: TextDocumentItem) {
-        let backend = self.clone();
-
-        backend.info("file opened").await;
+        self.versions.insert(params.uri.clone(), params.version);
+        self.wait_workspace().await;
+
+        // Only the queries that depend on the source of this file are invalidated, as the
+        // source is read from the overlay, that is a salsa input. The documents outside of the
+        // workspace aren't checked, as they don't have a package.
+        let path = PathBuf::from(params.uri.path());
+        let opened = self
+            .write(move |db| {
+                let Some(vfs_file) = Self::file(db, &path) else {
+                    return false;
+                };
+                vfs_file.open(db, Arc::new(params.text));
+                true
+            })
+            .await;
+        if !matches!(opened, Ok(true)) {
+            return;
+        }
 
+        let backend = self.clone();
         tokio::spawn(async move {
-            while let false = backend.workspace_ready.load(Ordering::SeqCst) {}
-
-            backend.info("  file opened within workspace").await;
-
-            let module_ref = backend.db.path_module(PathBuf::from(params.uri.path()));
-            let vfs_file = backend.db.vfs_file(module_ref);
-            let file = backend.db.ast(vfs_file);
-            let file = backend.db.infix_commands(file.into());
-            let file = backend.db.ordered_prec(file.into());
-            let file = backend.db.ast_resolved_file(file.into());
-            let _hir = backend.db.hir_file(file.into());
-
-            let source = backend.db.source(vfs_file);
-            let source = ropey::Rope::from(source.to_string());
-
-            let diagnostics = backend
-                .db
-                .diagnostics(vfs_file)
-                .into_iter()
-                .enumerate()
-                .map(|(order, diagnostic)| OrdDiagnostic { order, diagnostic })
-                .collect::<HashSet<OrdDiagnostic>>()
-                .into_iter()
-                .sorted_by(|d, n| d.order.cmp(&n.order))
-                .filter_map(|d| backend.build_diagnostic(d.diagnostic, &source))
-                .collect_vec();
+            let uri = params.uri.clone();
+
+            // If it's cancelled, a newer change will publish the diagnostics.
+            let Ok(diagnostics) = backend
+                .run(move |backend, db| backend.diagnostics_at(db, &uri))
+                .await
+            else {
+                return;
+            };
 
             backend
                 .client
@@ -168,58 +595,234 @@ impl Backend {
         });
     }
 
+    /// The diagnostics of the document, running all the passes of the compiler over it.
+    pub fn diagnostics_at(&self, db: &IdeDatabase, uri: &Url) -> Vec<Diagnostic> {
+        let Some((vfs_file, source)) = Self::document(db, uri) else {
+            return vec![];
+        };
+
+        let file = db.ast(vfs_file);
+        let file = db.infix_commands(file.into());
+        let file = db.ordered_prec(file.into());
+        let file = db.ast_resolved_file(file.into());
+        let _hir = db.hir_file(file.into());
+        let _holes = db.hir_holes(vfs_file);
+
+        db.diagnostics(vfs_file)
+            .into_iter()
+            .enumerate()
+            .map(|(order, diagnostic)| OrdDiagnostic { order, diagnostic })
+            .collect::<HashSet<OrdDiagnostic>>()
+            .into_iter()
+            .sorted_by(|d, n| d.order.cmp(&n.order))
+            .filter_map(|d| Self::build_diagnostic(db, uri, d.diagnostic, &source))
+            .collect_vec()
+    }
+
+    /// A snapshot of the database. The changes hold the lock of the database until all the
+    /// snapshots are dropped, so it must not be taken while holding another one: the requests take
+    /// a single snapshot in [Backend::run], and pass it along.
+    pub fn snapshot(&self) -> Snapshot<IdeDatabase> {
+        Self::lock(&self.db).snapshot()
+    }
+
+    /// Applies the change to the database. Salsa cancels the requests running on snapshots, and
+    /// waits for them to be dropped, so the change runs in the blocking thread pool. If the change
+    /// panics, it fails with an internal error.
+    pub async fn write<T, F>(&self, change: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut IdeDatabase) -> T + Send + 'static,
+    {
+        let db = self.db.clone();
+
+        tokio::task::spawn_blocking(move || change(&mut Self::lock(&db)))
+            .await
+            .map_err(|_| Error::internal_error())
+    }
+
+    /// Locks the database. If a change panicked while holding it, the lock is recovered, as the
+    /// inputs of salsa are set atomically, and the server keeps answering the other requests.
+    fn lock(db: &Mutex<IdeDatabase>) -> MutexGuard<'_, IdeDatabase> {
+        db.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Runs the request in the blocking thread pool, so the requests run concurrently, over a
+    /// single snapshot of the database. If a change is written while it runs, the request is
+    /// cancelled with `ContentModified`, so the editor can ask it again.
+    pub async fn run<T, F>(&self, request: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Backend, &IdeDatabase) -> T + Send + 'static,
+    {
+        let backend = self.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let db = backend.snapshot();
+            Cancelled::catch(AssertUnwindSafe(|| request(&backend, &db)))
+        })
+        .await;
+
+        match result {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(cancelled)) => Err(Error {
+                code: ErrorCode::from(CONTENT_MODIFIED),
+                message: cancelled.to_string().into(),
+                data: None,
+            }),
+            Err(_) => Err(Error::internal_error()),
+        }
+    }
+
+    /// Waits until all the workspaces are loaded, so the imports of the documents can be resolved.
+    pub async fn wait_workspace(&self) {
+        let mut ready = self.workspace_ready.subscribe();
+        loop {
+            let is_ready = *ready.borrow_and_update();
+            if is_ready || ready.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+
     pub async fn info(&self, message: impl Into<String>) {
         self.client
             .log_message(MessageType::INFO, message.into())
             .await;
     }
 
-    pub async fn load_workspace(&self, workspace: WorkspaceFolder) -> tokio::io::Result<()> {
-        let path = workspace.uri.path();
-        let mut files = tokio::fs::read_dir(path).await?;
-        let vfs = Arc::new(FileSystem {
-            base_dir: Some(path.into()),
+    /// Converts the diagnostic of the compiler to a LSP diagnostic, the children of the diagnostic,
+    /// like the context it was reported in, are sent as related information.
+    pub(crate) fn build_diagnostic(
+        db: &IdeDatabase,
+        uri: &Url,
+        diagnostic: AsenaDiagnostic,
+        rope: &Rope,
+    ) -> Option<Diagnostic> {
+        let message = diagnostic.message.to_string();
+        let range = diagnostic.message.span.range.clone();
+
+        let start = Self::offset_to_position(range.start(), rope)?;
+        let end = Self::offset_to_position(range.end(), rope)?;
+
+        let severity = match diagnostic.kind.severity() {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+            Severity::Info => DiagnosticSeverity::INFORMATION,
+            Severity::Hint => DiagnosticSeverity::HINT,
+        };
+
+        let tags = match diagnostic.kind {
+            DiagnosticKind::Deprecated => Some(vec![DiagnosticTag::DEPRECATED]),
+            DiagnosticKind::Unnecessary => Some(vec![DiagnosticTag::UNNECESSARY]),
+            _ => None,
+        };
+
+        let code = diagnostic.code_name();
+        let code_description = code.as_ref().and_then(|code| {
+            let href = Url::parse(&format!("{DIAGNOSTICS_URL}#{}", code.to_lowercase())).ok()?;
+            Some(CodeDescription { href })
         });
-        let pkg = Package::new(&*self.db, "Local", "0.0.0", vfs);
-        self.info(format!("loading workspace: {path:?}")).await;
-
-        while let Some(entry) = files.next_entry().await? {
-            let path = entry.path();
-            self.info(format!("  -> loading file: {path:?}")).await;
-
-            let name = entry.file_name().to_string_lossy().into_owned();
-            let vfs_path = VfsPath { path };
-            let metadata = entry.metadata().await?;
-            if metadata.is_file() && vfs_path.path.extension().unwrap_or_default() == "ase" {
-                VfsFileData::new(&*self.db, &name, vfs_path, pkg);
-            }
-        }
 
-        self.workspace_ready.store(true, Ordering::SeqCst);
+        let related_information = diagnostic
+            .children
+            .iter()
+            .filter_map(|child| {
+                let span = &child.message.span;
+                let location = match span.file {
+                    Some(_) => Self::location(db, span)?,
+                    None => Location::new(
+                        uri.clone(),
+                        Range::new(
+                            Self::offset_to_position(span.range.start(), rope)?,
+                            Self::offset_to_position(span.range.end(), rope)?,
+                        ),
+                    ),
+                };
+
+                Some(DiagnosticRelatedInformation {
+                    location,
+                    message: child.message.to_string(),
+                })
+            })
+            .collect_vec();
+
+        Some(Diagnostic {
+            range: Range::new(start, end),
+            severity: Some(severity),
+            code: code.map(NumberOrString::String),
+            code_description,
+            source: Some("asena".into()),
+            message,
+            related_information: Some(related_information).filter(|info| !info.is_empty()),
+            tags,
+            data: None,
+        })
+    }
 
-        Ok(())
+    /// Applies the change to the document, the changes without range replace the whole text.
+    pub(crate) fn apply_change(rope: &mut Rope, change: TextDocumentContentChangeEvent) {
+        let Some(range) = change.range else {
+            *rope = Rope::from_str(&change.text);
+            return;
+        };
+
+        let (Some(start), Some(end)) = (
+            Self::position_to_char(range.start, rope),
+            Self::position_to_char(range.end, rope),
+        ) else {
+            return;
+        };
+
+        rope.remove(start..end);
+        rope.insert(start, &change.text);
     }
 
-    fn build_diagnostic(&self, diagnostic: AsenaDiagnostic, rope: &Rope) -> Option<Diagnostic> {
-        let message = diagnostic.message.to_string();
-        let range = diagnostic.message.span.range;
+    /// The byte offset of the position, the characters of the position are counted in UTF-16
+    /// code units, as the protocol requires.
+    pub(crate) fn position_to_offset(position: Position, rope: &Rope) -> Option<usize> {
+        let index = Self::position_to_char(position, rope)?;
 
-        let start = Self::offset_to_position(range.start(), rope)?;
-        let end = Self::offset_to_position(range.end(), rope)?;
+        rope.try_char_to_byte(index).ok()
+    }
+
+    /// The char index of the position, to edit the rope.
+    fn position_to_char(position: Position, rope: &Rope) -> Option<usize> {
+        let line = position.line as usize;
+        if line == rope.len_lines() {
+            return Some(rope.len_chars());
+        }
 
-        Some(Diagnostic::new_simple(Range::new(start, end), message))
+        let first_char_of_line = rope.try_line_to_char(line).ok()?;
+        let line = rope.line(line);
+
+        // The characters past the end of the line are clamped before its line break, as the
+        // protocol requires.
+        let mut len = line.len_chars();
+        while len > 0 && matches!(line.char(len - 1), '\n' | '\r') {
+            len -= 1;
+        }
+        let character = (position.character as usize).min(line.char_to_utf16_cu(len));
+        Some(first_char_of_line + line.utf16_cu_to_char(character))
     }
 
-    fn offset_to_position(offset: usize, rope: &Rope) -> Option<Position> {
-        let line = rope.try_char_to_line(offset).ok()?;
+    /// The position of the byte offset, the characters are counted in UTF-16 code units.
+    pub(crate) fn offset_to_position(offset: usize, rope: &Rope) -> Option<Position> {
+        let index = rope.try_byte_to_char(offset).ok()?;
+        let line = rope.try_char_to_line(index).ok()?;
         let first_char_of_line = rope.try_line_to_char(line).ok()?;
-        let column = offset - first_char_of_line;
+        let column = rope.char_to_utf16_cu(index) - rope.char_to_utf16_cu(first_char_of_line);
         Some(Position::new(line as u32, column as u32))
     }
 }
 
 type AsenaDiagnostic = asena_report::Diagnostic<BoxInternalError>;
 
+/// The documentation of the diagnostic codes, the codes are linked to it.
+const DIAGNOSTICS_URL: &str = "https://github.com/aripiprazole/asena/blob/main/docs/diagnostics.md";
+
+/// The error code of the requests cancelled by a change of the document.
+const CONTENT_MODIFIED: i64 = -32801;
+
 #[derive(Debug, Clone)]
 struct OrdDiagnostic {
     order: usize,
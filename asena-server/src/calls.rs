@@ -0,0 +1,151 @@
+use asena_ast_db::symbols::AstSymbol;
+use asena_ast_resolver::calls::{declaration_of, incoming_calls, outgoing_calls};
+use asena_hir_db::info::signature_help;
+use tower_lsp::lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, Documentation,
+    MarkupContent, MarkupKind, ParameterInformation, ParameterLabel, Position, SignatureHelp,
+    SignatureInformation, Url,
+};
+
+use crate::{backend::Backend, ide_db::IdeDatabase, symbols::symbol_kind};
+
+impl Backend {
+    /// The signature of the call being written at the position, with the parameter of the
+    /// argument at the position highlighted.
+    pub fn signature_help_at(
+        &self,
+        db: &IdeDatabase,
+        uri: &Url,
+        position: Position,
+    ) -> Option<SignatureHelp> {
+        let (file, source) = Self::document(db, uri)?;
+        let offset = Self::position_to_offset(position, &source)?;
+
+        let help = signature_help(db, file, offset)?;
+
+        // The offsets of the parameters in the label are counted in UTF-16 code units.
+        let utf16 = |offset: usize| help.label[..offset].encode_utf16().count() as u32;
+        let parameters = help
+            .parameters
+            .iter()
+            .map(|parameter| ParameterInformation {
+                label: ParameterLabel::LabelOffsets([
+                    utf16(parameter.range.start),
+                    utf16(parameter.range.end),
+                ]),
+                documentation: None,
+            })
+            .collect();
+        let active_parameter = help.active_parameter.map(|index| index as u32);
+        let documentation = (!help.docs.is_empty()).then(|| {
+            Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: help.docs.join("\n"),
+            })
+        });
+
+        Some(SignatureHelp {
+            signatures: vec![SignatureInformation {
+                label: help.label,
+                documentation,
+                parameters: Some(parameters),
+                active_parameter,
+            }],
+            active_signature: Some(0),
+            active_parameter,
+        })
+    }
+
+    /// The declaration of the global at the position, that is the root of the call hierarchy.
+    pub fn prepare_call_hierarchy_at(
+        &self,
+        db: &IdeDatabase,
+        uri: &Url,
+        position: Position,
+    ) -> Vec<CallHierarchyItem> {
+        let Some(def) = Self::definition_at(db, uri, position) else {
+            return vec![];
+        };
+
+        declaration_of(db, def)
+            .and_then(|symbol| Self::call_hierarchy_item(db, symbol))
+            .into_iter()
+            .collect()
+    }
+
+    /// The declarations of the workspace that call the declaration of the item.
+    pub fn incoming_calls_at(
+        &self,
+        db: &IdeDatabase,
+        item: &CallHierarchyItem,
+    ) -> Vec<CallHierarchyIncomingCall> {
+        let Some(def) = Self::definition_at(db, &item.uri, item.selection_range.start) else {
+            return vec![];
+        };
+
+        let Some((file, _)) = Self::document(db, &item.uri) else {
+            return vec![];
+        };
+        let files = Self::workspace_files(db, file);
+
+        incoming_calls(db, &files, def)
+            .into_iter()
+            .filter_map(|call| {
+                let from_ranges = call
+                    .locations
+                    .iter()
+                    .filter_map(|loc| Some(Self::location(db, loc)?.range))
+                    .collect();
+
+                Some(CallHierarchyIncomingCall {
+                    from: Self::call_hierarchy_item(db, call.symbol)?,
+                    from_ranges,
+                })
+            })
+            .collect()
+    }
+
+    /// The declarations called by the declaration of the item.
+    pub fn outgoing_calls_at(
+        &self,
+        db: &IdeDatabase,
+        item: &CallHierarchyItem,
+    ) -> Vec<CallHierarchyOutgoingCall> {
+        let Some(def) = Self::definition_at(db, &item.uri, item.selection_range.start) else {
+            return vec![];
+        };
+
+        outgoing_calls(db, def)
+            .into_iter()
+            .filter_map(|call| {
+                let from_ranges = call
+                    .locations
+                    .iter()
+                    .filter_map(|loc| Some(Self::location(db, loc)?.range))
+                    .collect();
+
+                Some(CallHierarchyOutgoingCall {
+                    to: Self::call_hierarchy_item(db, call.symbol)?,
+                    from_ranges,
+                })
+            })
+            .collect()
+    }
+
+    /// The item of the declaration, the declarations without a file on the disk can't be shown.
+    fn call_hierarchy_item(db: &IdeDatabase, symbol: AstSymbol) -> Option<CallHierarchyItem> {
+        let location = Self::location(db, &symbol.loc)?;
+        let selection = Self::location(db, &symbol.selection)?;
+
+        Some(CallHierarchyItem {
+            name: symbol.name,
+            kind: symbol_kind(symbol.kind),
+            tags: None,
+            detail: None,
+            uri: location.uri,
+            range: location.range,
+            selection_range: selection.range,
+            data: None,
+        })
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::{HashMap, HashSet};
+
+use asena_ast_resolver::completion::{completions, AstCompletion, AstCompletionKind};
+use asena_hir::interner::HirInterner;
+use asena_hir_db::{
+    context::{display_type, globals},
+    hole::hole_at,
+};
+use tower_lsp::lsp_types::{CompletionItem, CompletionItemKind, Position, Url};
+
+use crate::{backend::Backend, ide_db::IdeDatabase};
+
+impl Backend {
+    /// The completions at the position, the ones that fit the type expected at the position
+    /// comes first.
+    pub fn completions_at(
+        &self,
+        db: &IdeDatabase,
+        uri: &Url,
+        position: Position,
+    ) -> Vec<CompletionItem> {
+        let Some((file, source)) = Self::document(db, uri) else {
+            return vec![];
+        };
+        let Some(offset) = Self::position_to_offset(position, &source) else {
+            return vec![];
+        };
+
+        let context = hole_at(db, file, offset);
+        let candidates: HashSet<String> = context
+            .iter()
+            .flat_map(|hole| hole.candidates.iter())
+            .map(|name| db.lookup_intern_name(*name))
+            .collect();
+        let mut details: HashMap<String, String> = globals(db, file)
+            .into_iter()
+            .map(|global| (db.lookup_intern_name(global.name), global.display(db)))
+            .collect();
+        for (name, value) in context.iter().flat_map(|hole| hole.locals.iter()) {
+            if let Some(value) = value {
+                details.insert(db.lookup_intern_name(*name), display_type(db, *value));
+            }
+        }
+
+        completions(db, file, offset)
+            .into_iter()
+            .map(|completion| Self::completion_item(completion, &candidates, &details))
+            .collect()
+    }
+
+    fn completion_item(
+        completion: AstCompletion,
+        candidates: &HashSet<String>,
+        details: &HashMap<String, String>,
+    ) -> CompletionItem {
+        let kind = match completion.kind {
+            AstCompletionKind::Local => CompletionItemKind::VARIABLE,
+            AstCompletionKind::Function => CompletionItemKind::FUNCTION,
+            AstCompletionKind::Constructor => CompletionItemKind::ENUM_MEMBER,
+            AstCompletionKind::Type => CompletionItemKind::CLASS,
+            AstCompletionKind::Module => CompletionItemKind::MODULE,
+            AstCompletionKind::Keyword => CompletionItemKind::KEYWORD,
+        };
+
+        let rank = match completion.kind {
+            _ if candidates.contains(&completion.name) => 0,
+            AstCompletionKind::Local => 1,
+            AstCompletionKind::Function | AstCompletionKind::Constructor => 2,
+            AstCompletionKind::Type | AstCompletionKind::Module => 3,
+            AstCompletionKind::Keyword => 4,
+        };
+
+        CompletionItem {
+            sort_text: Some(format!("{rank}{}", completion.label)),
+            detail: details.get(&completion.name).cloned(),
+            kind: Some(kind),
+            label: completion.label,
+            ..Default::default()
+        }
+    }
+}
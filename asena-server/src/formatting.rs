@@ -0,0 +1,56 @@
+use asena_ast_formatter::{format, format_range, FormatOptions};
+use tower_lsp::lsp_types::{FormattingOptions, Position, Range, TextEdit, Url};
+
+use crate::backend::Backend;
+
+impl Backend {
+    /// Formats the whole document, replacing all its text. The documents with syntax errors
+    /// aren't formatted.
+    pub fn formatting_at(&self, uri: &Url, options: &FormattingOptions) -> Option<Vec<TextEdit>> {
+        let source = self.documents.get(uri)?.clone();
+        let text = source.to_string();
+        let formatted = format(&text, &format_options(options)).ok()?;
+        if formatted == text {
+            return Some(vec![]);
+        }
+
+        let end = Self::offset_to_position(text.len(), &source)?;
+
+        Some(vec![TextEdit::new(
+            Range::new(Position::new(0, 0), end),
+            formatted,
+        )])
+    }
+
+    /// Formats the declarations that intersect the range.
+    pub fn range_formatting_at(
+        &self,
+        uri: &Url,
+        range: Range,
+        options: &FormattingOptions,
+    ) -> Option<Vec<TextEdit>> {
+        let source = self.documents.get(uri)?.clone();
+        let text = source.to_string();
+        let start = Self::position_to_offset(range.start, &source)?;
+        let end = Self::position_to_offset(range.end, &source)?;
+
+        let (replaced, formatted) =
+            format_range(&text, start..end, &format_options(options)).ok()??;
+        if text[replaced.clone()] == formatted {
+            return Some(vec![]);
+        }
+
+        let start = Self::offset_to_position(replaced.start, &source)?;
+        let end = Self::offset_to_position(replaced.end, &source)?;
+
+        Some(vec![TextEdit::new(Range::new(start, end), formatted)])
+    }
+}
+
+/// The indentation of the editor, the width of the lines is the default one.
+fn format_options(options: &FormattingOptions) -> FormatOptions {
+    FormatOptions {
+        indent: options.tab_size as usize,
+        ..Default::default()
+    }
+}
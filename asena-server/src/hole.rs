@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use asena_hir::interner::HirInterner;
+use asena_hir_db::{
+    db::HirDatabase,
+    hole::{describe, HirHole},
+};
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Hover, HoverContents, MarkupContent,
+    MarkupKind, Position, Range, TextEdit, Url, WorkspaceEdit,
+};
+
+use crate::{backend::Backend, ide_db::IdeDatabase};
+
+impl Backend {
+    /// The holes of the document, with their ranges in the document.
+    pub fn holes(db: &IdeDatabase, uri: &Url) -> Vec<(Range, HirHole)> {
+        let Some((vfs_file, source)) = Self::document(db, uri) else {
+            return vec![];
+        };
+
+        db.hir_holes(vfs_file)
+            .into_iter()
+            .filter_map(|hole| {
+                let range = hole.span.original.range.clone();
+                let start = Self::offset_to_position(range.start(), &source)?;
+                let end = Self::offset_to_position(range.end(), &source)?;
+
+                Some((Range::new(start, end), hole))
+            })
+            .collect()
+    }
+
+    /// Shows the expected type, the locals and the candidates of the hole under the cursor.
+    pub fn hole_hover(&self, db: &IdeDatabase, uri: &Url, position: Position) -> Option<Hover> {
+        let (range, hole) = Self::holes(db, uri)
+            .into_iter()
+            .find(|(range, _)| range.start <= position && position <= range.end)?;
+
+        let info = describe(db, &hole);
+        let value = format!(
+            "```asena\n? : {}\n```\n\n**Locals:** {}\n\n**Candidates:** {}",
+            info.expected, info.locals, info.candidates
+        );
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: Some(range),
+        })
+    }
+
+    /// Code actions that fill the holes in the range with one of their candidates.
+    pub fn hole_actions(
+        &self,
+        db: &IdeDatabase,
+        uri: &Url,
+        range: Range,
+    ) -> Vec<CodeActionOrCommand> {
+        Self::holes(db, uri)
+            .into_iter()
+            .filter(|(hole, _)| hole.start <= range.end && range.start <= hole.end)
+            .flat_map(|(hole_range, hole)| {
+                hole.candidates.into_iter().map(move |candidate| {
+                    let name = db.lookup_intern_name(candidate);
+                    let edit = TextEdit::new(hole_range, name.clone());
+
+                    CodeActionOrCommand::CodeAction(CodeAction {
+                        title: format!("Fill hole with `{name}`"),
+                        kind: Some(CodeActionKind::QUICKFIX),
+                        edit: Some(WorkspaceEdit {
+                            changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    })
+                })
+            })
+            .collect()
+    }
+}
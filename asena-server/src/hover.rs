@@ -0,0 +1,51 @@
+use asena_ast_db::db::AstDatabase;
+use asena_ast_resolver::references::definition_at;
+use asena_hir_db::{
+    context::{display_type, type_at},
+    info::def_info,
+};
+use tower_lsp::lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position, Url};
+
+use crate::{backend::Backend, ide_db::IdeDatabase};
+
+impl Backend {
+    /// Shows the signature and the documentation of the global under the cursor, or the type of
+    /// the local, and the type of the expression under the cursor, when it's known.
+    pub fn hover_at(&self, db: &IdeDatabase, uri: &Url, position: Position) -> Option<Hover> {
+        let (file, source) = Self::document(db, uri)?;
+        let offset = Self::position_to_offset(position, &source)?;
+
+        let expr_type = type_at(db, file, offset).map(|value| display_type(db, value));
+        let def = definition_at(db, file, offset);
+
+        let mut sections = vec![];
+        match def.map(|def| db.lookup_intern_def(def).name.to_string()) {
+            Some(name) => match def_info(db, file, &name) {
+                Some(info) => {
+                    sections.push(format!("```asena\n{}\n```", info.signature));
+                    sections.extend(info.docs);
+                    if let Some(value) = expr_type {
+                        sections.push(format!("**Type:** `{value}`"));
+                    }
+                }
+                None => {
+                    let value = expr_type.unwrap_or_else(|| "_".into());
+                    sections.push(format!("```asena\n{name} : {value}\n```"));
+                }
+            },
+            None => sections.extend(expr_type.map(|value| format!("```asena\n{value}\n```"))),
+        }
+
+        if sections.is_empty() {
+            return None;
+        }
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: sections.join("\n\n"),
+            }),
+            range: None,
+        })
+    }
+}
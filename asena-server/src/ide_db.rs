@@ -1,9 +1,11 @@
 use asena_ast_db::{
     db::{AstDatabase, AstDatabaseStorage},
+    stdlib::load_std,
     vfs::VfsFile,
 };
 use asena_ast_lowering::db::{AstLowerrer, AstLowerrerStorage};
 use asena_ast_resolver::db::{AstResolverDatabase, AstResolverStorage};
+use asena_highlight::db::HighlightStorage;
 use asena_hir::interner::HirStorage;
 use asena_hir_db::db::HirDatabaseStorage;
 use asena_prec::{db::PrecStorage, PrecDatabase};
@@ -19,14 +21,26 @@ use std::{
     HirDatabaseStorage,
     AstLowerrerStorage,
     AstResolverStorage,
+    HighlightStorage,
     HirStorage
 )]
-#[derive(Default)]
 pub struct IdeDatabase {
     pub storage: salsa::Storage<IdeDatabase>,
     pub logs: Mutex<Vec<salsa::Event>>,
 }
 
+impl Default for IdeDatabase {
+    /// Creates an empty database, with the standard library loaded.
+    fn default() -> Self {
+        let mut db = Self {
+            storage: Default::default(),
+            logs: Default::default(),
+        };
+        load_std(&mut db);
+        db
+    }
+}
+
 impl IdeDatabase {
     pub fn run_pipeline_catching(&self, vfs_file: VfsFile) {
         let db = AssertUnwindSafe(self);
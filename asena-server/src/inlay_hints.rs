@@ -0,0 +1,50 @@
+use asena_hir::interner::HirInterner;
+use asena_hir_db::{
+    context::display_type,
+    info::{hints, HirHintKind},
+};
+use tower_lsp::lsp_types::{InlayHint, InlayHintKind, InlayHintLabel, Range, Url};
+
+use crate::{backend::Backend, ide_db::IdeDatabase};
+
+impl Backend {
+    /// The inlay hints in the range: the types after the bindings, and the names of the
+    /// parameters before the arguments.
+    pub fn inlay_hints_at(&self, db: &IdeDatabase, uri: &Url, range: Range) -> Vec<InlayHint> {
+        let Some((file, source)) = Self::document(db, uri) else {
+            return vec![];
+        };
+
+        hints(db, file)
+            .into_iter()
+            .filter_map(|hint| {
+                let (label, offset, kind) = match hint.kind {
+                    HirHintKind::Type(value) => {
+                        let label = format!(": {}", display_type(db, value));
+                        (label, hint.loc.range.end(), InlayHintKind::TYPE)
+                    }
+                    HirHintKind::Parameter(name) => {
+                        let label = format!("{}:", db.lookup_intern_name(name));
+                        (label, hint.loc.range.start(), InlayHintKind::PARAMETER)
+                    }
+                };
+
+                let position = Self::offset_to_position(offset, &source)?;
+                if position < range.start || position > range.end {
+                    return None;
+                }
+
+                Some(InlayHint {
+                    position,
+                    label: InlayHintLabel::String(label),
+                    kind: Some(kind),
+                    text_edits: None,
+                    tooltip: None,
+                    padding_left: None,
+                    padding_right: Some(kind == InlayHintKind::PARAMETER),
+                    data: None,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,76 @@
+use asena_ast_db::symbols::{document_symbols, AstSymbolKind};
+use asena_ast_resolver::references::{declared_def, find_reads};
+use asena_hir_lowering::c::ENTRY_POINTS;
+use serde_json::json;
+use tower_lsp::lsp_types::{CodeLens, Command, Url};
+
+use crate::{backend::Backend, ide_db::IdeDatabase, run::RUN_COMMAND};
+
+/// The command of the client that shows the references, its arguments are the uri of the file,
+/// the position of the name, and the locations of the references.
+pub const SHOW_REFERENCES_COMMAND: &str = "asena.showReferences";
+
+impl Backend {
+    /// The number of references of each top level declaration, and the lenses that run the entry
+    /// points and the tests of the document.
+    pub fn code_lenses_at(&self, db: &IdeDatabase, uri: &Url) -> Vec<CodeLens> {
+        let Some((file, source)) = Self::document(db, uri) else {
+            return vec![];
+        };
+        let files = Self::workspace_files(db, file);
+
+        let mut lenses = vec![];
+        for symbol in document_symbols(db, file) {
+            let Some(range) = Self::range(&symbol.selection, &source) else {
+                continue;
+            };
+
+            if symbol.kind == AstSymbolKind::Function && is_runnable(&symbol.name) {
+                lenses.push(CodeLens {
+                    range,
+                    command: Some(Command {
+                        title: "▶ Run".into(),
+                        command: RUN_COMMAND.into(),
+                        arguments: Some(vec![json!(uri), json!(symbol.name)]),
+                    }),
+                    data: None,
+                });
+            }
+
+            let Some(def) = declared_def(db, file, &symbol.selection) else {
+                continue;
+            };
+
+            let locations = find_reads(db, &files, def)
+                .into_iter()
+                .filter_map(|loc| Self::location(db, &loc))
+                .collect::<Vec<_>>();
+            let title = match locations.len() {
+                1 => "1 reference".to_string(),
+                count => format!("{count} references"),
+            };
+
+            lenses.push(CodeLens {
+                range,
+                command: Some(Command {
+                    title,
+                    command: SHOW_REFERENCES_COMMAND.into(),
+                    arguments: Some(vec![json!(uri), json!(range.start), json!(locations)]),
+                }),
+                data: None,
+            });
+        }
+
+        lenses
+    }
+}
+
+/// If the binding can be run: the entry points, like `Main`, and the tests, that are the bindings
+/// named with the `test` prefix, like `testAppend`.
+fn is_runnable(name: &str) -> bool {
+    let is_test = name
+        .strip_prefix("test")
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(|c: char| c.is_uppercase()));
+
+    ENTRY_POINTS.contains(&name) || is_test
+}
@@ -1,29 +1,56 @@
 #![feature(try_blocks)]
 
-use std::sync::{atomic::AtomicBool, Arc};
+use std::sync::{Arc, Mutex};
 
-use tower_lsp::{LspService, Server};
+use tokio::sync::watch;
+use tower_lsp::{ClientSocket, LspService, Server};
 
 use crate::backend::Backend;
 
 pub mod backend;
+pub mod calls;
+pub mod completion;
+pub mod formatting;
+pub mod hole;
+pub mod hover;
 pub mod ide_db;
+pub mod inlay_hints;
+pub mod lens;
+pub mod navigation;
+pub mod quickfix;
+pub mod run;
+pub mod semantic_tokens;
+pub mod structure;
+pub mod symbols;
+pub mod workspace;
+
+#[cfg(test)]
+mod tests;
+
+/// The language server, with an empty database. The editor talks to it through the socket.
+pub fn service() -> (LspService<Backend>, ClientSocket) {
+    let db = ide_db::IdeDatabase::default();
+
+    LspService::build(|client| Backend {
+        client,
+        db: Arc::new(Mutex::new(db)),
+        workspace_ready: Arc::new(watch::channel(false).0),
+        documents: Default::default(),
+        versions: Default::default(),
+        semantic_tokens: Default::default(),
+        workspaces: Default::default(),
+    })
+    .finish()
+}
 
 #[tokio::main]
 async fn main() {
     env_logger::init();
 
-    let db = ide_db::IdeDatabase::default();
-
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
 
-    let (service, socket) = LspService::build(|client| Backend {
-        client,
-        db: Arc::new(db),
-        workspace_ready: Arc::new(AtomicBool::new(false)),
-    })
-    .finish();
+    let (service, socket) = service();
 
     Server::new(stdin, stdout, socket).serve(service).await;
 }
@@ -0,0 +1,172 @@
+use std::{collections::HashMap, path::Path};
+
+use asena_ast_db::{db::AstDatabase, def::DefWithId, vfs::VfsFile, ModuleRef};
+use asena_ast_resolver::{
+    error::ResolutionError,
+    references::{definition_at, document_highlights, find_references, rename, AstReferenceKind},
+};
+use asena_span::Loc;
+use ropey::Rope;
+use tower_lsp::lsp_types::{
+    DocumentHighlight, DocumentHighlightKind, Location, Position, Range, TextEdit, Url,
+    WorkspaceEdit,
+};
+
+use crate::{backend::Backend, ide_db::IdeDatabase};
+
+impl Backend {
+    /// The file of the document, and its source. The documents outside of the workspace aren't
+    /// files of the database, so they don't have one.
+    pub fn document(db: &IdeDatabase, uri: &Url) -> Option<(VfsFile, Rope)> {
+        let vfs_file = Self::file(db, Path::new(uri.path()))?;
+        let source = Rope::from(db.source(vfs_file).to_string());
+
+        Some((vfs_file, source))
+    }
+
+    /// The file of the path, if it's registered in the database. Unlike
+    /// [AstDatabase::path_module], it doesn't report the unknown paths, as they are expected, like
+    /// the files opened outside of the workspace.
+    pub fn file(db: &IdeDatabase, path: &Path) -> Option<VfsFile> {
+        match db.build_system().file_module(&path.to_path_buf())? {
+            ModuleRef::Found(vfs_file) => Some(vfs_file),
+            ModuleRef::NotFound => None,
+        }
+    }
+
+    /// The files of the package of the document, the references are searched in all of them.
+    pub fn workspace_files(db: &IdeDatabase, file: VfsFile) -> Vec<VfsFile> {
+        let pkg = db.lookup_intern_vfs_file(file).pkg;
+
+        pkg.files(db).iter().map(|file| *file).collect()
+    }
+
+    /// Converts the location to a LSP location, the locations without a file on the disk, like
+    /// the ones of the standard library, can't be shown.
+    pub fn location(db: &IdeDatabase, loc: &Loc) -> Option<Location> {
+        let path = loc.file.clone()?;
+        let uri = Url::from_file_path(&path).ok()?;
+        let vfs_file = Self::file(db, &path)?;
+        let source = Rope::from(db.source(vfs_file).to_string());
+
+        let start = Self::offset_to_position(loc.range.start(), &source)?;
+        let end = Self::offset_to_position(loc.range.end(), &source)?;
+
+        Some(Location::new(uri, Range::new(start, end)))
+    }
+
+    pub(crate) fn definition_at(
+        db: &IdeDatabase,
+        uri: &Url,
+        position: Position,
+    ) -> Option<DefWithId> {
+        let (file, source) = Self::document(db, uri)?;
+        let offset = Self::position_to_offset(position, &source)?;
+
+        definition_at(db, file, offset)
+    }
+
+    pub fn goto_definition_at(
+        &self,
+        db: &IdeDatabase,
+        uri: &Url,
+        position: Position,
+    ) -> Option<Location> {
+        let def = Self::definition_at(db, uri, position)?;
+        let data = db.lookup_intern_def(def);
+
+        Self::location(db, &data.token.span)
+    }
+
+    pub fn references_at(
+        &self,
+        db: &IdeDatabase,
+        uri: &Url,
+        position: Position,
+        include_declaration: bool,
+    ) -> Vec<Location> {
+        let Some(def) = Self::definition_at(db, uri, position) else {
+            return vec![];
+        };
+
+        let Some((file, _)) = Self::document(db, uri) else {
+            return vec![];
+        };
+        let files = Self::workspace_files(db, file);
+        let declaration = db.lookup_intern_def(def).token.span;
+
+        find_references(db, &files, def)
+            .into_iter()
+            .filter(|loc| include_declaration || *loc != declaration)
+            .filter_map(|loc| Self::location(db, &loc))
+            .collect()
+    }
+
+    /// The reads and the bindings of the name at the position, in the document.
+    pub fn document_highlights_at(
+        &self,
+        db: &IdeDatabase,
+        uri: &Url,
+        position: Position,
+    ) -> Vec<DocumentHighlight> {
+        let Some(def) = Self::definition_at(db, uri, position) else {
+            return vec![];
+        };
+
+        let Some((file, source)) = Self::document(db, uri) else {
+            return vec![];
+        };
+
+        document_highlights(db, file, def)
+            .into_iter()
+            .filter_map(|highlight| {
+                let start = Self::offset_to_position(highlight.loc.range.start(), &source)?;
+                let end = Self::offset_to_position(highlight.loc.range.end(), &source)?;
+                let kind = match highlight.kind {
+                    AstReferenceKind::Read => DocumentHighlightKind::READ,
+                    AstReferenceKind::Binding => DocumentHighlightKind::WRITE,
+                };
+
+                Some(DocumentHighlight {
+                    range: Range::new(start, end),
+                    kind: Some(kind),
+                })
+            })
+            .collect()
+    }
+
+    /// Renames the name at the position, and all its references in the workspace.
+    pub fn rename_at(
+        &self,
+        db: &IdeDatabase,
+        uri: &Url,
+        position: Position,
+        new_name: &str,
+    ) -> Result<Option<WorkspaceEdit>, ResolutionError> {
+        let Some(def) = Self::definition_at(db, uri, position) else {
+            return Ok(None);
+        };
+
+        let Some((file, _)) = Self::document(db, uri) else {
+            return Ok(None);
+        };
+        let files = Self::workspace_files(db, file);
+
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        for edit in rename(db, &files, def, new_name)? {
+            let Some(location) = Self::location(db, &edit.loc) else {
+                continue;
+            };
+
+            changes
+                .entry(location.uri)
+                .or_default()
+                .push(TextEdit::new(location.range, edit.text));
+        }
+
+        Ok(Some(WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        }))
+    }
+}
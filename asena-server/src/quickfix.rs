@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use asena_ast_db::db::AstDatabase;
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Range, TextEdit, Url, WorkspaceEdit,
+};
+
+use crate::{backend::Backend, ide_db::IdeDatabase};
+
+impl Backend {
+    /// Code actions that applies the quickfixes of the diagnostics in the range.
+    pub fn quickfix_actions(
+        &self,
+        db: &IdeDatabase,
+        uri: &Url,
+        range: Range,
+    ) -> Vec<CodeActionOrCommand> {
+        let Some((file, source)) = Self::document(db, uri) else {
+            return vec![];
+        };
+
+        let mut actions = vec![];
+        for diagnostic in db.diagnostics(file) {
+            let quickfixes = diagnostic.quickfixes.clone();
+            let Some(lsp_diagnostic) = Self::build_diagnostic(db, uri, diagnostic, &source) else {
+                continue;
+            };
+
+            if lsp_diagnostic.range.end < range.start || lsp_diagnostic.range.start > range.end {
+                continue;
+            }
+
+            for quickfix in quickfixes {
+                let edit = quickfix.edit();
+                let (Some(start), Some(end)) = (
+                    Self::offset_to_position(edit.start, &source),
+                    Self::offset_to_position(edit.end, &source),
+                ) else {
+                    continue;
+                };
+
+                let edit = TextEdit::new(Range::new(start, end), edit.text);
+
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: quickfix.to_string(),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![lsp_diagnostic.clone()]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                        ..Default::default()
+                    }),
+                    is_preferred: Some(true),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        actions
+    }
+}
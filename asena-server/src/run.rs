@@ -0,0 +1,168 @@
+use std::{
+    io::{BufRead, BufReader, Read},
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use asena_ast_db::manifest::Manifest;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tower_lsp::lsp_types::{MessageType, Url};
+
+use crate::backend::Backend;
+
+/// The command that builds the package of a binding, and runs the binding, its arguments are the
+/// uri of the file, and the name of the binding.
+pub const RUN_COMMAND: &str = "asena.run";
+
+impl Backend {
+    /// Builds the package of the file with the C backend, with the binding as the entry point,
+    /// and runs it. The output of the compiler, and of the program, is streamed to the log of the
+    /// client.
+    pub async fn run_binding(&self, uri: &Url, entry: &str) {
+        let Ok(path) = uri.to_file_path() else {
+            return;
+        };
+
+        let root = Manifest::find_root(&path);
+        let asena = std::env::var("ASENA_PATH").unwrap_or_else(|_| "asena".into());
+
+        self.info(format!("building `{entry}` of {}", root.display()))
+            .await;
+
+        let mut build = Command::new(asena);
+        build
+            .args(["build", "--backend", "c", "--message-format", "json"])
+            .args(["--entry", entry])
+            .arg("--dir")
+            .arg(&root);
+        let Some(lines) = self.stream(build).await else {
+            return;
+        };
+
+        // The executable is printed as an artifact, it's in the `debug` directory of the
+        // `target` directory of the package by default.
+        let executable = lines
+            .iter()
+            .find_map(|line| artifact_path(line))
+            .or_else(|| {
+                let manifest = Manifest::read(&root).ok()?;
+                Some(root.join("target").join("debug").join(manifest.name))
+            });
+        let Some(executable) = executable else {
+            let message = format!("could not find the executable of {}", root.display());
+            self.client.log_message(MessageType::ERROR, message).await;
+            return;
+        };
+
+        self.info(format!("running {}", executable.display())).await;
+        self.stream(Command::new(executable)).await;
+    }
+
+    /// Runs the command, and logs the lines of its standard output, and of its standard error,
+    /// as they are written. Returns the lines of the standard output, if the command succeeded.
+    async fn stream(&self, mut command: Command) -> Option<Vec<String>> {
+        let program = command.get_program().to_string_lossy().to_string();
+        let child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(error) => {
+                let message = format!("could not run `{program}`: {error}");
+                self.client.log_message(MessageType::ERROR, message).await;
+                return None;
+            }
+        };
+
+        // The pipes are read in their own threads, as they block, and the lines are sent to the
+        // client from here.
+        let (sender, mut receiver) = mpsc::channel(64);
+        let readers = [
+            child
+                .stdout
+                .take()
+                .map(|stdout| read_lines(stdout, MessageType::INFO, &sender)),
+            child
+                .stderr
+                .take()
+                .map(|stderr| read_lines(stderr, MessageType::ERROR, &sender)),
+        ];
+        drop(sender);
+
+        let mut stdout = vec![];
+        while let Some((kind, line)) = receiver.recv().await {
+            let message = diagnostic_message(&line).unwrap_or_else(|| line.clone());
+            self.client.log_message(kind, message).await;
+            if kind == MessageType::INFO {
+                stdout.push(line);
+            }
+        }
+
+        for reader in readers.into_iter().flatten() {
+            let _ = reader.join();
+        }
+
+        let status = tokio::task::spawn_blocking(move || child.wait()).await;
+        match status {
+            Ok(Ok(status)) if status.success() => Some(stdout),
+            Ok(Ok(status)) => {
+                let message = format!("`{program}` failed with {status}");
+                self.client.log_message(MessageType::ERROR, message).await;
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Reads the lines of the pipe in a new thread, and sends them with the kind of message they
+/// are logged with.
+fn read_lines<R>(
+    pipe: R,
+    kind: MessageType,
+    sender: &mpsc::Sender<(MessageType, String)>,
+) -> std::thread::JoinHandle<()>
+where
+    R: Read + Send + 'static,
+{
+    let sender = sender.clone();
+
+    std::thread::spawn(move || {
+        for line in BufReader::new(pipe).lines().map_while(Result::ok) {
+            if sender.blocking_send((kind, line)).is_err() {
+                return;
+            }
+        }
+    })
+}
+
+/// The path of the artifact of the JSON line printed by `asena build --message-format json`.
+fn artifact_path(line: &str) -> Option<PathBuf> {
+    let value = serde_json::from_str::<Value>(line).ok()?;
+    if value["type"] != "artifact" {
+        return None;
+    }
+
+    value["path"].as_str().map(PathBuf::from)
+}
+
+/// The diagnostic of the JSON line printed by `asena build --message-format json`, in the
+/// `file:line:column: severity: message` format of the compilers.
+fn diagnostic_message(line: &str) -> Option<String> {
+    let value = serde_json::from_str::<Value>(line).ok()?;
+    if value["type"] != "diagnostic" {
+        return None;
+    }
+
+    Some(format!(
+        "{}:{}:{}: {}: {}",
+        value["file"].as_str()?,
+        value["line"],
+        value["column"],
+        value["severity"].as_str()?,
+        value["message"].as_str()?,
+    ))
+}
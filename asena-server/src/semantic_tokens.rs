@@ -0,0 +1,197 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use asena_highlight::{db::HighlightDatabase, HighlightColor};
+use tower_lsp::lsp_types::{
+    Range, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens,
+    SemanticTokensDelta, SemanticTokensEdit, SemanticTokensFullDeltaResult, SemanticTokensLegend,
+    Url,
+};
+
+use crate::{backend::Backend, ide_db::IdeDatabase};
+
+pub const TOKEN_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::STRING,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::MACRO,
+    SemanticTokenType::OPERATOR,
+];
+
+pub const TOKEN_MODIFIERS: &[SemanticTokenModifier] = &[
+    SemanticTokenModifier::DECLARATION,
+    SemanticTokenModifier::DEFAULT_LIBRARY,
+];
+
+const DECLARATION: u32 = 1 << 0;
+const DEFAULT_LIBRARY: u32 = 1 << 1;
+
+/// The id of the last semantic tokens sent to the editor, used to compute the deltas.
+static RESULT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// The index of the token type in the legend, and the modifiers of the color. The colors without
+/// a token type, like the delimitators, are not sent to the editor.
+fn token_type(color: HighlightColor) -> Option<(u32, u32)> {
+    let index = |value: SemanticTokenType| {
+        TOKEN_TYPES
+            .iter()
+            .position(|token_type| *token_type == value)
+            .unwrap() as u32
+    };
+
+    Some(match color {
+        HighlightColor::Error | HighlightColor::Eof | HighlightColor::Delimitator => return None,
+        HighlightColor::String => (index(SemanticTokenType::STRING), 0),
+        HighlightColor::Number => (index(SemanticTokenType::NUMBER), 0),
+        HighlightColor::LocalReference => (index(SemanticTokenType::VARIABLE), 0),
+        HighlightColor::GlobalReference => (index(SemanticTokenType::VARIABLE), 0),
+        HighlightColor::CallReference => (index(SemanticTokenType::FUNCTION), 0),
+        HighlightColor::GlobalFunction => (index(SemanticTokenType::FUNCTION), DECLARATION),
+        HighlightColor::GlobalVariable => (index(SemanticTokenType::VARIABLE), DECLARATION),
+        HighlightColor::BuiltinType => (index(SemanticTokenType::TYPE), DEFAULT_LIBRARY),
+        HighlightColor::BuiltinFunction => (index(SemanticTokenType::FUNCTION), DEFAULT_LIBRARY),
+        HighlightColor::Attribute | HighlightColor::Command => (index(SemanticTokenType::MACRO), 0),
+        HighlightColor::HardKeyword | HighlightColor::SoftKeyword => {
+            (index(SemanticTokenType::KEYWORD), 0)
+        }
+        HighlightColor::Operator | HighlightColor::Assign => {
+            (index(SemanticTokenType::OPERATOR), 0)
+        }
+    })
+}
+
+impl Backend {
+    pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+        SemanticTokensLegend {
+            token_types: TOKEN_TYPES.to_vec(),
+            token_modifiers: TOKEN_MODIFIERS.to_vec(),
+        }
+    }
+
+    /// The semantic tokens of the document, relative to the previous ones, as the protocol
+    /// requires. If there is a `range`, only the tokens in it are returned.
+    pub fn semantic_tokens(
+        &self,
+        db: &IdeDatabase,
+        uri: &Url,
+        range: Option<Range>,
+    ) -> Vec<SemanticToken> {
+        let Some((file, source)) = Self::document(db, uri) else {
+            return vec![];
+        };
+
+        let mut tokens = vec![];
+        let mut previous = (0, 0);
+        for (loc, color) in db.highlight(file).iter() {
+            let Some((token_type, token_modifiers_bitset)) = token_type(*color) else {
+                continue;
+            };
+
+            let (Some(start), Some(end)) = (
+                Self::offset_to_position(loc.range.start(), &source),
+                Self::offset_to_position(loc.range.end(), &source),
+            ) else {
+                continue;
+            };
+
+            if range.is_some_and(|range| end < range.start || start > range.end) {
+                continue;
+            }
+
+            // The tokens can't be multiline, so they are truncated at the end of the line.
+            let length = if start.line == end.line {
+                end.character - start.character
+            } else {
+                let line = source.line(start.line as usize);
+                let len = line
+                    .chars()
+                    .filter(|c| !matches!(c, '\r' | '\n'))
+                    .map(char::len_utf16)
+                    .sum::<usize>();
+                (len as u32).saturating_sub(start.character)
+            };
+
+            let (line, character) = previous;
+            let delta_line = start.line - line;
+            let delta_start = if delta_line == 0 {
+                start.character - character
+            } else {
+                start.character
+            };
+
+            tokens.push(SemanticToken {
+                delta_line,
+                delta_start,
+                length,
+                token_type,
+                token_modifiers_bitset,
+            });
+            previous = (start.line, start.character);
+        }
+
+        tokens
+    }
+
+    /// The semantic tokens of the whole document, they are stored to compute the next delta.
+    pub fn semantic_tokens_full_at(&self, db: &IdeDatabase, uri: &Url) -> SemanticTokens {
+        let data = self.semantic_tokens(db, uri, None);
+        let result_id = RESULT_ID.fetch_add(1, Ordering::SeqCst).to_string();
+        let tokens = SemanticTokens {
+            result_id: Some(result_id),
+            data,
+        };
+
+        self.semantic_tokens.insert(uri.clone(), tokens.clone());
+        tokens
+    }
+
+    /// The changes since the tokens with the `previous_result_id`, as a single edit that replaces
+    /// the tokens between the common prefix, and the common suffix.
+    pub fn semantic_tokens_delta_at(
+        &self,
+        db: &IdeDatabase,
+        uri: &Url,
+        previous_result_id: &str,
+    ) -> SemanticTokensFullDeltaResult {
+        let previous = self
+            .semantic_tokens
+            .get(uri)
+            .map(|tokens| tokens.value().clone())
+            .filter(|tokens| tokens.result_id.as_deref() == Some(previous_result_id));
+
+        let tokens = self.semantic_tokens_full_at(db, uri);
+        let Some(previous) = previous else {
+            return SemanticTokensFullDeltaResult::Tokens(tokens);
+        };
+
+        let old = &previous.data;
+        let new = &tokens.data;
+        let prefix = old
+            .iter()
+            .zip(new.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        let mut edits = vec![];
+        if prefix + suffix != old.len() || prefix + suffix != new.len() {
+            edits.push(SemanticTokensEdit {
+                start: (prefix * 5) as u32,
+                delete_count: ((old.len() - prefix - suffix) * 5) as u32,
+                data: Some(new[prefix..new.len() - suffix].to_vec()),
+            });
+        }
+
+        SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta {
+            result_id: tokens.result_id,
+            edits,
+        })
+    }
+}
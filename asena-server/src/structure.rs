@@ -0,0 +1,86 @@
+use std::ops::Range as OffsetRange;
+
+use asena_ast_db::structure::{folding_ranges, selection_ranges, AstFoldingKind};
+use ropey::Rope;
+use tower_lsp::lsp_types::{FoldingRange, FoldingRangeKind, Position, Range, SelectionRange, Url};
+
+use crate::{backend::Backend, ide_db::IdeDatabase};
+
+impl Backend {
+    /// The blocks and the runs of comments of the document, that spans more than one line.
+    pub fn folding_ranges_at(&self, db: &IdeDatabase, uri: &Url) -> Vec<FoldingRange> {
+        let Some((file, source)) = Self::document(db, uri) else {
+            return vec![];
+        };
+
+        folding_ranges(db, file)
+            .into_iter()
+            .filter_map(|folding| {
+                let range = Self::offset_range(&folding.range, &source)?;
+                if range.start.line >= range.end.line {
+                    return None;
+                }
+
+                // The blocks have no kind, the region kind is for the `#region` markers.
+                let kind = match folding.kind {
+                    AstFoldingKind::Block => None,
+                    AstFoldingKind::Comment => Some(FoldingRangeKind::Comment),
+                };
+
+                Some(FoldingRange {
+                    start_line: range.start.line,
+                    start_character: Some(range.start.character),
+                    end_line: range.end.line,
+                    end_character: Some(range.end.character),
+                    kind,
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    /// The nodes that enclose each of the positions, the parent of each range is the range of the
+    /// node that encloses it. The positions outside of the code select nothing.
+    pub fn selection_ranges_at(
+        &self,
+        db: &IdeDatabase,
+        uri: &Url,
+        positions: &[Position],
+    ) -> Vec<SelectionRange> {
+        let Some((file, source)) = Self::document(db, uri) else {
+            return vec![];
+        };
+
+        positions
+            .iter()
+            .map(|position| {
+                let ranges = Self::position_to_offset(*position, &source)
+                    .map(|offset| selection_ranges(db, file, offset))
+                    .unwrap_or_default();
+
+                // The ranges are linked from the outermost one, that has no parent.
+                ranges
+                    .iter()
+                    .rev()
+                    .filter_map(|range| Self::offset_range(range, &source))
+                    .fold(None, |parent, range| {
+                        Some(SelectionRange {
+                            range,
+                            parent: parent.map(Box::new),
+                        })
+                    })
+                    .unwrap_or(SelectionRange {
+                        range: Range::new(*position, *position),
+                        parent: None,
+                    })
+            })
+            .collect()
+    }
+
+    fn offset_range(range: &OffsetRange<usize>, source: &Rope) -> Option<Range> {
+        let start = Self::offset_to_position(range.start, source)?;
+        let end = Self::offset_to_position(range.end, source)?;
+
+        Some(Range::new(start, end))
+    }
+}
@@ -0,0 +1,83 @@
+use asena_ast_db::symbols::{document_symbols, workspace_symbols, AstSymbol, AstSymbolKind};
+use asena_span::Loc;
+use ropey::Rope;
+use tower_lsp::lsp_types::{DocumentSymbol, Range, SymbolInformation, SymbolKind, Url};
+
+use crate::{backend::Backend, ide_db::IdeDatabase};
+
+impl Backend {
+    /// The outline of the document, with the declarations inside of the enums, classes, traits
+    /// and instances as children.
+    pub fn document_symbols_at(&self, db: &IdeDatabase, uri: &Url) -> Vec<DocumentSymbol> {
+        let Some((file, source)) = Self::document(db, uri) else {
+            return vec![];
+        };
+
+        document_symbols(db, file)
+            .into_iter()
+            .filter_map(|symbol| Self::document_symbol(symbol, &source))
+            .collect()
+    }
+
+    /// The symbols of all the packages, that fuzzy matches the query.
+    #[allow(deprecated)]
+    pub fn workspace_symbols_at(&self, db: &IdeDatabase, query: &str) -> Vec<SymbolInformation> {
+        workspace_symbols(db, query)
+            .into_iter()
+            .filter_map(|found| {
+                let location = Self::location(db, &found.symbol.selection)?;
+
+                Some(SymbolInformation {
+                    name: found.symbol.name,
+                    kind: symbol_kind(found.symbol.kind),
+                    tags: None,
+                    deprecated: None,
+                    location,
+                    container_name: found.container,
+                })
+            })
+            .collect()
+    }
+
+    #[allow(deprecated)]
+    fn document_symbol(symbol: AstSymbol, source: &Rope) -> Option<DocumentSymbol> {
+        let range = Self::range(&symbol.loc, source)?;
+        let selection_range = Self::range(&symbol.selection, source).unwrap_or(range);
+        let children = symbol
+            .children
+            .into_iter()
+            .filter_map(|child| Self::document_symbol(child, source))
+            .collect();
+
+        Some(DocumentSymbol {
+            name: symbol.name,
+            detail: None,
+            kind: symbol_kind(symbol.kind),
+            tags: None,
+            deprecated: None,
+            range,
+            selection_range,
+            children: Some(children),
+        })
+    }
+
+    pub(crate) fn range(loc: &Loc, source: &Rope) -> Option<Range> {
+        let start = Self::offset_to_position(loc.range.start(), source)?;
+        let end = Self::offset_to_position(loc.range.end(), source)?;
+
+        Some(Range::new(start, end))
+    }
+}
+
+pub(crate) fn symbol_kind(kind: AstSymbolKind) -> SymbolKind {
+    match kind {
+        AstSymbolKind::Enum => SymbolKind::ENUM,
+        AstSymbolKind::Variant => SymbolKind::ENUM_MEMBER,
+        AstSymbolKind::Class => SymbolKind::CLASS,
+        AstSymbolKind::Field => SymbolKind::FIELD,
+        AstSymbolKind::Trait => SymbolKind::INTERFACE,
+        AstSymbolKind::Instance => SymbolKind::OBJECT,
+        AstSymbolKind::Method => SymbolKind::METHOD,
+        AstSymbolKind::Function => SymbolKind::FUNCTION,
+    }
+}
@@ -0,0 +1,916 @@
+use ropey::Rope;
+use serde_json::{json, Value};
+use tower_lsp::lsp_types::{
+    CompletionItemKind, Diagnostic, InlayHintKind, Position, Range, SemanticTokenType,
+    TextDocumentContentChangeEvent,
+};
+
+use crate::{backend::Backend, semantic_tokens::TOKEN_TYPES};
+
+use self::harness::TestEditor;
+
+mod harness;
+
+const MANIFEST: &str = "name: example
+version: 0.1.0
+source:
+  - src
+";
+
+const LIB: &str = "twice : Int -> Int
+twice x = x
+";
+
+const MAIN: &str = "use Lib
+
+four : Int
+four = twice 4
+";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn initialize_advertises_the_capabilities() {
+    let editor = TestEditor::start(&[]).await;
+    let capabilities = &editor.initialize["capabilities"];
+
+    assert_eq!(capabilities["definitionProvider"], true);
+    assert_eq!(capabilities["hoverProvider"], true);
+    assert_eq!(
+        capabilities["completionProvider"]["triggerCharacters"],
+        json!(["."])
+    );
+    assert_eq!(
+        capabilities["executeCommandProvider"]["commands"],
+        json!(["asena.run"])
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn publishes_the_diagnostics_of_the_changes() {
+    let mut editor = TestEditor::start(&[
+        ("asena.yml", MANIFEST),
+        (
+            "src/Main.ase",
+            "double : Int -> Int\ndouble x = missing x\n",
+        ),
+    ])
+    .await;
+
+    editor.open("src/Main.ase").await;
+    let diagnostics = editor.diagnostics("src/Main.ase", 1).await;
+    assert!(mentions(&diagnostics, "missing"), "{diagnostics:#?}");
+
+    editor
+        .change("src/Main.ase", 2, "double : Int -> Int\ndouble x = x\n")
+        .await;
+    let diagnostics = editor.diagnostics("src/Main.ase", 2).await;
+    assert!(!mentions(&diagnostics, "missing"), "{diagnostics:#?}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn locates_the_diagnostics_in_utf16_code_units() {
+    // The emoji is a single character, but two UTF-16 code units, and four bytes.
+    let source = "double : Int -> Int\ndouble x = x \"😀\" missing\n";
+    let mut editor = TestEditor::start(&[("asena.yml", MANIFEST), ("src/Main.ase", source)]).await;
+
+    editor.open("src/Main.ase").await;
+    let diagnostics = editor.diagnostics("src/Main.ase", 1).await;
+    let diagnostic = diagnostics
+        .iter()
+        .find(|diagnostic| diagnostic.message.contains("`missing`"))
+        .unwrap_or_else(|| panic!("{diagnostics:#?}"));
+    assert_eq!(diagnostic.range, Range::new(pos(1, 18), pos(1, 25)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn goes_to_the_definitions_of_the_other_modules_of_the_workspace() {
+    let mut editor = TestEditor::start(&[
+        ("asena.yml", MANIFEST),
+        ("src/Lib.ase", LIB),
+        ("src/Main.ase", MAIN),
+    ])
+    .await;
+
+    editor.open("src/Main.ase").await;
+    editor.diagnostics("src/Main.ase", 1).await;
+
+    let uri = editor.uri("src/Main.ase");
+    let location = editor
+        .request(
+            "textDocument/definition",
+            json!({
+                "textDocument": { "uri": uri },
+                "position": { "line": 3, "character": 8 },
+            }),
+        )
+        .await;
+
+    assert_eq!(location["uri"], editor.uri("src/Lib.ase").as_str());
+    assert_eq!(
+        location["range"]["start"],
+        json!({ "line": 0, "character": 0 })
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn completes_the_names_of_the_other_modules_of_the_workspace() {
+    let mut editor = TestEditor::start(&[
+        ("asena.yml", MANIFEST),
+        ("src/Lib.ase", LIB),
+        ("src/Main.ase", MAIN),
+    ])
+    .await;
+
+    editor.open("src/Main.ase").await;
+    editor.diagnostics("src/Main.ase", 1).await;
+
+    let uri = editor.uri("src/Main.ase");
+    let items = editor
+        .request(
+            "textDocument/completion",
+            json!({
+                "textDocument": { "uri": uri },
+                "position": { "line": 3, "character": 9 },
+            }),
+        )
+        .await;
+
+    let labels = items
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["label"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert!(labels.contains(&"twice"), "{labels:?}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn completes_the_locals_in_scope_with_their_types() {
+    let source = "pick (count : Nat) : Nat\npick count = co\n";
+    let mut editor = open_main(source).await;
+
+    let items = editor
+        .request("textDocument/completion", at_main(&editor, 1, 15))
+        .await;
+    let item = |label: &str| {
+        items
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|item| item["label"] == label)
+            .cloned()
+            .unwrap_or_else(|| panic!("no `{label}` in {items:#}"))
+    };
+
+    let count = item("count");
+    assert_eq!(count["kind"], json!(CompletionItemKind::VARIABLE));
+    assert_eq!(count["detail"], "Nat");
+
+    // The locals are ranked before the keywords.
+    let sort_text = |item: &Value| item["sortText"].as_str().unwrap().to_string();
+    assert!(sort_text(&count) < sort_text(&item("let")));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn ignores_the_documents_outside_of_the_workspace() {
+    let mut editor = TestEditor::start(&[
+        ("asena.yml", MANIFEST),
+        ("src/Lib.ase", LIB),
+        ("src/Main.ase", MAIN),
+        ("Scratch.ase", LIB),
+    ])
+    .await;
+
+    // The file isn't in the source directories, so it isn't a module of the package.
+    editor.open("Scratch.ase").await;
+    let uri = editor.uri("Scratch.ase");
+    let hover = editor
+        .request(
+            "textDocument/hover",
+            json!({
+                "textDocument": { "uri": uri },
+                "position": { "line": 1, "character": 0 },
+            }),
+        )
+        .await;
+    assert_eq!(hover, json!(null));
+
+    editor
+        .notify(
+            "textDocument/didClose",
+            json!({ "textDocument": { "uri": uri } }),
+        )
+        .await;
+
+    // The server keeps answering the documents of the workspace.
+    editor.open("src/Main.ase").await;
+    editor.diagnostics("src/Main.ase", 1).await;
+
+    let uri = editor.uri("src/Main.ase");
+    let location = editor
+        .request(
+            "textDocument/definition",
+            json!({
+                "textDocument": { "uri": uri },
+                "position": { "line": 3, "character": 8 },
+            }),
+        )
+        .await;
+    assert_eq!(location["uri"], editor.uri("src/Lib.ase").as_str());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn publishes_the_errors_of_the_manifest() {
+    let mut editor = TestEditor::start(&[
+        ("asena.yml", "name: example\nversion: one\n"),
+        ("src/Main.ase", MAIN),
+    ])
+    .await;
+
+    let diagnostics = editor.file_diagnostics("asena.yml").await;
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:#?}");
+    assert_eq!(diagnostics[0].range.start.line, 1);
+    assert!(diagnostics[0].message.contains("`version`"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn loads_the_package_again_when_the_manifest_changes() {
+    let mut editor = TestEditor::start(&[
+        ("asena.yml", MANIFEST),
+        ("src/Main.ase", MAIN),
+        ("lib/Lib.ase", LIB),
+    ])
+    .await;
+
+    editor.open("src/Main.ase").await;
+    let diagnostics = editor.diagnostics("src/Main.ase", 1).await;
+    assert!(mentions(&diagnostics, "Lib"), "{diagnostics:#?}");
+
+    // The `lib` directory becomes a source directory, so `Lib` is found.
+    let manifest = format!("{MANIFEST}  - lib\n");
+    std::fs::write(editor.root.join("asena.yml"), manifest).unwrap();
+    let uri = editor.uri("asena.yml");
+    editor
+        .notify(
+            "workspace/didChangeWatchedFiles",
+            json!({ "changes": [{ "uri": uri, "type": 2 }] }),
+        )
+        .await;
+
+    let diagnostics = editor.diagnostics("src/Main.ase", 1).await;
+    assert!(!mentions(&diagnostics, "Lib"), "{diagnostics:#?}");
+    assert!(!mentions(&diagnostics, "twice"), "{diagnostics:#?}");
+}
+
+const FLAGS: &str = "// A comment
+// on two lines
+enum Flag {
+  On : Flag,
+  Off : Flag
+}
+
+flip : Flag -> Flag
+flip f = f
+
+flop : Flag -> Flag
+flop f = flip (flip f)
+";
+
+const PICK: &str = "/// Picks the first.
+pick [a: Set] (x : a) (y : a) : a
+pick x y = x
+
+one : Nat
+one = Succ Zero
+
+both : Nat
+both = pick Zero one
+";
+
+const HOLE: &str = "one : Nat
+one = Succ Zero
+
+pick (x : Nat) : Nat
+pick x = ? x
+";
+
+const BOOL: &str = "enum Bool {
+  True : Bool,
+  False : Bool
+}
+
+not : Bool -> Bool
+not b = b
+";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn hovers_the_holes_with_their_context() {
+    let mut editor = open_main(HOLE).await;
+
+    let hover = editor
+        .request("textDocument/hover", at_main(&editor, 4, 9))
+        .await;
+    let value = hover["contents"]["value"].as_str().unwrap();
+
+    assert!(value.starts_with("```asena\n? : Nat\n```"), "{value}");
+    assert!(value.contains("**Locals:** x : Nat"), "{value}");
+    assert!(value.contains("one"), "{value}");
+    assert_eq!(
+        hover["range"],
+        json!({
+            "start": { "line": 4, "character": 9 },
+            "end": { "line": 4, "character": 12 },
+        })
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn fills_the_holes_with_their_candidates() {
+    let mut editor = open_main(HOLE).await;
+
+    let uri = editor.uri("src/Main.ase");
+    let actions = editor
+        .request(
+            "textDocument/codeAction",
+            json!({
+                "textDocument": { "uri": uri },
+                "range": {
+                    "start": { "line": 4, "character": 10 },
+                    "end": { "line": 4, "character": 10 },
+                },
+                "context": { "diagnostics": [] },
+            }),
+        )
+        .await;
+
+    let action = actions
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|action| action["title"] == "Fill hole with `one`")
+        .unwrap_or_else(|| panic!("{actions:#}"));
+    assert_eq!(action["kind"], "quickfix");
+    assert_eq!(
+        action["edit"]["changes"][uri.as_str()],
+        json!([{
+            "range": {
+                "start": { "line": 4, "character": 9 },
+                "end": { "line": 4, "character": 12 },
+            },
+            "newText": "one",
+        }])
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn applies_the_quickfixes_of_the_diagnostics() {
+    let mut editor = open_main("one : Nat\none = λ\n").await;
+
+    let uri = editor.uri("src/Main.ase");
+    let actions = editor
+        .request(
+            "textDocument/codeAction",
+            json!({
+                "textDocument": { "uri": uri },
+                "range": {
+                    "start": { "line": 1, "character": 6 },
+                    "end": { "line": 1, "character": 6 },
+                },
+                "context": { "diagnostics": [] },
+            }),
+        )
+        .await;
+
+    let action = actions
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|action| action["title"] == "Remove `λ`, Insert `\\`")
+        .unwrap_or_else(|| panic!("{actions:#}"));
+    assert_eq!(action["kind"], "quickfix");
+    assert_eq!(action["isPreferred"], true);
+    assert_eq!(
+        action["edit"]["changes"][uri.as_str()],
+        json!([{
+            "range": {
+                "start": { "line": 1, "character": 6 },
+                "end": { "line": 1, "character": 7 },
+            },
+            "newText": "\\",
+        }])
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn outlines_and_searches_the_symbols() {
+    let mut editor = open_main(BOOL).await;
+
+    let uri = editor.uri("src/Main.ase");
+    let symbols = editor
+        .request(
+            "textDocument/documentSymbol",
+            json!({ "textDocument": { "uri": uri } }),
+        )
+        .await;
+
+    let outline = symbols
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|symbol| {
+            let children = symbol["children"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|child| (child["name"].clone(), child["kind"].clone()))
+                .collect::<Vec<_>>();
+            (symbol["name"].clone(), symbol["kind"].clone(), children)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        outline,
+        vec![
+            (
+                json!("Bool"),
+                json!(10),
+                vec![(json!("True"), json!(22)), (json!("False"), json!(22))],
+            ),
+            (json!("not"), json!(12), vec![]),
+        ]
+    );
+    assert_eq!(
+        symbols[0]["selectionRange"],
+        json!({
+            "start": { "line": 0, "character": 5 },
+            "end": { "line": 0, "character": 9 },
+        })
+    );
+
+    let found = editor
+        .request("workspace/symbol", json!({ "query": "Fals" }))
+        .await;
+    let symbol = found
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|symbol| symbol["name"] == "False")
+        .unwrap_or_else(|| panic!("{found:#}"));
+    assert_eq!(symbol["containerName"], "Bool");
+    assert_eq!(symbol["location"]["uri"], uri.as_str());
+    assert_eq!(
+        symbol["location"]["range"]["start"],
+        json!({ "line": 2, "character": 2 })
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn answers_the_requests_sent_between_the_changes() {
+    let mut editor = open_main("n1 : Nat\nn1 = 1\n").await;
+    let uri = editor.uri("src/Main.ase");
+
+    // The requests aren't waited for, so the changes cancel the ones that are still running.
+    let mut ids = vec![];
+    for version in 2..=10 {
+        let text = format!("n{version} : Nat\nn{version} = {version}\n");
+        editor.change("src/Main.ase", version, &text).await;
+
+        let params = json!({ "textDocument": { "uri": uri } });
+        ids.push(
+            editor
+                .send_request("textDocument/semanticTokens/full", params)
+                .await,
+        );
+        ids.push(
+            editor
+                .send_request("textDocument/hover", at_main(&editor, 1, 0))
+                .await,
+        );
+    }
+
+    for response in editor.responses(&ids).await {
+        match response.get("error") {
+            Some(error) => assert_eq!(error["code"], -32801, "{response}"),
+            None => assert!(response.get("result").is_some(), "{response}"),
+        }
+    }
+
+    editor.diagnostics("src/Main.ase", 10).await;
+    let hover = editor
+        .request("textDocument/hover", at_main(&editor, 1, 0))
+        .await;
+    let value = hover["contents"]["value"].as_str().unwrap();
+    assert!(value.starts_with("```asena\nn10"), "{value}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn folds_the_blocks_and_the_comments() {
+    let mut editor = open_main(FLAGS).await;
+    let uri = editor.uri("src/Main.ase");
+
+    let foldings = editor
+        .request(
+            "textDocument/foldingRange",
+            json!({ "textDocument": { "uri": uri } }),
+        )
+        .await;
+    let foldings = foldings
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|folding| {
+            (
+                folding["startLine"].clone(),
+                folding["endLine"].clone(),
+                folding.get("kind").cloned(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        foldings,
+        vec![
+            (json!(0), json!(1), Some(json!("comment"))),
+            (json!(2), json!(4), None),
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn expands_the_selection_to_the_enclosing_nodes() {
+    let mut editor = open_main(FLAGS).await;
+    let uri = editor.uri("src/Main.ase");
+
+    let selections = editor
+        .request(
+            "textDocument/selectionRange",
+            json!({
+                "textDocument": { "uri": uri },
+                "positions": [{ "line": 11, "character": 20 }],
+            }),
+        )
+        .await;
+
+    let mut ranges = vec![];
+    let mut selection = &selections[0];
+    while !selection.is_null() {
+        let range: Range = serde_json::from_value(selection["range"].clone()).unwrap();
+        ranges.push(range);
+        selection = &selection["parent"];
+    }
+
+    assert_eq!(ranges.first(), Some(&Range::new(pos(11, 20), pos(11, 21))));
+    assert_eq!(ranges.last(), Some(&Range::new(pos(2, 0), pos(11, 22))));
+    assert!(ranges.len() > 2, "{ranges:#?}");
+    for (child, parent) in ranges.iter().zip(&ranges[1..]) {
+        assert!(
+            parent.start <= child.start && child.end <= parent.end,
+            "{ranges:#?}"
+        );
+        assert_ne!(parent, child);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn highlights_the_bindings_and_the_reads_of_the_name() {
+    let mut editor = open_main(FLAGS).await;
+
+    let highlights = editor
+        .request("textDocument/documentHighlight", at_main(&editor, 11, 16))
+        .await;
+    let highlights = highlights.as_array().unwrap();
+
+    // The kinds are `Read` and `Write`, for the bindings.
+    for (range, kind) in [
+        (Range::new(pos(7, 0), pos(7, 4)), 3),
+        (Range::new(pos(11, 9), pos(11, 13)), 2),
+        (Range::new(pos(11, 15), pos(11, 19)), 2),
+    ] {
+        let highlight = json!({ "range": range, "kind": kind });
+        assert!(highlights.contains(&highlight), "{highlights:#?}");
+    }
+    assert!(highlights
+        .iter()
+        .all(|highlight| highlight["range"]["start"]["line"] != 10));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn helps_with_the_signature_of_the_call() {
+    let mut editor = open_main(PICK).await;
+
+    let help = editor
+        .request("textDocument/signatureHelp", at_main(&editor, 8, 18))
+        .await;
+    let signature = &help["signatures"][0];
+    let label = signature["label"].as_str().unwrap();
+
+    assert!(label.starts_with("pick [a"), "{label}");
+    assert_eq!(signature["documentation"]["value"], "Picks the first.");
+
+    // The implicit `a` isn't passed, so the second argument is the `y` parameter.
+    assert_eq!(help["activeParameter"], 2);
+    let parameter = &signature["parameters"][2]["label"];
+    let start = parameter[0].as_u64().unwrap() as usize;
+    let end = parameter[1].as_u64().unwrap() as usize;
+    assert_eq!(&label[start..end], "(y : a)");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn goes_through_the_hierarchy_of_the_calls() {
+    let mut editor = open_main(PICK).await;
+
+    let items = editor
+        .request("textDocument/prepareCallHierarchy", at_main(&editor, 1, 0))
+        .await;
+    let pick = &items[0];
+    assert_eq!(pick["name"], "pick");
+
+    let incoming = editor
+        .request("callHierarchy/incomingCalls", json!({ "item": pick }))
+        .await;
+    let call = incoming
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|call| call["from"]["name"] == "both")
+        .unwrap_or_else(|| panic!("{incoming:#}"));
+    assert_eq!(
+        call["fromRanges"],
+        json!([{
+            "start": { "line": 8, "character": 7 },
+            "end": { "line": 8, "character": 11 },
+        }])
+    );
+
+    let items = editor
+        .request("textDocument/prepareCallHierarchy", at_main(&editor, 7, 0))
+        .await;
+    let outgoing = editor
+        .request("callHierarchy/outgoingCalls", json!({ "item": items[0] }))
+        .await;
+    let mut names = outgoing
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|call| call["to"]["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["one", "pick"]);
+}
+
+/// The lens builds the package with `asena build`, that is replaced by a script, that writes its
+/// arguments, and prints a program that logs a line as its artifact.
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread")]
+async fn runs_the_entry_points_of_the_lenses() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut editor = open_main("main = println \"hello\"\n").await;
+    let root = editor.root.display().to_string();
+
+    let script = editor.root.join("asena");
+    std::fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\n\
+             echo \"$@\" > '{root}/arguments'\n\
+             printf '#!/bin/sh\\necho hello from main\\n' > '{root}/program'\n\
+             chmod +x '{root}/program'\n\
+             echo '{{\"type\":\"artifact\",\"path\":\"{root}/program\"}}'\n"
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    std::env::set_var("ASENA_PATH", &script);
+
+    let uri = editor.uri("src/Main.ase");
+    let lenses = editor
+        .request(
+            "textDocument/codeLens",
+            json!({ "textDocument": { "uri": uri } }),
+        )
+        .await;
+    let command = lenses
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|lens| &lens["command"])
+        .find(|command| command["command"] == "asena.run")
+        .unwrap_or_else(|| panic!("{lenses:#}"))
+        .clone();
+    assert_eq!(command["arguments"], json!([uri, "main"]));
+
+    editor
+        .request(
+            "workspace/executeCommand",
+            json!({ "command": command["command"], "arguments": command["arguments"] }),
+        )
+        .await;
+    editor
+        .notification(|message| {
+            message["method"] == "window/logMessage"
+                && message["params"]["message"] == "hello from main"
+        })
+        .await;
+
+    let arguments = std::fs::read_to_string(editor.root.join("arguments")).unwrap();
+    assert_eq!(
+        arguments.trim_end(),
+        format!("build --backend c --message-format json --entry main --dir {root}")
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sends_the_semantic_tokens_and_their_deltas() {
+    let mut editor = open_main("one : Nat\none = 1\n").await;
+    let uri = editor.uri("src/Main.ase");
+    let document = json!({ "textDocument": { "uri": uri } });
+
+    let full = editor
+        .request("textDocument/semanticTokens/full", document.clone())
+        .await;
+    let number = TOKEN_TYPES
+        .iter()
+        .position(|token_type| *token_type == SemanticTokenType::NUMBER)
+        .unwrap() as u32;
+    let tokens = absolute_tokens(&full["data"]);
+    assert!(tokens.contains(&[1, 6, 1, number, 0]), "{tokens:?}");
+
+    // Nothing changed, so there are no edits.
+    let previous = full["resultId"].clone();
+    let delta = editor
+        .request(
+            "textDocument/semanticTokens/full/delta",
+            json!({ "textDocument": { "uri": uri }, "previousResultId": previous }),
+        )
+        .await;
+    assert_eq!(delta["edits"], json!([]));
+
+    // The edit replaces the tokens of the literal, that is longer now.
+    editor
+        .change("src/Main.ase", 2, "one : Nat\none = 12\n")
+        .await;
+    editor.diagnostics("src/Main.ase", 2).await;
+    let delta = editor
+        .request(
+            "textDocument/semanticTokens/full/delta",
+            json!({ "textDocument": { "uri": uri }, "previousResultId": delta["resultId"] }),
+        )
+        .await;
+    let edits = delta["edits"].as_array().unwrap();
+    assert_eq!(edits.len(), 1, "{delta:#}");
+
+    let mut data = full["data"].as_array().unwrap().clone();
+    let start = edits[0]["start"].as_u64().unwrap() as usize;
+    let delete_count = edits[0]["deleteCount"].as_u64().unwrap() as usize;
+    let inserted = edits[0]["data"].as_array().unwrap().clone();
+    data.splice(start..start + delete_count, inserted);
+
+    let tokens = absolute_tokens(&Value::Array(data));
+    assert!(tokens.contains(&[1, 6, 2, number, 0]), "{tokens:?}");
+}
+
+const DOUBLE: &str = "/// Twice the number.
+double (n : Nat) : Nat
+double n = add n n
+
+four : Nat
+four = double 2
+";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn hovers_the_signatures_and_the_docs_of_the_globals() {
+    let mut editor = open_main(DOUBLE).await;
+
+    let hover = editor
+        .request("textDocument/hover", at_main(&editor, 5, 8))
+        .await;
+    let value = hover["contents"]["value"].as_str().unwrap();
+
+    let (signature, docs) = value.split_once("\n\n").unwrap();
+    assert!(signature.starts_with("```asena\ndouble"), "{value}");
+    assert!(signature.contains("Nat"), "{value}");
+    assert!(docs.contains("Twice the number."), "{value}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn hints_the_names_of_the_parameters_of_the_arguments() {
+    let mut editor = open_main(DOUBLE).await;
+
+    let uri = editor.uri("src/Main.ase");
+    let hints = editor
+        .request(
+            "textDocument/inlayHint",
+            json!({
+                "textDocument": { "uri": uri },
+                "range": {
+                    "start": { "line": 0, "character": 0 },
+                    "end": { "line": 6, "character": 0 },
+                },
+            }),
+        )
+        .await;
+
+    let hint = hints
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|hint| hint["position"] == json!({ "line": 5, "character": 14 }))
+        .unwrap_or_else(|| panic!("{hints:#}"));
+    assert_eq!(hint["label"], "n:");
+    assert_eq!(hint["kind"], json!(InlayHintKind::PARAMETER));
+}
+
+#[test]
+fn applies_the_changes_after_the_characters_outside_of_the_bmp() {
+    // The emojis take two UTF-16 code units, but a single char of the rope.
+    let mut rope = Rope::from_str("a😀b\n𝔸 = x\n");
+
+    Backend::apply_change(&mut rope, change((0, 1), (0, 3), "c"));
+    assert_eq!(rope.to_string(), "acb\n𝔸 = x\n");
+
+    Backend::apply_change(&mut rope, change((1, 5), (1, 6), "y"));
+    assert_eq!(rope.to_string(), "acb\n𝔸 = y\n");
+
+    Backend::apply_change(&mut rope, change((1, 0), (1, 2), "😀😀"));
+    assert_eq!(rope.to_string(), "acb\n😀😀 = y\n");
+}
+
+#[test]
+fn clamps_the_changes_past_the_end_of_the_line() {
+    let mut rope = Rope::from_str("😀\nx\n");
+
+    Backend::apply_change(&mut rope, change((0, 2), (0, 10), "!"));
+    assert_eq!(rope.to_string(), "😀!\nx\n");
+
+    // The position after the last line is the end of the document.
+    Backend::apply_change(&mut rope, change((2, 0), (2, 0), "y\n"));
+    assert_eq!(rope.to_string(), "😀!\nx\ny\n");
+}
+
+#[test]
+fn converts_the_utf16_positions_to_byte_offsets() {
+    let rope = Rope::from_str("a😀b\n𝔸x\n");
+
+    assert_eq!(Backend::position_to_offset(pos(0, 3), &rope), Some(5));
+    assert_eq!(Backend::position_to_offset(pos(1, 2), &rope), Some(11));
+    assert_eq!(Backend::offset_to_position(5, &rope), Some(pos(0, 3)));
+    assert_eq!(Backend::offset_to_position(11, &rope), Some(pos(1, 2)));
+}
+
+/// Starts the editor with a package, that has `source` as its main module, and opens it.
+async fn open_main(source: &str) -> TestEditor {
+    let mut editor = TestEditor::start(&[("asena.yml", MANIFEST), ("src/Main.ase", source)]).await;
+    editor.open("src/Main.ase").await;
+    editor.diagnostics("src/Main.ase", 1).await;
+
+    editor
+}
+
+/// The params of the requests at a position of the main module.
+fn at_main(editor: &TestEditor, line: u32, character: u32) -> Value {
+    json!({
+        "textDocument": { "uri": editor.uri("src/Main.ase") },
+        "position": { "line": line, "character": character },
+    })
+}
+
+fn pos(line: u32, character: u32) -> Position {
+    Position::new(line, character)
+}
+
+/// The change of the text between the positions, as `(line, character)`.
+fn change(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+    TextDocumentContentChangeEvent {
+        range: Some(Range::new(pos(start.0, start.1), pos(end.0, end.1))),
+        range_length: None,
+        text: text.into(),
+    }
+}
+
+/// The semantic tokens as `[line, character, length, type, modifiers]`, with the positions
+/// relative to the start of the document, instead of the previous token.
+fn absolute_tokens(data: &Value) -> Vec<[u32; 5]> {
+    let data = serde_json::from_value::<Vec<u32>>(data.clone()).unwrap();
+
+    let mut previous = (0, 0);
+    data.chunks(5)
+        .map(|token| {
+            let line = previous.0 + token[0];
+            let character = match token[0] {
+                0 => previous.1 + token[1],
+                _ => token[1],
+            };
+            previous = (line, character);
+
+            [line, character, token[2], token[3], token[4]]
+        })
+        .collect()
+}
+
+/// If a diagnostic is about the name.
+fn mentions(diagnostics: &[Diagnostic], name: &str) -> bool {
+    let name = format!("`{name}`");
+
+    diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.message.contains(&name))
+}
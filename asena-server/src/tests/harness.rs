@@ -0,0 +1,279 @@
+//! An editor for the tests, that drives the language server in the same process, through
+//! in-memory streams, with the JSON-RPC messages of the protocol. The server is pointed at a
+//! temporary workspace folder, that is removed when the editor is dropped.
+
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, DuplexStream};
+use tower_lsp::{
+    lsp_types::{Diagnostic, Url},
+    Server,
+};
+
+/// How long a message of the server is waited for, before the test fails.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct TestEditor {
+    pub root: PathBuf,
+
+    /// The result of the `initialize` request, with the capabilities of the server.
+    pub initialize: Value,
+
+    writer: DuplexStream,
+    reader: BufReader<DuplexStream>,
+    next_id: i64,
+
+    /// The notifications of the server that weren't waited for yet, like the diagnostics of the
+    /// other documents, or the log messages.
+    notifications: Vec<Value>,
+}
+
+impl TestEditor {
+    /// Writes the files, that are paths relative to the workspace folder with their text, starts
+    /// the server, and initializes it with the workspace folder.
+    pub async fn start(files: &[(&str, &str)]) -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let root = std::env::temp_dir().join(format!(
+            "asena-server-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        for (path, text) in files {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, text).unwrap();
+        }
+
+        let (writer, server_reader) = tokio::io::duplex(1 << 16);
+        let (server_writer, reader) = tokio::io::duplex(1 << 16);
+
+        let (service, socket) = crate::service();
+        tokio::spawn(Server::new(server_reader, server_writer, socket).serve(service));
+
+        let mut editor = Self {
+            root,
+            initialize: Value::Null,
+            writer,
+            reader: BufReader::new(reader),
+            next_id: 0,
+            notifications: vec![],
+        };
+
+        let folder = editor.workspace_folder();
+        editor.initialize = editor
+            .request(
+                "initialize",
+                json!({
+                    "processId": null,
+                    "capabilities": {},
+                    "workspaceFolders": [folder],
+                }),
+            )
+            .await;
+        editor.notify("initialized", json!({})).await;
+
+        editor
+    }
+
+    /// The uri of the file, that is a path relative to the workspace folder.
+    pub fn uri(&self, path: &str) -> Url {
+        Url::from_file_path(self.root.join(path)).unwrap()
+    }
+
+    /// Sends the request, and waits for its result. The test fails if the server answers with
+    /// an error.
+    pub async fn request(&mut self, method: &str, params: Value) -> Value {
+        let id = self.send_request(method, params).await;
+        let response = self.responses(&[id]).await.remove(0);
+        if let Some(error) = response.get("error") {
+            panic!("the request `{method}` failed: {error}");
+        }
+
+        response["result"].clone()
+    }
+
+    /// Sends the request without waiting for its response, returns its id.
+    pub async fn send_request(&mut self, method: &str, params: Value) -> i64 {
+        self.next_id += 1;
+        let id = self.next_id;
+
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        }))
+        .await;
+
+        id
+    }
+
+    /// Waits for the responses of the requests, in any order, and returns them in the order of
+    /// the ids, with their result or their error.
+    pub async fn responses(&mut self, ids: &[i64]) -> Vec<Value> {
+        let mut responses = vec![Value::Null; ids.len()];
+        while responses.iter().any(Value::is_null) {
+            let response = self
+                .receive_until(|message| {
+                    message.get("method").is_none() && ids.iter().any(|id| message["id"] == *id)
+                })
+                .await;
+            let index = ids.iter().position(|id| response["id"] == *id).unwrap();
+            responses[index] = response;
+        }
+
+        responses
+    }
+
+    pub async fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        }))
+        .await;
+    }
+
+    /// Opens the document, with the text of the file in the workspace folder.
+    pub async fn open(&mut self, path: &str) {
+        let text = std::fs::read_to_string(self.root.join(path)).unwrap();
+        let uri = self.uri(path);
+
+        self.notify(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": {
+                    "uri": uri,
+                    "languageId": "asena",
+                    "version": 1,
+                    "text": text,
+                },
+            }),
+        )
+        .await;
+    }
+
+    /// Replaces the whole text of the opened document.
+    pub async fn change(&mut self, path: &str, version: i32, text: &str) {
+        let uri = self.uri(path);
+
+        self.notify(
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": uri, "version": version },
+                "contentChanges": [{ "text": text }],
+            }),
+        )
+        .await;
+    }
+
+    /// Waits for the diagnostics of the version of the document.
+    pub async fn diagnostics(&mut self, path: &str, version: i32) -> Vec<Diagnostic> {
+        self.published_diagnostics(path, json!(version)).await
+    }
+
+    /// Waits for the diagnostics of a file that isn't opened, like the manifest, they have no
+    /// version.
+    pub async fn file_diagnostics(&mut self, path: &str) -> Vec<Diagnostic> {
+        self.published_diagnostics(path, Value::Null).await
+    }
+
+    async fn published_diagnostics(&mut self, path: &str, version: Value) -> Vec<Diagnostic> {
+        let uri = self.uri(path);
+        let is_published = |message: &Value| {
+            message["method"] == "textDocument/publishDiagnostics"
+                && message["params"]["uri"] == uri.as_str()
+                && message["params"]["version"] == version
+        };
+
+        let notification = self.notification(is_published).await;
+
+        serde_json::from_value(notification["params"]["diagnostics"].clone()).unwrap()
+    }
+
+    /// Waits for a notification of the server that matches, like a log message. The ones that
+    /// were already received are looked at first.
+    pub async fn notification(&mut self, matches: impl Fn(&Value) -> bool) -> Value {
+        match self.notifications.iter().position(&matches) {
+            Some(index) => self.notifications.remove(index),
+            None => self.receive_until(matches).await,
+        }
+    }
+
+    fn workspace_folder(&self) -> Value {
+        json!({
+            "uri": Url::from_file_path(&self.root).unwrap(),
+            "name": "example",
+        })
+    }
+
+    async fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        let header = format!("Content-Length: {}\r\n\r\n", body.len());
+
+        self.writer.write_all(header.as_bytes()).await.unwrap();
+        self.writer.write_all(body.as_bytes()).await.unwrap();
+    }
+
+    /// Reads the messages of the server until one matches. The requests of the server are
+    /// answered meanwhile, and its notifications are kept.
+    async fn receive_until(&mut self, matches: impl Fn(&Value) -> bool) -> Value {
+        loop {
+            let message = tokio::time::timeout(TIMEOUT, self.receive())
+                .await
+                .expect("timed out waiting for the server");
+
+            if matches(&message) {
+                return message;
+            }
+
+            match (message.get("id"), message.get("method")) {
+                (Some(id), Some(method)) => {
+                    let result = match method.as_str() {
+                        Some("workspace/workspaceFolders") => json!([self.workspace_folder()]),
+                        _ => Value::Null,
+                    };
+
+                    self.send(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+                        .await;
+                }
+                (None, Some(_)) => self.notifications.push(message),
+                _ => {}
+            }
+        }
+    }
+
+    async fn receive(&mut self) -> Value {
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            let read = self.reader.read_line(&mut line).await.unwrap();
+            assert!(read > 0, "the server closed the connection");
+
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(value) = line.strip_prefix("Content-Length: ") {
+                length = value.parse().unwrap();
+            }
+        }
+
+        let mut body = vec![0; length];
+        self.reader.read_exact(&mut body).await.unwrap();
+
+        serde_json::from_slice(&body).unwrap()
+    }
+}
+
+impl Drop for TestEditor {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
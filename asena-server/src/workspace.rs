@@ -0,0 +1,325 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use asena_ast_db::{
+    db::AstDatabase,
+    manifest::{Manifest, MANIFEST_FILE},
+    package::Package,
+    resolution::{resolve_package, Resolution},
+    vfs::{discover_modules, module_name, FileSystem, VfsFileData, VfsPath},
+    ModuleRef,
+};
+use ropey::Rope;
+use tower_lsp::lsp_types::{
+    FileChangeType, FileEvent, FileOperationFilter, FileOperationPattern, FileOperationPatternKind,
+    FileOperationRegistrationOptions, MessageType, Url, WorkspaceFolder,
+};
+
+use crate::backend::{Backend, TextDocumentItem};
+
+/// A workspace folder opened in the editor, it's loaded as a package.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub package: Package,
+
+    /// The absolute paths of the source directories of the package.
+    pub source_dirs: Vec<PathBuf>,
+}
+
+impl Workspace {
+    /// The name of the module of the file, if it's in one of the source directories.
+    pub fn module_name(&self, path: &Path) -> Option<String> {
+        self.source_dirs
+            .iter()
+            .find_map(|source_dir| module_name(source_dir, path))
+    }
+}
+
+impl Backend {
+    /// Loads the package of the workspace folder, with the name and version of its manifest, the
+    /// modules of all its source directories, and its dependencies.
+    pub async fn load_workspace(&self, folder: WorkspaceFolder) {
+        let Ok(root) = folder.uri.to_file_path() else {
+            return;
+        };
+
+        let (manifest, resolution) = self
+            .check_manifest(&root)
+            .await
+            .unwrap_or_else(|| (Manifest::new(&folder.name), Resolution::default()));
+
+        self.info(format!(
+            "loading workspace: {} {} at {root:?}",
+            manifest.name, manifest.version
+        ))
+        .await;
+
+        let source_dirs = manifest
+            .source_dirs
+            .iter()
+            .map(|source_dir| root.join(source_dir))
+            .collect::<Vec<_>>();
+
+        let vfs = Arc::new(FileSystem {
+            base_dir: Some(root.clone()),
+            ..Default::default()
+        });
+
+        let Ok(workspace) = self
+            .write(move |db| {
+                let dependencies = resolution.load(&*db);
+                let package = Package::with_dependencies(
+                    &*db,
+                    &manifest.name,
+                    &manifest.version,
+                    vfs,
+                    dependencies,
+                );
+                // The declarations are imported into the global scope, like `asena check` does,
+                // so the other modules of the workspace can use them.
+                for source_dir in &source_dirs {
+                    for (name, path) in discover_modules(source_dir) {
+                        let file = VfsFileData::new(&*db, &name, VfsPath { path }, package);
+                        db.global_scope().write().unwrap().import(&*db, file, None);
+                    }
+                }
+
+                Workspace {
+                    package,
+                    source_dirs,
+                }
+            })
+            .await
+        else {
+            return;
+        };
+
+        self.workspaces.insert(root, workspace);
+    }
+
+    /// Reads the manifest of the workspace, and resolves its dependencies. The errors are
+    /// published as the diagnostics of the manifest, they are cleared when it's valid.
+    pub async fn check_manifest(&self, root: &Path) -> Option<(Manifest, Resolution)> {
+        let path = root.join(MANIFEST_FILE);
+        let text = std::fs::read_to_string(&path).unwrap_or_default();
+
+        let (checked, error) = match Manifest::read(root) {
+            Ok(manifest) => match resolve_package(root, &manifest) {
+                Ok(resolution) => (Some((manifest, resolution)), None),
+
+                // The package is still loaded, without its dependencies.
+                Err(error) => {
+                    let diagnostic = error.diagnostic(&path, &text);
+                    (Some((manifest, Resolution::default())), Some(diagnostic))
+                }
+            },
+            Err(error) => (None, Some(error.diagnostic(&path, &text))),
+        };
+
+        if let Some(ref diagnostic) = error {
+            let message = diagnostic.message.to_string();
+            self.client.log_message(MessageType::WARNING, message).await;
+        }
+
+        if let Ok(uri) = Url::from_file_path(&path) {
+            // The snapshot locks the database, so it's taken in the blocking thread pool.
+            let diagnostic = match error {
+                Some(diagnostic) => {
+                    let uri = uri.clone();
+                    self.run(move |_, db| {
+                        let rope = Rope::from_str(&text);
+                        Self::build_diagnostic(db, &uri, diagnostic.boxed(), &rope)
+                    })
+                    .await
+                    .ok()
+                    .flatten()
+                }
+                None => None,
+            };
+            let diagnostics = diagnostic.into_iter().collect();
+
+            self.client
+                .publish_diagnostics(uri, diagnostics, None)
+                .await;
+        }
+
+        checked
+    }
+
+    /// Adds the created file to the package of its workspace. If it's a directory, all its
+    /// modules are added.
+    pub async fn create_path(&self, path: PathBuf) {
+        let paths = if path.is_dir() {
+            discover_modules(&path)
+                .into_iter()
+                .map(|(_, path)| path)
+                .collect()
+        } else {
+            vec![path]
+        };
+
+        let modules = paths
+            .into_iter()
+            .filter_map(|path| {
+                self.workspaces.iter().find_map(|workspace| {
+                    let name = workspace.module_name(&path)?;
+                    Some((name, path.clone(), workspace.package))
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let _ = self
+            .write(move |db| {
+                for (name, path, package) in modules {
+                    // The file is registered again, as it's interned only once, if it was deleted
+                    // and created again.
+                    let file = VfsFileData::new(&*db, &name, VfsPath { path }, package);
+                    file.register(&*db);
+                    db.global_scope().write().unwrap().import(&*db, file, None);
+                }
+            })
+            .await;
+    }
+
+    /// Loads the package of the workspace again, when its manifest is changed, so its name,
+    /// version, source directories and dependencies are updated. The opened documents keep their
+    /// unsaved text, and their diagnostics are published again.
+    pub async fn reload_workspace(&self, root: &Path) {
+        let Some((root, workspace)) = self.workspaces.remove(root) else {
+            // The manifests of the other directories aren't loaded, they are only checked.
+            self.check_manifest(root).await;
+            return;
+        };
+
+        let package = workspace.package;
+        let _ = self
+            .write(move |db| {
+                let files = db
+                    .lookup_intern_package(package)
+                    .files
+                    .iter()
+                    .map(|file| *file)
+                    .collect::<Vec<_>>();
+
+                for file in files {
+                    file.close(db);
+                    file.unregister(&*db);
+                }
+            })
+            .await;
+
+        let Ok(uri) = Url::from_directory_path(&root) else {
+            return;
+        };
+        let name = root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.load_workspace(WorkspaceFolder { uri, name }).await;
+
+        let documents = self
+            .documents
+            .iter()
+            .filter(|entry| {
+                let path = entry.key().to_file_path();
+                path.is_ok_and(|path| path.starts_with(&root))
+            })
+            .map(|entry| (entry.key().clone(), entry.value().to_string()))
+            .collect::<Vec<_>>();
+
+        for (uri, text) in documents {
+            let version = self.versions.get(&uri).map(|version| *version);
+            self.on_change(TextDocumentItem {
+                uri,
+                text,
+                version: version.unwrap_or_default(),
+            })
+            .await;
+        }
+    }
+
+    /// Removes the deleted file from its package, and from the global scope. If it's a
+    /// directory, all the files inside of it are removed.
+    pub async fn delete_path(&self, path: PathBuf) {
+        let _ = self
+            .write(move |db| {
+                let files = db
+                    .build_system()
+                    .files
+                    .iter()
+                    .filter(|entry| entry.key().starts_with(&path))
+                    .filter_map(|entry| match entry.value() {
+                        ModuleRef::Found(file) => Some(*file),
+                        ModuleRef::NotFound => None,
+                    })
+                    .collect::<Vec<_>>();
+
+                for file in files {
+                    file.close(db);
+                    file.unregister(&*db);
+                }
+            })
+            .await;
+    }
+
+    /// Reads the changed file from the disk again, only the queries that depend on its source are
+    /// invalidated.
+    pub async fn reload_path(&self, path: PathBuf) {
+        let _ = self
+            .write(move |db| {
+                if let Some(file) = Self::file(db, &path) {
+                    file.reload(db);
+                }
+            })
+            .await;
+    }
+
+    /// Applies the changes of the files made outside of the editor, like by a `git checkout`.
+    pub async fn watched_files_changed(&self, changes: Vec<FileEvent>) {
+        for change in changes {
+            let Ok(path) = change.uri.to_file_path() else {
+                continue;
+            };
+
+            if path.file_name().is_some_and(|name| name == MANIFEST_FILE) {
+                if let Some(root) = path.parent() {
+                    self.reload_workspace(root).await;
+                }
+                continue;
+            }
+
+            match change.typ {
+                FileChangeType::CREATED => self.create_path(path).await,
+                FileChangeType::DELETED => self.delete_path(path).await,
+                _ => self.reload_path(path).await,
+            }
+        }
+    }
+
+    /// The files and directories that the editor notifies when they are created, renamed or
+    /// deleted.
+    pub fn file_operations() -> FileOperationRegistrationOptions {
+        let filter = |glob: &str, matches| FileOperationFilter {
+            scheme: Some("file".into()),
+            pattern: FileOperationPattern {
+                glob: glob.into(),
+                matches: Some(matches),
+                options: None,
+            },
+        };
+
+        FileOperationRegistrationOptions {
+            filters: vec![
+                filter("**/*.ase", FileOperationPatternKind::File),
+                filter("**", FileOperationPatternKind::Folder),
+            ],
+        }
+    }
+}
+
+/// The path of the file of the uri, as it's sent by the file operations.
+pub(crate) fn file_path(uri: &str) -> Option<PathBuf> {
+    Url::parse(uri).ok()?.to_file_path().ok()
+}
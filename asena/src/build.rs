@@ -0,0 +1,183 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use asena_ast_db::manifest::Manifest;
+use asena_hir_lowering::{db::LlirDatabase, LlirConfig};
+use clap::ValueEnum;
+use eyre::{bail, eyre, Context};
+
+use crate::{
+    check::{check_package, report, take_diagnostics, MessageFormat},
+    imp::DatabaseImpl,
+};
+
+/// The name of the static library built by the `asena-runtime` crate, linked by the C backend.
+pub const RUNTIME_LIBRARY: &str = "asena_runtime";
+
+/// The directory of the artifacts, at the root of the package.
+pub const TARGET_DIR: &str = "target";
+
+#[derive(ValueEnum, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    #[default]
+    C,
+    Llvm,
+}
+
+#[derive(Debug, Clone)]
+pub struct BuildOptions {
+    pub backend: Backend,
+
+    /// The root directory of the package, or any directory inside of it.
+    pub dir: PathBuf,
+
+    /// The directory of the artifacts, the `target` directory of the package by default. They
+    /// are written in its `debug` or `release` subdirectory.
+    pub output: Option<PathBuf>,
+    pub runtime: Option<PathBuf>,
+    pub entry: Option<String>,
+
+    /// Builds with the release profile, that enables the optimizations.
+    pub release: bool,
+    pub message_format: MessageFormat,
+    pub config: LlirConfig,
+}
+
+/// Checks the package, like `asena check`, and compiles it with the backend options of its
+/// manifest. The backend only runs if there are no errors. Returns the path of the produced
+/// executable.
+pub fn build(options: &BuildOptions) -> eyre::Result<PathBuf> {
+    let db = DatabaseImpl::default();
+    let checked = check_package(&db, &Manifest::find_root(&options.dir))?;
+    let name = &checked.manifest.name;
+    let pkg = checked.package;
+
+    let diagnostics = take_diagnostics(&db, pkg);
+    let errors = report(&db, &diagnostics, options.message_format);
+    if errors > 0 {
+        bail!("could not compile `{name}` due to {errors} previous errors");
+    }
+
+    // The backend options of the manifest are enabled, even without their flags.
+    let defaults = LlirConfig::from(checked.manifest.backend);
+    let profile = profile(options.release);
+    let output = options
+        .output
+        .clone()
+        .unwrap_or_else(|| checked.root.join(TARGET_DIR))
+        .join(profile);
+    let options = &BuildOptions {
+        config: LlirConfig {
+            optimize: options.config.optimize || defaults.optimize || options.release,
+            debug: options.config.debug || defaults.debug,
+        },
+        ..options.clone()
+    };
+
+    let result = match options.backend {
+        Backend::Llvm => match db.llir_package(pkg, options.config) {
+            Ok(_) => Err(eyre!(
+                "the llvm backend does not emit artifacts yet, use `--backend c`"
+            )),
+            Err(error) => Err(eyre!(error)),
+        },
+        Backend::C => db
+            .c_package(pkg, options.config, options.entry.clone())
+            .map_err(|error| eyre!(error))
+            .and_then(|package| compile_c(options, &output, name, &package.source)),
+    };
+
+    // The diagnostics of the checks were taken out of the packages, so these are the ones
+    // reported by the backend.
+    let diagnostics = take_diagnostics(&db, pkg);
+    let errors = report(&db, &diagnostics, options.message_format);
+    if errors > 0 {
+        bail!("could not compile `{name}` due to {errors} previous errors");
+    }
+
+    result
+}
+
+/// The name of the profile, that is the directory of its artifacts.
+pub fn profile(release: bool) -> &'static str {
+    if release {
+        "release"
+    } else {
+        "debug"
+    }
+}
+
+/// Writes the C source to the output directory, and compiles it with the system C compiler,
+/// that can be changed with the `CC` environment variable.
+fn compile_c(
+    options: &BuildOptions,
+    output: &Path,
+    name: &str,
+    source: &str,
+) -> eyre::Result<PathBuf> {
+    std::fs::create_dir_all(output)
+        .wrap_err_with(|| format!("could not create {}", output.display()))?;
+
+    let source_path = output.join(name).with_extension("c");
+    let executable = output.join(name);
+    std::fs::write(&source_path, source)
+        .wrap_err_with(|| format!("could not write {}", source_path.display()))?;
+
+    let runtime = runtime_dir(options)?;
+    let cc = std::env::var("CC").unwrap_or_else(|_| "cc".into());
+
+    let mut command = Command::new(&cc);
+    command
+        .arg("-std=c99")
+        .arg(if options.config.optimize {
+            "-O2"
+        } else {
+            "-O0"
+        })
+        .arg(&source_path)
+        .arg("-o")
+        .arg(&executable)
+        .arg("-L")
+        .arg(&runtime)
+        .arg(format!("-l{RUNTIME_LIBRARY}"));
+
+    if options.config.debug {
+        command.arg("-g");
+    }
+
+    // The system libraries required by the rust standard library, when statically linked.
+    if cfg!(target_os = "linux") {
+        command.args(["-lpthread", "-ldl", "-lm"]);
+    }
+
+    let status = command
+        .status()
+        .wrap_err_with(|| format!("could not run the C compiler `{cc}`"))?;
+
+    if !status.success() {
+        bail!("the C compiler `{cc}` failed with {status}");
+    }
+
+    Ok(executable)
+}
+
+/// Finds the directory with the runtime static library: the `--runtime` option, the
+/// `ASENA_RUNTIME_DIR` environment variable, or the directory of the current executable, that is
+/// where cargo puts the library when building the workspace.
+fn runtime_dir(options: &BuildOptions) -> eyre::Result<PathBuf> {
+    if let Some(ref runtime) = options.runtime {
+        return Ok(runtime.clone());
+    }
+
+    if let Ok(runtime) = std::env::var("ASENA_RUNTIME_DIR") {
+        return Ok(runtime.into());
+    }
+
+    let executable = std::env::current_exe().wrap_err("could not find the asena executable")?;
+    executable
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| eyre!("could not find the asena runtime, use `--runtime`"))
+}
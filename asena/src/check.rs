@@ -0,0 +1,204 @@
+//! Checks a package, running all the passes of the compiler over its modules, but the backend,
+//! and reports the diagnostics for humans, or as JSON lines for the tools.
+
+use std::{
+    panic::{resume_unwind, AssertUnwindSafe},
+    path::{Path, PathBuf},
+};
+
+use asena_ast_db::{
+    db::AstDatabase,
+    manifest::Manifest,
+    package::{Package, PackageData},
+};
+use asena_hir_db::db::HirDatabase;
+use asena_report::{BoxInternalError, Diagnostic, Severity};
+use clap::ValueEnum;
+use eyre::bail;
+use itertools::Itertools;
+use serde_json::json;
+
+use crate::{imp::DatabaseImpl, project::load_package, search::position_of};
+
+#[derive(ValueEnum, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageFormat {
+    /// The diagnostics are printed with their sources, in the standard error.
+    #[default]
+    Human,
+
+    /// Each diagnostic is printed as a JSON object, in its own line of the standard output.
+    Json,
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckOptions {
+    /// The root directory of the package, or any directory inside of it.
+    pub dir: PathBuf,
+    pub message_format: MessageFormat,
+}
+
+/// A package loaded from its manifest, with all its modules checked.
+#[derive(Debug, Clone)]
+pub struct CheckedPackage {
+    pub manifest: Manifest,
+    pub root: PathBuf,
+    pub package: Package,
+}
+
+/// Checks the package of the directory, and reports its diagnostics. It fails if there are
+/// errors, the warnings are only reported.
+pub fn check(options: &CheckOptions) -> eyre::Result<Manifest> {
+    let db = DatabaseImpl::default();
+    let checked = check_package(&db, &Manifest::find_root(&options.dir))?;
+    let diagnostics = diagnostics(&db, checked.package);
+
+    let errors = report(&db, &diagnostics, options.message_format);
+    if errors > 0 {
+        let name = &checked.manifest.name;
+        bail!("could not check `{name}` due to {errors} previous errors");
+    }
+
+    Ok(checked.manifest)
+}
+
+/// Loads the package at the root directory, with its dependencies, and runs the passes over all
+/// its modules: parsing, infix commands, precedence, resolution, lowering, and the usage of the
+/// multiplicities. There is no type checker yet, so the types are not checked.
+pub fn check_package(db: &DatabaseImpl, root: &Path) -> eyre::Result<CheckedPackage> {
+    let (manifest, package) = load_package(db, root)?;
+    if db.import_modules(root, &manifest, package).is_empty() {
+        bail!(
+            "`{}` has no modules in its source directories",
+            manifest.name
+        );
+    }
+
+    let db = AssertUnwindSafe(db);
+    let result = std::panic::catch_unwind(|| {
+        for package in checked_packages(*db, package) {
+            db.hir_defs(package);
+        }
+    });
+
+    if let Err(err) = result {
+        eprintln!("pipeline of the compiler during checking:");
+        db.dump_and_write_logs();
+        resume_unwind(err);
+    }
+
+    Ok(CheckedPackage {
+        manifest,
+        root: root.to_path_buf(),
+        package,
+    })
+}
+
+/// The diagnostics of the package, and of its dependencies, in the order they were reported.
+pub fn diagnostics(db: &dyn AstDatabase, package: Package) -> Vec<Diagnostic<BoxInternalError>> {
+    checked_packages(db, package)
+        .into_iter()
+        .flat_map(|package| {
+            db.lookup_intern_package(package)
+                .errors
+                .read()
+                .unwrap()
+                .clone()
+        })
+        .collect()
+}
+
+/// Takes the diagnostics of the package, and of its dependencies, out of them, so the next
+/// passes, like the backend, only leave their own diagnostics.
+pub fn take_diagnostics(
+    db: &dyn AstDatabase,
+    package: Package,
+) -> Vec<Diagnostic<BoxInternalError>> {
+    checked_packages(db, package)
+        .into_iter()
+        .flat_map(|package| {
+            let errors = db.lookup_intern_package(package).errors;
+            let mut errors = errors.write().unwrap();
+            std::mem::take(&mut *errors)
+        })
+        .collect()
+}
+
+/// Prints the diagnostics in the message format, and returns the number of errors.
+pub fn report(
+    db: &dyn AstDatabase,
+    diagnostics: &[Diagnostic<BoxInternalError>],
+    format: MessageFormat,
+) -> usize {
+    match format {
+        MessageFormat::Human => {
+            // The diagnostics are reported in the order of the passes, so they're sorted by their
+            // files first, to print each file once.
+            let file = |diagnostic: &&Diagnostic<BoxInternalError>| {
+                diagnostic.message.span.file.clone().unwrap_or_default()
+            };
+            let groups = diagnostics.iter().sorted_by_key(file).group_by(file);
+
+            for (file, diagnostics) in &groups {
+                let source = db.source(db.vfs_file(db.path_module(file.clone())));
+                PackageData::print_file_diagnostics(&file, &source, diagnostics.cloned().collect());
+            }
+        }
+        MessageFormat::Json => {
+            for diagnostic in diagnostics {
+                println!("{}", diagnostic_json(db, diagnostic));
+            }
+        }
+    }
+
+    diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.kind.severity() == Severity::Error)
+        .count()
+}
+
+/// The diagnostic as a JSON object, with its 1-based start and end positions.
+fn diagnostic_json(db: &dyn AstDatabase, diagnostic: &Diagnostic<BoxInternalError>) -> String {
+    let span = &diagnostic.message.span;
+    let file = span.file.clone().unwrap_or_default();
+    let source = db.source(db.vfs_file(db.path_module(file.clone())));
+    let (line, column) = position_of(&source, span.range.start());
+    let (end_line, end_column) = position_of(&source, span.range.end());
+
+    let value = json!({
+        "type": "diagnostic",
+        "file": file.display().to_string(),
+        "line": line,
+        "column": column,
+        "end_line": end_line,
+        "end_column": end_column,
+        "severity": diagnostic.kind.severity().name(),
+        "code": diagnostic.code_name(),
+        "message": diagnostic.message.to_string(),
+    });
+
+    value.to_string()
+}
+
+/// The artifact produced by `asena build`, as a JSON object, like the diagnostics.
+pub fn artifact_json(path: &Path) -> String {
+    let value = json!({
+        "type": "artifact",
+        "path": path.display().to_string(),
+    });
+
+    value.to_string()
+}
+
+/// The package, and its dependencies, but the standard library, that is checked with the
+/// compiler. The dependencies of the package are the transitive ones too, as they're all loaded
+/// from the resolution of its manifest.
+fn checked_packages(db: &dyn AstDatabase, package: Package) -> Vec<Package> {
+    let data = db.lookup_intern_package(package);
+
+    data.dependencies
+        .iter()
+        .copied()
+        .filter(|dependency| *dependency != db.std_package())
+        .chain([package])
+        .collect()
+}
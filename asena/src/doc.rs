@@ -0,0 +1,393 @@
+//! Generates the HTML documentation of a package: a page for each module, with the signatures of
+//! its declarations, and their `///` doc comments written in Markdown. The names in the
+//! signatures are linked to their declarations, using the definitions found by the resolver.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use asena_ast::{BindingId, Decl, Documented, GlobalName, Typed};
+use asena_ast_db::{db::AstDatabase, manifest::Manifest, vfs::VfsFile};
+use asena_ast_resolver::db::AstResolverDatabase;
+use asena_leaf::ast::Located;
+use asena_leaf::token::{kind::TokenKind, token_set::HasTokens, Token};
+use asena_span::{Loc, Spanned};
+use eyre::{bail, Context};
+
+use crate::{imp::DatabaseImpl, project::load_package};
+
+use self::markdown::{escape, markdown};
+
+pub mod markdown;
+
+#[derive(Debug, Clone)]
+pub struct DocOptions {
+    /// The root directory of the package, with the `asena.yml` manifest.
+    pub dir: PathBuf,
+
+    /// The directory where the documentation is written, in a directory named by the package.
+    pub output: PathBuf,
+}
+
+/// A declaration of a module, with the declarations inside of it, like the variants of an enum.
+#[derive(Debug, Clone)]
+pub struct DocItem {
+    pub name: String,
+
+    /// The id of the declaration in the page of its module, the declarations inside of another
+    /// one are prefixed by its anchor, like `Maybe.Just`.
+    pub anchor: String,
+
+    /// The tokens of the signature of the declaration, without its body.
+    pub header: Vec<Spanned<Token>>,
+
+    /// The location of the name, that is the location of its definition. The instances have no
+    /// name, so they can't be referenced.
+    pub selection: Option<Loc>,
+    pub docs: Vec<String>,
+    pub children: Vec<DocItem>,
+}
+
+/// The pages and the anchors of the declarations, by the locations of their definitions.
+type Links = HashMap<(Option<PathBuf>, usize), String>;
+
+/// A module of the package, with its declarations.
+#[derive(Debug, Clone)]
+pub struct DocModule {
+    pub name: String,
+    pub file: VfsFile,
+    pub items: Vec<DocItem>,
+}
+
+impl DocItem {
+    /// A named declaration, its signature is made of the tokens before the `stop` token.
+    fn named<T: HasTokens>(name: BindingId, node: &T, stop: TokenKind) -> Self {
+        let text = name.to_fn_id().to_string();
+
+        Self {
+            anchor: text.clone(),
+            name: text,
+            header: header(node, stop),
+            selection: Some(name.location().into_owned()),
+            docs: node.docs(),
+            children: vec![],
+        }
+    }
+
+    fn with_children(mut self, mut children: Vec<DocItem>) -> Self {
+        for child in children.iter_mut() {
+            child.anchor = format!("{}.{}", self.anchor, child.anchor);
+        }
+
+        self.children = children;
+        self
+    }
+}
+
+/// Generates the documentation of the package at the directory, returning the path of its
+/// index page.
+pub fn doc(options: &DocOptions) -> eyre::Result<PathBuf> {
+    let db = DatabaseImpl::default();
+    let (manifest, pkg) = load_package(&db, &options.dir)?;
+    let files = db.import_modules(&options.dir, &manifest, pkg);
+
+    if files.is_empty() {
+        bail!("could not find any module in {}", options.dir.display());
+    }
+
+    let modules = files
+        .into_iter()
+        .map(|(name, file)| DocModule {
+            items: doc_items(&db, file),
+            name,
+            file,
+        })
+        .collect::<Vec<_>>();
+
+    let mut links = Links::new();
+    for module in &modules {
+        let mut items = module.items.iter().collect::<Vec<_>>();
+        while let Some(item) = items.pop() {
+            items.extend(&item.children);
+
+            if let Some(selection) = &item.selection {
+                let href = format!("{}.html#{}", module.name, item.anchor);
+                links.insert(location_key(selection), href);
+            }
+        }
+    }
+
+    let output = options.output.join(&manifest.name);
+    std::fs::create_dir_all(&output)
+        .wrap_err_with(|| format!("could not create the directory {}", output.display()))?;
+
+    for module in &modules {
+        let path = output.join(format!("{}.html", module.name));
+        let html = render_module(&db, &manifest, module, &links);
+        std::fs::write(&path, html)
+            .wrap_err_with(|| format!("could not write {}", path.display()))?;
+    }
+
+    let index = output.join("index.html");
+    std::fs::write(&index, render_index(&manifest, &modules))
+        .wrap_err_with(|| format!("could not write {}", index.display()))?;
+
+    Ok(index)
+}
+
+/// The declarations of the file, in their order. The signature and the equations of a binding
+/// group are documented once, with the signature, and the docs of the first one that has them.
+pub fn doc_items(db: &dyn AstDatabase, file: VfsFile) -> Vec<DocItem> {
+    use TokenKind::{EqualSymbol, LeftBrace};
+
+    let mut items: Vec<DocItem> = vec![];
+
+    for decl in db.ast(file).declarations() {
+        let item = match decl {
+            Decl::Signature(ref decl) => {
+                let mut item = DocItem::named(decl.name(), decl, LeftBrace);
+
+                // The signature replaces the equations written before it.
+                if let Some(index) = items.iter().position(|group| group.name == item.name) {
+                    let group = items.remove(index);
+                    if item.docs.is_empty() {
+                        item.docs = group.docs;
+                    }
+                }
+
+                item
+            }
+            Decl::Assign(ref decl) => {
+                let item = DocItem::named(decl.name(), decl, EqualSymbol);
+                if let Some(group) = items.iter_mut().find(|group| group.name == item.name) {
+                    if group.docs.is_empty() {
+                        group.docs = item.docs;
+                    }
+                    continue;
+                }
+
+                item
+            }
+            Decl::Enum(ref decl) => {
+                let variants = decl
+                    .variants()
+                    .into_iter()
+                    .map(|variant| DocItem::named(variant.name(), &variant, LeftBrace));
+                let methods = decl
+                    .methods()
+                    .into_iter()
+                    .map(|method| DocItem::named(method.name(), &method, LeftBrace));
+
+                DocItem::named(decl.name(), decl, LeftBrace)
+                    .with_children(variants.chain(methods).collect())
+            }
+            Decl::Class(ref decl) => {
+                let fields = decl
+                    .fields()
+                    .into_iter()
+                    .map(|field| DocItem::named(field.name(), &field, LeftBrace));
+                let methods = decl
+                    .methods()
+                    .into_iter()
+                    .map(|method| DocItem::named(method.name(), &method, LeftBrace));
+
+                DocItem::named(decl.name(), decl, LeftBrace)
+                    .with_children(fields.chain(methods).collect())
+            }
+            Decl::Trait(ref decl) => {
+                let fields = decl
+                    .fields()
+                    .into_iter()
+                    .map(|field| DocItem::named(field.name(), &field, LeftBrace));
+                let methods = decl
+                    .default_methods()
+                    .into_iter()
+                    .map(|method| DocItem::named(method.name(), &method, LeftBrace));
+
+                DocItem::named(decl.name(), decl, LeftBrace)
+                    .with_children(fields.chain(methods).collect())
+            }
+            Decl::Instance(ref decl) => {
+                // The instances have no name, so they are named by their type, like it's written
+                // in the source.
+                let tokens = match decl.gadt_type() {
+                    Typed::Explicit(type_expr) => header(&type_expr, LeftBrace),
+                    Typed::Infer => vec![],
+                };
+                let name = tokens
+                    .iter()
+                    .map(|token| token.value.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let methods = decl
+                    .methods()
+                    .into_iter()
+                    .map(|method| DocItem::named(method.name(), &method, LeftBrace));
+
+                DocItem {
+                    anchor: format!("instance-{}", name.replace(' ', "-")),
+                    name: format!("instance {name}"),
+                    header: header(decl, LeftBrace),
+                    selection: None,
+                    docs: decl.docs(),
+                    children: vec![],
+                }
+                .with_children(methods.collect())
+            }
+            Decl::Use(_) | Decl::Command(_) | Decl::Error => continue,
+        };
+
+        items.push(item);
+    }
+
+    items
+}
+
+/// The tokens of the node before the `stop` token, without the trailing commas and semicolons.
+fn header<T: HasTokens>(node: &T, stop: TokenKind) -> Vec<Spanned<Token>> {
+    let mut tokens = node
+        .tokens()
+        .into_iter()
+        .take_while(|token| token.value.kind != stop && token.value.kind != TokenKind::Eof)
+        .collect::<Vec<_>>();
+
+    while tokens
+        .last()
+        .is_some_and(|token| matches!(token.value.kind, TokenKind::Comma | TokenKind::Semi))
+    {
+        tokens.pop();
+    }
+
+    tokens
+}
+
+fn location_key(loc: &Loc) -> (Option<PathBuf>, usize) {
+    (loc.file.clone(), loc.range.start())
+}
+
+/// Renders the signature of the declaration, in a single line, linking the names to the pages of
+/// their declarations.
+fn render_header(item: &DocItem, references: &HashMap<usize, Loc>, links: &Links) -> String {
+    let mut html = String::new();
+    let mut previous: Option<&Spanned<Token>> = None;
+
+    for token in &item.header {
+        let spaced = previous.is_some_and(|previous| {
+            !previous.value.full_text.after_whitespace.is_empty()
+                || !token.value.full_text.before_whitespace.is_empty()
+        });
+        if spaced {
+            html.push(' ');
+        }
+
+        let text = escape(&token.value.text);
+        let href = references
+            .get(&token.span.range.start())
+            .and_then(|definition| links.get(&location_key(definition)));
+
+        match href {
+            Some(href) => html.push_str(&format!("<a href=\"{}\">{text}</a>", escape(href))),
+            None => html.push_str(&text),
+        }
+
+        previous = Some(token);
+    }
+
+    html
+}
+
+fn render_item(item: &DocItem, references: &HashMap<usize, Loc>, links: &Links) -> String {
+    let mut html = format!("<section class=\"item\" id=\"{}\">\n", escape(&item.anchor));
+    html.push_str(&format!(
+        "<pre class=\"header\"><code>{}</code></pre>\n",
+        render_header(item, references, links)
+    ));
+
+    if !item.docs.is_empty() {
+        let docs = markdown(&item.docs.join("\n"));
+        html.push_str(&format!("<div class=\"docs\">\n{docs}</div>\n"));
+    }
+
+    if !item.children.is_empty() {
+        html.push_str("<div class=\"members\">\n");
+        for child in &item.children {
+            html.push_str(&render_item(child, references, links));
+        }
+        html.push_str("</div>\n");
+    }
+
+    html.push_str("</section>\n");
+    html
+}
+
+fn render_module(
+    db: &DatabaseImpl,
+    manifest: &Manifest,
+    module: &DocModule,
+    links: &Links,
+) -> String {
+    // The definitions of the names referenced in the module, by the start of the names.
+    let references = db
+        .ast_references(module.file)
+        .iter()
+        .map(|reference| {
+            let definition = db.lookup_intern_def(reference.def).token.span;
+            (reference.loc.range.start(), definition)
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut body = format!("<h1>Module <code>{}</code></h1>\n", escape(&module.name));
+    for item in &module.items {
+        body.push_str(&render_item(item, &references, links));
+    }
+
+    render_page(manifest, &module.name, &body)
+}
+
+fn render_index(manifest: &Manifest, modules: &[DocModule]) -> String {
+    let mut body = format!(
+        "<h1>{} <small>{}</small></h1>\n<ul class=\"modules\">\n",
+        escape(&manifest.name),
+        escape(&manifest.version)
+    );
+
+    for module in modules {
+        let name = escape(&module.name);
+        body.push_str(&format!("<li><a href=\"{name}.html\">{name}</a></li>\n"));
+    }
+
+    body.push_str("</ul>\n");
+    render_page(manifest, "Modules", &body)
+}
+
+fn render_page(manifest: &Manifest, title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n\
+         <html lang=\"en\">\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>{title} - {name} {version}</title>\n\
+         <style>{STYLE}</style>\n\
+         </head>\n\
+         <body>\n\
+         <nav><a href=\"index.html\">{name} {version}</a></nav>\n\
+         <main>\n{body}</main>\n\
+         </body>\n\
+         </html>\n",
+        title = escape(title),
+        name = escape(&manifest.name),
+        version = escape(&manifest.version),
+    )
+}
+
+const STYLE: &str = "\
+body { margin: 0; font-family: sans-serif; line-height: 1.5; color: #222; }
+nav { padding: 0.5em 2em; background: #f4f4f4; border-bottom: 1px solid #ddd; }
+main { max-width: 60em; padding: 1em 2em; }
+a { color: #3366aa; text-decoration: none; }
+a:hover { text-decoration: underline; }
+pre, code { font-family: monospace; }
+pre { padding: 0.5em; overflow-x: auto; background: #f8f8f8; }
+.item { margin: 1.5em 0; }
+.item .header { margin: 0; border-left: 3px solid #3366aa; }
+.members { margin-left: 2em; }
+:target > .header { background: #fff8d0; }
+";
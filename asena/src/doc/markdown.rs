@@ -0,0 +1,255 @@
+//! Renders the Markdown of the doc comments into HTML. It supports the paragraphs, the headings,
+//! the lists, the fenced code blocks, and the inline code, emphasis, strong emphasis and links.
+
+/// Renders the Markdown text into HTML, the HTML written in the text is escaped.
+pub fn markdown(text: &str) -> String {
+    let mut blocks = Blocks::default();
+    let mut lines = text.lines();
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("```") {
+            blocks.flush();
+
+            let mut code = String::new();
+            for line in lines.by_ref() {
+                if line.trim().starts_with("```") {
+                    break;
+                }
+                code.push_str(line);
+                code.push('\n');
+            }
+
+            blocks
+                .html
+                .push_str(&format!("<pre><code>{}</code></pre>\n", escape(&code)));
+        } else if trimmed.is_empty() {
+            blocks.flush();
+        } else if let Some((level, title)) = heading(trimmed) {
+            blocks.flush();
+
+            // The headings of the page are used by the declarations.
+            let level = (level + 3).min(6);
+            blocks
+                .html
+                .push_str(&format!("<h{level}>{}</h{level}>\n", inline(title)));
+        } else if let Some((tag, item)) = list_item(trimmed) {
+            if !blocks.paragraph.is_empty() || blocks.list.as_ref().is_some_and(|l| l.tag != tag) {
+                blocks.flush();
+            }
+
+            let list = blocks
+                .list
+                .get_or_insert_with(|| List { tag, items: vec![] });
+            list.items.push(item.to_string());
+        } else if let Some(item) = blocks.list.as_mut().and_then(|list| list.items.last_mut()) {
+            // The lines after an item, that aren't items, continue it.
+            item.push(' ');
+            item.push_str(trimmed);
+        } else {
+            blocks.paragraph.push(trimmed);
+        }
+    }
+
+    blocks.flush();
+    blocks.html
+}
+
+#[derive(Default)]
+struct Blocks<'a> {
+    html: String,
+    paragraph: Vec<&'a str>,
+    list: Option<List>,
+}
+
+struct List {
+    tag: &'static str,
+    items: Vec<String>,
+}
+
+impl Blocks<'_> {
+    /// Writes the paragraph or the list that is open.
+    fn flush(&mut self) {
+        if !self.paragraph.is_empty() {
+            let text = self.paragraph.join(" ");
+            self.html.push_str(&format!("<p>{}</p>\n", inline(&text)));
+            self.paragraph.clear();
+        }
+
+        if let Some(List { tag, items }) = self.list.take() {
+            self.html.push_str(&format!("<{tag}>\n"));
+            for item in items {
+                self.html.push_str(&format!("<li>{}</li>\n", inline(&item)));
+            }
+            self.html.push_str(&format!("</{tag}>\n"));
+        }
+    }
+}
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let title = line[level..].strip_prefix(' ')?;
+    if level == 0 || level > 6 {
+        return None;
+    }
+
+    Some((level, title.trim()))
+}
+
+fn list_item(line: &str) -> Option<(&'static str, &str)> {
+    if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Some(("ul", item));
+    }
+
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    let item = line[digits..].strip_prefix(". ")?;
+    if digits == 0 {
+        return None;
+    }
+
+    Some(("ol", item))
+}
+
+/// Renders the inline code, the emphasis, the strong emphasis and the links of the text.
+fn inline(text: &str) -> String {
+    let mut html = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some((code, after)) = delimited(rest, "`") {
+            html.push_str(&format!("<code>{}</code>", escape(code)));
+            rest = after;
+        } else if let Some((strong, after)) = delimited(rest, "**") {
+            html.push_str(&format!("<strong>{}</strong>", inline(strong)));
+            rest = after;
+        } else if let Some((emphasis, after)) = delimited(rest, "*") {
+            html.push_str(&format!("<em>{}</em>", inline(emphasis)));
+            rest = after;
+        } else if let Some((label, url, after)) = link(rest) {
+            // The links with other schemes, like `javascript:`, are rendered as their label.
+            if is_safe_url(url) {
+                html.push_str(&format!(
+                    "<a href=\"{}\">{}</a>",
+                    escape(url),
+                    inline(label)
+                ));
+            } else {
+                html.push_str(&inline(label));
+            }
+            rest = after;
+        } else {
+            html.push_str(&escape(&rest[..c.len_utf8()]));
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    html
+}
+
+/// Splits the text between the delimiters at the start of the text, and the text after them. The
+/// delimited text can't start with a whitespace, so `a * b` isn't an emphasis.
+fn delimited<'a>(text: &'a str, delimiter: &str) -> Option<(&'a str, &'a str)> {
+    let inner = text.strip_prefix(delimiter)?;
+    if inner.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let end = inner.find(delimiter).filter(|end| *end > 0)?;
+
+    Some((&inner[..end], &inner[end + delimiter.len()..]))
+}
+
+/// Splits a `[label](url)` link at the start of the text.
+fn link(text: &str) -> Option<(&str, &str, &str)> {
+    let inner = text.strip_prefix('[')?;
+    let (label, inner) = inner.split_once("](")?;
+    let (url, after) = inner.split_once(')')?;
+
+    Some((label, url, after))
+}
+
+/// If the url is relative, or its scheme is `http`, `https` or `mailto`. The browsers ignore the
+/// whitespace and the control characters in the schemes, so they are ignored too.
+fn is_safe_url(url: &str) -> bool {
+    let url = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>();
+    let scheme_end = url.find(|c| matches!(c, ':' | '/' | '?' | '#'));
+
+    match scheme_end {
+        Some(end) if url[end..].starts_with(':') => {
+            let scheme = url[..end].to_ascii_lowercase();
+            matches!(scheme.as_str(), "http" | "https" | "mailto")
+        }
+        _ => true,
+    }
+}
+
+/// Escapes the characters of the text that are special in HTML.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_the_blocks() {
+        let text = "# Usage\n\
+                    Adds the\n\
+                    numbers.\n\
+                    \n\
+                    - first\n\
+                    \x20 continued\n\
+                    - second\n\
+                    1. one\n\
+                    \n\
+                    ```\n\
+                    a < b\n\
+                    ```";
+
+        assert_eq!(
+            markdown(text),
+            "<h4>Usage</h4>\n\
+             <p>Adds the numbers.</p>\n\
+             <ul>\n<li>first continued</li>\n<li>second</li>\n</ul>\n\
+             <ol>\n<li>one</li>\n</ol>\n\
+             <pre><code>a &lt; b\n</code></pre>\n"
+        );
+    }
+
+    #[test]
+    fn renders_the_inlines_and_escapes_the_html() {
+        assert_eq!(
+            markdown("`a<b>` **bold** *it* a * b [docs](https://asena.dev/?a=1&b=2) <script>"),
+            "<p><code>a&lt;b&gt;</code> <strong>bold</strong> <em>it</em> a * b \
+             <a href=\"https://asena.dev/?a=1&amp;b=2\">docs</a> &lt;script&gt;</p>\n"
+        );
+    }
+
+    #[test]
+    fn only_links_the_safe_schemes() {
+        assert_eq!(
+            markdown("[a](Nat.html#Succ) [b](mailto:me@asena.dev)"),
+            "<p><a href=\"Nat.html#Succ\">a</a> <a href=\"mailto:me@asena.dev\">b</a></p>\n"
+        );
+        assert_eq!(
+            markdown("[click](javascript:void) [data](DATA:text/html,x) [tab](java\tscript:x)"),
+            "<p>click data tab</p>\n"
+        );
+    }
+}
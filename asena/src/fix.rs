@@ -0,0 +1,73 @@
+use std::path::{Path, PathBuf};
+
+use asena_ast_db::{
+    db::AstDatabase,
+    manifest::Manifest,
+    vfs::{VfsFileData, VfsPath},
+};
+use asena_ast_resolver::db::AstResolverDatabase;
+use asena_prec::PrecDatabase;
+use asena_report::apply_quickfixes;
+use eyre::{eyre, Context};
+
+use crate::{imp::DatabaseImpl, project::load_package, rename::source_files};
+
+#[derive(Debug, Clone)]
+pub struct FixOptions {
+    pub file: PathBuf,
+    pub dry_run: bool,
+}
+
+/// Applies the quickfixes of the diagnostics of the file, like the ones shown by the editor, and
+/// writes the changes to the disk. If it's a dry run, the fixed source is printed instead. Returns
+/// the number of applied fixes.
+pub fn fix(options: &FixOptions) -> eyre::Result<usize> {
+    let base_dir = options
+        .file
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+
+    let db = DatabaseImpl::default();
+    let root = Manifest::find_root(&options.file);
+    let (_, pkg) = load_package(&db, &root)?;
+
+    // The other files of the directory are loaded, so the missing imports can be found.
+    let mut target = None;
+    for path in source_files(&base_dir, &options.file)? {
+        let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+            continue;
+        };
+
+        let name = name.to_string();
+        let is_target = path == options.file;
+        let file = VfsFileData::new(&db, &name, VfsPath { path }, pkg);
+        db.global_scope().write().unwrap().import(&db, file, None);
+        if is_target {
+            target = Some(file);
+        }
+    }
+
+    let target = target.ok_or_else(|| eyre!("could not find {}", options.file.display()))?;
+    let ast = db.ast(target);
+    let ast = db.infix_commands(ast.into());
+    let ast = db.ordered_prec(ast.into());
+    db.ast_resolved_file(ast.into());
+
+    let diagnostics = db.diagnostics(target);
+    let quickfixes = diagnostics
+        .iter()
+        .flat_map(|diagnostic| diagnostic.quickfixes.iter());
+
+    let source = db.source(target);
+    let (fixed, count) = apply_quickfixes(&source, quickfixes);
+
+    if options.dry_run {
+        print!("{fixed}");
+    } else if count > 0 {
+        std::fs::write(&options.file, fixed)
+            .wrap_err_with(|| format!("could not write {}", options.file.display()))?;
+    }
+
+    Ok(count)
+}
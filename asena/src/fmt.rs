@@ -0,0 +1,48 @@
+use std::path::{Path, PathBuf};
+
+use asena_ast_db::vfs::discover_modules;
+use asena_ast_formatter::{format, FormatOptions};
+use eyre::Context;
+
+#[derive(Debug, Clone)]
+pub struct FmtOptions {
+    /// The files to format, if it's empty, all the `.ase` files of the current directory are
+    /// formatted.
+    pub files: Vec<PathBuf>,
+    pub check: bool,
+    pub format: FormatOptions,
+}
+
+/// Formats the files, and writes them to the disk. If it's a check, the files aren't written.
+/// Returns the files that weren't formatted.
+pub fn fmt(options: &FmtOptions) -> eyre::Result<Vec<PathBuf>> {
+    let files = if options.files.is_empty() {
+        discover_modules(Path::new("."))
+            .into_iter()
+            .map(|(_, path)| path)
+            .collect()
+    } else {
+        options.files.clone()
+    };
+
+    let mut unformatted = vec![];
+    for path in files {
+        let source = std::fs::read_to_string(&path)
+            .wrap_err_with(|| format!("could not read {}", path.display()))?;
+        let formatted = format(&source, &options.format)
+            .wrap_err_with(|| format!("could not format {}", path.display()))?;
+
+        if formatted == source {
+            continue;
+        }
+
+        if !options.check {
+            std::fs::write(&path, formatted)
+                .wrap_err_with(|| format!("could not write {}", path.display()))?;
+        }
+
+        unformatted.push(path);
+    }
+
+    Ok(unformatted)
+}
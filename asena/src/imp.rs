@@ -1,9 +1,13 @@
 use asena_ast_db::{
     db::{AstDatabase, AstDatabaseStorage},
-    vfs::VfsFile,
+    manifest::Manifest,
+    package::Package,
+    stdlib::load_std,
+    vfs::{discover_modules, FileSystem, VfsFile, VfsFileData, VfsPath},
 };
 use asena_ast_lowering::db::AstLowerrerStorage;
 use asena_ast_resolver::db::{AstResolverDatabase, AstResolverStorage};
+use asena_highlight::db::HighlightStorage;
 use asena_hir::interner::HirStorage;
 use asena_hir_db::db::HirDatabaseStorage;
 use asena_hir_lowering::{
@@ -12,9 +16,11 @@ use asena_hir_lowering::{
 };
 use asena_leaf::ast::Located;
 use asena_prec::{db::PrecStorage, PrecDatabase};
+use im::Vector;
 use std::{
     panic::{resume_unwind, AssertUnwindSafe},
-    sync::Mutex,
+    path::Path,
+    sync::{Arc, Mutex},
 };
 
 #[salsa::database(
@@ -23,16 +29,73 @@ use std::{
     HirDatabaseStorage,
     AstLowerrerStorage,
     AstResolverStorage,
+    HighlightStorage,
     HirStorage,
     LlirStorage
 )]
-#[derive(Default)]
 pub struct DatabaseImpl {
     pub storage: salsa::Storage<DatabaseImpl>,
     pub logs: Mutex<Vec<salsa::Event>>,
 }
 
+impl Default for DatabaseImpl {
+    /// Creates an empty database, with the standard library loaded.
+    fn default() -> Self {
+        let mut db = Self {
+            storage: Default::default(),
+            logs: Default::default(),
+        };
+        load_std(&mut db);
+        db
+    }
+}
+
 impl DatabaseImpl {
+    /// Creates the package of the manifest, with the name and version of the manifest, rooted at
+    /// the directory.
+    pub fn manifest_package(
+        &self,
+        root: &Path,
+        manifest: &Manifest,
+        dependencies: Vector<Package>,
+    ) -> Package {
+        let vfs = FileSystem {
+            base_dir: Some(root.to_path_buf()),
+            ..Default::default()
+        };
+
+        Package::with_dependencies(
+            self,
+            &manifest.name,
+            &manifest.version,
+            Arc::new(vfs),
+            dependencies,
+        )
+    }
+
+    /// Imports all the modules of the source directories of the package into the global scope,
+    /// and returns them with their names.
+    pub fn import_modules(
+        &self,
+        root: &Path,
+        manifest: &Manifest,
+        pkg: Package,
+    ) -> Vec<(String, VfsFile)> {
+        let mut files = vec![];
+        for source_dir in &manifest.source_dirs {
+            for (name, path) in discover_modules(&root.join(source_dir)) {
+                let file = VfsFileData::new(self, &name, VfsPath { path }, pkg);
+                self.global_scope()
+                    .write()
+                    .unwrap()
+                    .import(self, file, None);
+                files.push((name, file));
+            }
+        }
+
+        files
+    }
+
     pub fn run_pipeline_catching(&self, vfs_file: VfsFile, config: LlirConfig) {
         let db = AssertUnwindSafe(self);
         let result = std::panic::catch_unwind(|| {
@@ -42,7 +105,11 @@ impl DatabaseImpl {
             let file = db.ast_resolved_file(file.into());
             let pkg = db.package_of(file.location().into_owned());
 
+            // The llvm backend is opt-in, the C backend is always available.
+            #[cfg(feature = "llvm")]
             db.llir_package(pkg, config).unwrap(); // TODO: handle
+            #[cfg(not(feature = "llvm"))]
+            db.c_package(pkg, config, None).unwrap();
         });
 
         match result {
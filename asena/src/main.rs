@@ -9,11 +9,28 @@ use std::path::PathBuf;
 
 use asena_grammar::Linebreak;
 use asena_highlight::{Annotator, VirtualFile};
+use asena_hir_lowering::LlirConfig;
 use asena_lexer::Lexer;
+use build::{Backend, BuildOptions};
+use check::{CheckOptions, MessageFormat};
 use clap::{Args, Parser, Subcommand};
+use doc::DocOptions;
+use fix::FixOptions;
+use fmt::FmtOptions;
+use project::NewOptions;
+use rename::RenameOptions;
+use search::SearchOptions;
 
+pub mod build;
+pub mod check;
+pub mod doc;
+pub mod fix;
+pub mod fmt;
 pub mod imp;
 pub mod panik;
+pub mod project;
+pub mod rename;
+pub mod search;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -24,10 +41,101 @@ pub struct Cli {
 }
 
 #[derive(Args, Debug, Clone)]
-pub struct RenameArgs {}
+#[clap(about = "Creates a project in a new directory, with a manifest and a `Main` module.")]
+pub struct NewArgs {
+    /// The directory of the project.
+    pub dir: PathBuf,
+
+    /// The name of the package, the name of the directory by default.
+    #[clap(long)]
+    pub name: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+#[clap(about = "Creates a project in an existing directory, with a manifest and a `Main` module.")]
+pub struct InitArgs {
+    /// The directory of the project.
+    #[clap(short = 'd', long, default_value = ".")]
+    pub dir: PathBuf,
+
+    /// The name of the package, the name of the directory by default.
+    #[clap(long)]
+    pub name: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+#[clap(about = "Renames a name, and all its references in the `.ase` files of the directory.")]
+pub struct RenameArgs {
+    /// The position of the name, as `line:col`, both starting at 1.
+    #[clap(long)]
+    pub at: String,
+
+    /// The new name.
+    #[clap(long)]
+    pub to: String,
+
+    /// A "file.ase" with the name to rename
+    #[clap(short = 'f', long)]
+    pub file: PathBuf,
+}
+
+#[derive(Args, Debug, Clone)]
+#[clap(about = "Applies the quickfixes of the diagnostics of a `.ase` file.")]
+pub struct FixArgs {
+    /// Prints the fixed file, instead of writing it.
+    #[clap(long, default_value = "false")]
+    pub dry_run: bool,
+
+    /// A "file.ase" to fix
+    #[clap(short = 'f', long)]
+    pub file: PathBuf,
+}
+
+#[derive(Args, Debug, Clone)]
+#[clap(about = "Formats the `.ase` files, keeping their comments.")]
+pub struct FmtArgs {
+    /// Checks if the files are formatted, instead of writing them.
+    #[clap(long, default_value = "false")]
+    pub check: bool,
+
+    /// The maximum width of the lines.
+    #[clap(long, default_value = "100")]
+    pub width: usize,
+
+    /// The number of spaces of each indentation level.
+    #[clap(long, default_value = "2")]
+    pub indent: usize,
+
+    /// The files to format, all the `.ase` files of the current directory by default.
+    pub files: Vec<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+#[clap(
+    about = "Searches the declarations of the `.ase` files of a directory, by fuzzy matching their names."
+)]
+pub struct SearchArgs {
+    /// The name to search, the characters must appear in the name in the same order.
+    pub query: String,
+
+    /// The directory with the `.ase` files.
+    #[clap(short = 'd', long, default_value = ".")]
+    pub dir: PathBuf,
+}
 
 #[derive(Args, Debug, Clone)]
-pub struct SearchArgs {}
+#[clap(
+    about = "Generates the HTML documentation of a package, from the `///` comments of its declarations."
+)]
+pub struct DocArgs {
+    /// The root directory of the package, with the `asena.yml` manifest.
+    #[clap(short = 'd', long, default_value = ".")]
+    pub dir: PathBuf,
+
+    /// The directory where the documentation is written.
+    #[clap(short = 'o', long, default_value = "target/doc")]
+    pub output: PathBuf,
+}
 
 #[derive(Args, Debug, Clone)]
 #[clap(aliases = &["hi"])]
@@ -55,12 +163,74 @@ pub struct EvalArgs {
     pub file: String,
 }
 
+#[derive(Args, Debug, Clone)]
+#[clap(
+    about = "Checks all the modules of a package, and of its dependencies, without running the \
+               backend. There is no type checker yet, so the types are not checked."
+)]
+pub struct CheckArgs {
+    /// The root directory of the package, or any directory inside of it.
+    #[clap(short = 'd', long, default_value = ".")]
+    pub dir: PathBuf,
+
+    /// The format of the diagnostics, `json` prints a JSON object by line.
+    #[clap(long, value_enum, default_value_t = MessageFormat::Human)]
+    pub message_format: MessageFormat,
+}
+
+#[derive(Args, Debug, Clone)]
+#[clap(about = "Compiles a package into an executable.")]
+pub struct BuildArgs {
+    /// The root directory of the package, or any directory inside of it.
+    #[clap(short = 'd', long, default_value = ".")]
+    pub dir: PathBuf,
+
+    /// The code generation backend, the `c` backend requires a C compiler in the `PATH`, and the
+    /// `llvm` backend requires the `llvm` feature.
+    #[clap(short, long, value_enum, default_value_t = Backend::C)]
+    pub backend: Backend,
+
+    /// The directory where the artifacts are written, the `target` directory of the package by
+    /// default.
+    #[clap(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// The directory with the `asena-runtime` static library.
+    #[clap(long)]
+    pub runtime: Option<PathBuf>,
+
+    /// The binding run by the executable, the first of `Main` and `main` by default. Only the `c`
+    /// backend supports it.
+    #[clap(long)]
+    pub entry: Option<String>,
+
+    /// Emits debug information.
+    #[clap(short = 'g', long, default_value = "false")]
+    pub debug: bool,
+
+    /// Builds with the release profile, in the `release` directory, with the optimizations of the
+    /// backend.
+    #[clap(long, default_value = "false")]
+    pub release: bool,
+
+    /// The format of the diagnostics, `json` prints a JSON object by line.
+    #[clap(long, value_enum, default_value_t = MessageFormat::Human)]
+    pub message_format: MessageFormat,
+}
+
 #[derive(Subcommand, Debug, Clone)]
 pub enum Command {
+    New(NewArgs),
+    Init(InitArgs),
     Rename(RenameArgs),
+    Fix(FixArgs),
+    Fmt(FmtArgs),
     Search(SearchArgs),
+    Doc(DocArgs),
     Highlight(HighlightArgs),
     Eval(EvalArgs),
+    Check(CheckArgs),
+    Build(BuildArgs),
 }
 
 pub fn run_cli() {
@@ -68,8 +238,134 @@ pub fn run_cli() {
 
     use asena_parser::Parser;
     match cli.command {
-        Command::Rename(..) => todo!(),
-        Command::Search(..) => todo!(),
+        Command::New(args) => {
+            let options = NewOptions {
+                dir: args.dir,
+                name: args.name,
+            };
+
+            match project::new(&options) {
+                Ok(manifest) => println!("Created {}", manifest.display()),
+                Err(error) => {
+                    eprintln!("error: {error:?}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Init(args) => {
+            let options = NewOptions {
+                dir: args.dir,
+                name: args.name,
+            };
+
+            match project::init(&options) {
+                Ok(manifest) => println!("Created {}", manifest.display()),
+                Err(error) => {
+                    eprintln!("error: {error:?}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Rename(args) => {
+            let result = RenameOptions::parse_position(&args.at).and_then(|(line, column)| {
+                rename::rename(&RenameOptions {
+                    file: args.file,
+                    line,
+                    column,
+                    to: args.to,
+                })
+            });
+
+            match result {
+                Ok(count) => println!("Renamed {count} occurrences"),
+                Err(error) => {
+                    eprintln!("error: {error:?}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Fix(args) => {
+            let options = FixOptions {
+                file: args.file,
+                dry_run: args.dry_run,
+            };
+
+            match fix::fix(&options) {
+                Ok(count) if !options.dry_run => println!("Applied {count} fixes"),
+                Ok(_) => {}
+                Err(error) => {
+                    eprintln!("error: {error:?}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Fmt(args) => {
+            let options = FmtOptions {
+                files: args.files,
+                check: args.check,
+                format: asena_ast_formatter::FormatOptions {
+                    width: args.width,
+                    indent: args.indent,
+                },
+            };
+
+            match fmt::fmt(&options) {
+                Ok(files) if options.check && !files.is_empty() => {
+                    for file in files {
+                        println!("Not formatted {}", file.display());
+                    }
+                    std::process::exit(1);
+                }
+                Ok(_) => {}
+                Err(error) => {
+                    eprintln!("error: {error:?}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Search(args) => {
+            let options = SearchOptions {
+                query: args.query,
+                dir: args.dir,
+            };
+
+            match search::search(&options) {
+                Ok(results) => {
+                    for result in results {
+                        let name = match result.symbol.container {
+                            Some(container) => format!("{container}.{}", result.symbol.symbol.name),
+                            None => result.symbol.symbol.name,
+                        };
+
+                        println!(
+                            "{}:{}:{}: {:?} {name}",
+                            result.path.display(),
+                            result.line,
+                            result.column,
+                            result.symbol.symbol.kind,
+                        );
+                    }
+                }
+                Err(error) => {
+                    eprintln!("error: {error:?}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Doc(args) => {
+            let options = DocOptions {
+                dir: args.dir,
+                output: args.output,
+            };
+
+            match doc::doc(&options) {
+                Ok(index) => println!("Finished {}", index.display()),
+                Err(error) => {
+                    eprintln!("error: {error:?}");
+                    std::process::exit(1);
+                }
+            }
+        }
         Command::Highlight(args) if !args.semantic => {
             let path = args.file;
             let file = std::fs::read_to_string(path.clone()).unwrap();
@@ -100,6 +396,49 @@ pub fn run_cli() {
             let tree = parser.build_tree();
             println!("{:#?}", tree.data());
         }
+        Command::Check(args) => {
+            let options = CheckOptions {
+                dir: args.dir,
+                message_format: args.message_format,
+            };
+
+            match check::check(&options) {
+                Ok(manifest) if options.message_format == MessageFormat::Human => {
+                    println!("Checked {} {}", manifest.name, manifest.version)
+                }
+                Ok(_) => {}
+                Err(error) => {
+                    eprintln!("error: {error:?}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Build(args) => {
+            let options = BuildOptions {
+                backend: args.backend,
+                dir: args.dir,
+                output: args.output,
+                runtime: args.runtime,
+                entry: args.entry,
+                release: args.release,
+                message_format: args.message_format,
+                config: LlirConfig {
+                    debug: args.debug,
+                    ..LlirConfig::default()
+                },
+            };
+
+            match build::build(&options) {
+                Ok(executable) => match options.message_format {
+                    MessageFormat::Human => println!("Finished {}", executable.display()),
+                    MessageFormat::Json => println!("{}", check::artifact_json(&executable)),
+                },
+                Err(error) => {
+                    eprintln!("error: {error:?}");
+                    std::process::exit(1);
+                }
+            }
+        }
     }
 }
 
@@ -114,8 +453,11 @@ fn main() {
 mod tests {
     use asena_ast_db::{db::AstDatabase, package::*, vfs::*};
     use asena_hir_lowering::LlirConfig;
+    use asena_report::Severity;
     use std::sync::Arc;
 
+    use crate::imp::DatabaseImpl;
+
     #[test]
     fn pipeline_works() {
         env_logger::builder()
@@ -130,11 +472,204 @@ mod tests {
 
         let local_pkg = Package::new(&db, "Local", "0.0.0", Arc::new(Default::default()));
         let file = VfsFileData::new(&db, "Test", "./Test.ase".into(), local_pkg);
-        VfsFileData::new(&db, "Nat", "./Nat.ase".into(), local_pkg);
-        VfsFileData::new(&db, "IO", "./IO.ase".into(), local_pkg);
 
         db.global_scope().write().unwrap().import(&db, file, None);
         db.run_pipeline_catching(file, LlirConfig::default());
         db.lookup_intern_package(local_pkg).print_diagnostics(&db);
     }
+
+    #[test]
+    fn check_reports_the_errors_of_the_package() {
+        let dir = std::env::temp_dir().join(format!("asena-broken-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let options = crate::project::NewOptions {
+            dir: dir.clone(),
+            name: Some("broken".into()),
+        };
+        crate::project::new(&options).unwrap();
+        std::fs::write(
+            dir.join("src/Main.ase"),
+            "Main {\n  IO.println undefinedName\n}\n",
+        )
+        .unwrap();
+
+        let db = DatabaseImpl::default();
+        let checked = crate::check::check_package(&db, &dir).unwrap();
+        let diagnostics = crate::check::diagnostics(&db, checked.package);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.kind.severity() == Severity::Error));
+    }
+
+    #[test]
+    fn check_reports_the_errors_of_the_transitive_dependencies() {
+        let dir = std::env::temp_dir().join(format!("asena-transitive-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        for name in ["app", "a", "b"] {
+            let options = crate::project::NewOptions {
+                dir: dir.join(name),
+                name: Some(name.into()),
+            };
+            crate::project::new(&options).unwrap();
+        }
+        for (name, dependency) in [("app", "a"), ("a", "b")] {
+            let manifest = dir.join(name).join("asena.yml");
+            let text = std::fs::read_to_string(&manifest).unwrap();
+            let dependencies =
+                format!("dependencies:\n  {dependency}:\n    path: ../{dependency}\n");
+            std::fs::write(&manifest, text + &dependencies).unwrap();
+        }
+        std::fs::write(
+            dir.join("b/src/Broken.ase"),
+            "Broken {\n  IO.println undefinedName\n}\n",
+        )
+        .unwrap();
+
+        let db = DatabaseImpl::default();
+        let checked = crate::check::check_package(&db, &dir.join("app")).unwrap();
+        let diagnostics = crate::check::diagnostics(&db, checked.package);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(
+            diagnostics.iter().any(|diagnostic| {
+                diagnostic.kind.severity() == Severity::Error
+                    && diagnostic
+                        .message
+                        .span
+                        .file
+                        .as_ref()
+                        .is_some_and(|file| file.ends_with("b/src/Broken.ase"))
+            }),
+            "{diagnostics:#?}"
+        );
+    }
+
+    #[test]
+    fn fix_applies_the_quickfixes_of_the_file() {
+        let dir = std::env::temp_dir().join(format!("asena-fix-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let options = crate::project::NewOptions {
+            dir: dir.clone(),
+            name: Some("fix".into()),
+        };
+        crate::project::new(&options).unwrap();
+        let file = dir.join("src/Main.ase");
+        std::fs::write(&file, "id : Nat -> Nat\nid = λn -> n\n").unwrap();
+
+        let dry_run = crate::fix::FixOptions {
+            file: file.clone(),
+            dry_run: true,
+        };
+        assert_eq!(crate::fix::fix(&dry_run).unwrap(), 1);
+        let unchanged = std::fs::read_to_string(&file).unwrap();
+
+        let options = crate::fix::FixOptions {
+            file: file.clone(),
+            dry_run: false,
+        };
+        let count = crate::fix::fix(&options).unwrap();
+        let fixed = std::fs::read_to_string(&file).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(unchanged, "id : Nat -> Nat\nid = λn -> n\n");
+        assert_eq!(count, 1);
+        assert_eq!(fixed, "id : Nat -> Nat\nid = \\n -> n\n");
+    }
+
+    #[test]
+    fn search_finds_the_declarations_of_the_directory() {
+        let dir = std::env::temp_dir().join(format!("asena-search-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let options = crate::project::NewOptions {
+            dir: dir.clone(),
+            name: Some("search".into()),
+        };
+        crate::project::new(&options).unwrap();
+        let file = dir.join("src/Numbers.ase");
+        std::fs::write(
+            &file,
+            "one : Nat\none = 1\n\nplusOne : Nat -> Nat\nplusOne n = n\n",
+        )
+        .unwrap();
+
+        let options = crate::search::SearchOptions {
+            query: "plone".into(),
+            dir: dir.join("src"),
+        };
+        let results = crate::search::search(&options).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let result = results
+            .iter()
+            .find(|result| result.symbol.symbol.name == "plusOne")
+            .unwrap_or_else(|| panic!("{results:#?}"));
+        assert_eq!(result.path, file);
+        assert_eq!((result.line, result.column), (4, 1));
+        assert!(results
+            .iter()
+            .all(|result| result.symbol.symbol.name != "one"));
+    }
+
+    #[test]
+    fn doc_renders_the_docs_and_links_the_declarations() {
+        let dir = std::env::temp_dir().join(format!("asena-doc-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let options = crate::project::NewOptions {
+            dir: dir.clone(),
+            name: Some("flags".into()),
+        };
+        crate::project::new(&options).unwrap();
+        std::fs::write(
+            dir.join("src/Main.ase"),
+            "/// The flags, see [the docs](javascript:void).\n\
+             enum Flag {\n\
+             \x20 /// It's set.\n\
+             \x20 On : Flag,\n\
+             \x20 Off : Flag\n\
+             }\n\
+             \n\
+             /// Flips the **flag**.\n\
+             flip : Flag -> Flag\n\
+             flip f = f\n",
+        )
+        .unwrap();
+
+        let options = crate::doc::DocOptions {
+            dir: dir.clone(),
+            output: dir.join("target/doc"),
+        };
+        let index = crate::doc::doc(&options).unwrap();
+        let index_html = std::fs::read_to_string(&index).unwrap();
+        let main_html = std::fs::read_to_string(index.with_file_name("Main.html")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(index, dir.join("target/doc/flags/index.html"));
+        assert!(
+            index_html.contains("<a href=\"Main.html\">Main</a>"),
+            "{index_html}"
+        );
+
+        assert!(
+            main_html.contains("<p>The flags, see the docs.</p>"),
+            "{main_html}"
+        );
+        assert!(main_html.contains("id=\"Flag.On\""), "{main_html}");
+        assert!(main_html.contains("<p>It&#39;s set.</p>"), "{main_html}");
+        assert!(
+            main_html.contains("<p>Flips the <strong>flag</strong>.</p>"),
+            "{main_html}"
+        );
+        assert!(
+            main_html.contains("<a href=\"Main.html#Flag\">Flag</a>"),
+            "{main_html}"
+        );
+        assert!(!main_html.contains("javascript:"), "{main_html}");
+    }
 }
@@ -0,0 +1,137 @@
+//! Creates the layout of the new projects, and loads the existing ones with their dependencies. A
+//! project is a package with an `asena.yml` manifest, and a `Main` module in its `src`
+//! directory.
+
+use std::path::{Path, PathBuf};
+
+use asena_ast_db::{
+    manifest::{is_package_name, Manifest, MANIFEST_FILE},
+    package::{Package, PackageData},
+    resolution::resolve_package,
+    stdlib::STD_PACKAGE,
+};
+use eyre::{bail, eyre, Context};
+
+use crate::imp::DatabaseImpl;
+
+/// The directory with the modules of the created projects.
+pub const SOURCE_DIR: &str = "src";
+
+/// The entry module of the created projects.
+const MAIN_MODULE: &str = "Main {\n  IO.println \"hello, world\"\n}\n";
+
+#[derive(Debug, Clone)]
+pub struct NewOptions {
+    /// The root directory of the project.
+    pub dir: PathBuf,
+
+    /// The name of the package, the name of the directory by default.
+    pub name: Option<String>,
+}
+
+/// Creates the project in a new directory, and returns the path of its manifest.
+pub fn new(options: &NewOptions) -> eyre::Result<PathBuf> {
+    if options.dir.exists() {
+        bail!(
+            "{} already exists, use `asena init` to create a project inside of it",
+            options.dir.display()
+        );
+    }
+
+    std::fs::create_dir_all(&options.dir)
+        .wrap_err_with(|| format!("could not create {}", options.dir.display()))?;
+
+    init(options)
+}
+
+/// Creates the project in an existing directory, and returns the path of its manifest. The
+/// files of the directory are kept, the `Main` module is only written if it doesn't exist.
+pub fn init(options: &NewOptions) -> eyre::Result<PathBuf> {
+    let manifest_path = options.dir.join(MANIFEST_FILE);
+    if manifest_path.exists() {
+        bail!("{} already exists", manifest_path.display());
+    }
+
+    let name = match options.name {
+        Some(ref name) => name.clone(),
+        None => options
+            .dir
+            .canonicalize()
+            .ok()
+            .and_then(|dir| Some(dir.file_name()?.to_str()?.to_string()))
+            .ok_or_else(|| eyre!("could not name the package, use `--name`"))?,
+    };
+
+    if !is_package_name(&name) {
+        bail!(
+            "`{name}` is not a valid package name, it must start with a letter, followed by \
+             letters, digits, `-` or `_`, use `--name`"
+        );
+    }
+
+    if name == STD_PACKAGE {
+        bail!("`{name}` is reserved for the standard library, use `--name`");
+    }
+
+    let mut manifest = Manifest::new(&name);
+    manifest.version = "0.1.0".into();
+    manifest.source_dirs = vec![PathBuf::from(SOURCE_DIR)];
+
+    let main = options
+        .dir
+        .join(SOURCE_DIR)
+        .join(&manifest.entry)
+        .with_extension("ase");
+    write_new(&main, MAIN_MODULE)?;
+    write_new(&options.dir.join(".gitignore"), "/target\n")?;
+    write_new(&manifest_path, &manifest.render())?;
+
+    Ok(manifest_path)
+}
+
+/// Reads the manifest of the package at the root directory. Its errors are printed as build
+/// diagnostics, at their lines of the manifest.
+fn read_manifest(root: &Path) -> eyre::Result<Manifest> {
+    Manifest::read(root).map_err(|error| {
+        let path = root.join(MANIFEST_FILE);
+        let text = std::fs::read_to_string(&path).unwrap_or_default();
+        let diagnostic = error.diagnostic(&path, &text);
+
+        PackageData::print_file_diagnostics(&path, &text, vec![diagnostic]);
+        eyre!("could not read {} due to previous errors", path.display())
+    })
+}
+
+/// Reads the manifest of the package at the root directory, resolves its dependencies, writing
+/// its lockfile, and creates the package. The errors of the manifest, and of the dependencies,
+/// are printed as build diagnostics.
+pub fn load_package(db: &DatabaseImpl, root: &Path) -> eyre::Result<(Manifest, Package)> {
+    let manifest = read_manifest(root)?;
+    let resolution = resolve_package(root, &manifest).map_err(|error| {
+        let path = root.join(MANIFEST_FILE);
+        let text = std::fs::read_to_string(&path).unwrap_or_default();
+        let diagnostic = error.diagnostic(&path, &text);
+
+        PackageData::print_file_diagnostics(&path, &text, vec![diagnostic]);
+        eyre!("could not resolve the dependencies of `{}`", manifest.name)
+    })?;
+
+    let dependencies = resolution.load(db);
+    let pkg = db.manifest_package(root, &manifest, dependencies);
+
+    Ok((manifest, pkg))
+}
+
+/// Writes the file, and its parent directories, if it doesn't exist yet.
+fn write_new(path: &Path, contents: &str) -> eyre::Result<()> {
+    if path.exists() {
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .wrap_err_with(|| format!("could not create {}", parent.display()))?;
+    }
+
+    std::fs::write(path, contents).wrap_err_with(|| format!("could not write {}", path.display()))
+}
@@ -0,0 +1,170 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use asena_ast_db::{
+    db::AstDatabase,
+    manifest::Manifest,
+    vfs::{VfsFileData, VfsPath},
+};
+use asena_ast_resolver::references::{definition_at, rename as rename_def};
+use eyre::{bail, eyre, Context};
+
+use crate::{imp::DatabaseImpl, project::load_package};
+
+#[derive(Debug, Clone)]
+pub struct RenameOptions {
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub to: String,
+}
+
+impl RenameOptions {
+    /// Parses the `line:col` position, both 1-based.
+    pub fn parse_position(at: &str) -> eyre::Result<(usize, usize)> {
+        let (line, column) = at
+            .split_once(':')
+            .ok_or_else(|| eyre!("invalid position `{at}`, expected `line:col`"))?;
+        let line = line.parse::<usize>().wrap_err("invalid line")?;
+        let column = column.parse::<usize>().wrap_err("invalid column")?;
+        if line == 0 || column == 0 {
+            bail!("invalid position `{at}`, the line and the column starts at 1");
+        }
+
+        Ok((line, column))
+    }
+}
+
+/// Renames the name at the position, and all its references in the `.ase` files of the directory
+/// of the file, writing the changes to the disk. Returns the number of edits.
+pub fn rename(options: &RenameOptions) -> eyre::Result<usize> {
+    let base_dir = options
+        .file
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+
+    let db = DatabaseImpl::default();
+    let root = Manifest::find_root(&options.file);
+    let (_, pkg) = load_package(&db, &root)?;
+
+    let mut files = vec![];
+    let mut target = None;
+    for path in source_files(&base_dir, &options.file)? {
+        let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+            continue;
+        };
+
+        let name = name.to_string();
+        let is_target = path == options.file;
+        let file = VfsFileData::new(&db, &name, VfsPath { path }, pkg);
+        db.global_scope().write().unwrap().import(&db, file, None);
+        if is_target {
+            target = Some(file);
+        }
+        files.push(file);
+    }
+
+    let target = target.ok_or_else(|| eyre!("could not find {}", options.file.display()))?;
+    let source = db.source(target);
+    let offset = offset_of(&source, options.line, options.column).ok_or_else(|| {
+        eyre!(
+            "the position {}:{} is outside the file",
+            options.line,
+            options.column
+        )
+    })?;
+
+    let Some(def) = definition_at(&db, target, offset) else {
+        bail!("there is no name at {}:{}", options.line, options.column);
+    };
+
+    let edits = rename_def(&db, &files, def, &options.to).map_err(|error| eyre!(error))?;
+
+    let mut changes: HashMap<PathBuf, Vec<(usize, usize)>> = HashMap::new();
+    for edit in edits.iter() {
+        let Some(path) = edit.loc.file.clone() else {
+            continue;
+        };
+
+        let range = (edit.loc.range.start(), edit.loc.range.end());
+        changes.entry(path).or_default().push(range);
+    }
+
+    let mut count = 0;
+    for (path, mut ranges) in changes {
+        let file = files
+            .iter()
+            .copied()
+            .find(|file| db.lookup_intern_vfs_file(*file).id.path == path);
+        let mut source = match file {
+            Some(file) => db.source(file).to_string(),
+            None => continue,
+        };
+
+        // Applies the edits from the end of the file, so the offsets of the remaining edits are
+        // still valid.
+        ranges.sort_by(|a, b| b.cmp(a));
+        ranges.dedup();
+        for (start, end) in ranges {
+            source.replace_range(start..end, &options.to);
+            count += 1;
+        }
+
+        std::fs::write(&path, source)
+            .wrap_err_with(|| format!("could not write {}", path.display()))?;
+    }
+
+    Ok(count)
+}
+
+/// The `.ase` files of the directory, the `file` is always included.
+pub(crate) fn source_files(base_dir: &Path, file: &Path) -> eyre::Result<Vec<PathBuf>> {
+    let dir = if base_dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        base_dir
+    };
+
+    let mut paths = vec![file.to_path_buf()];
+    let entries = std::fs::read_dir(dir)
+        .wrap_err_with(|| format!("could not read the directory {}", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        let path = if base_dir.as_os_str().is_empty() {
+            path.strip_prefix(".")
+                .map(Path::to_path_buf)
+                .unwrap_or(path)
+        } else {
+            path
+        };
+
+        if path.extension().is_some_and(|ext| ext == "ase") && path != file {
+            paths.push(path);
+        }
+    }
+
+    Ok(paths)
+}
+
+/// The byte offset of the 1-based `line` and `column`, the column is counted in characters.
+fn offset_of(source: &str, line: usize, column: usize) -> Option<usize> {
+    let mut offset = 0;
+    for (index, text) in source.split_inclusive('\n').enumerate() {
+        if index + 1 == line {
+            let column = text
+                .char_indices()
+                .map(|(index, _)| index)
+                .chain(std::iter::once(text.len()))
+                .nth(column - 1)?;
+
+            return Some(offset + column);
+        }
+
+        offset += text.len();
+    }
+
+    None
+}
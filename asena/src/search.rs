@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+
+use asena_ast_db::{
+    db::AstDatabase,
+    manifest::Manifest,
+    symbols::{workspace_symbols, AstSymbolMatch},
+    vfs::{VfsFileData, VfsPath},
+};
+use eyre::Context;
+
+use crate::{imp::DatabaseImpl, project::load_package};
+
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub query: String,
+    pub dir: PathBuf,
+}
+
+/// A symbol found by the search, with its 1-based position.
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub symbol: AstSymbolMatch,
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Searches the declarations of the `.ase` files of the directory, that fuzzy matches the query,
+/// the best matches comes first.
+pub fn search(options: &SearchOptions) -> eyre::Result<Vec<SearchResult>> {
+    let db = DatabaseImpl::default();
+    let root = Manifest::find_root(&options.dir);
+    let (_, pkg) = load_package(&db, &root)?;
+
+    let entries = std::fs::read_dir(&options.dir)
+        .wrap_err_with(|| format!("could not read the directory {}", options.dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if !path.extension().is_some_and(|ext| ext == "ase") {
+            continue;
+        }
+
+        let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+            continue;
+        };
+
+        let name = name.to_string();
+        VfsFileData::new(&db, &name, VfsPath { path }, pkg);
+    }
+
+    let results = workspace_symbols(&db, &options.query)
+        .into_iter()
+        .filter_map(|symbol| {
+            let path = symbol.symbol.selection.file.clone()?;
+            let module_ref = db.path_module(path.clone());
+            let source = db.source(db.vfs_file(module_ref));
+            let (line, column) = position_of(&source, symbol.symbol.selection.range.start());
+
+            Some(SearchResult {
+                symbol,
+                path,
+                line,
+                column,
+            })
+        })
+        .collect();
+
+    Ok(results)
+}
+
+/// The 1-based line and column of the byte offset, the column is counted in characters.
+pub(crate) fn position_of(source: &str, offset: usize) -> (usize, usize) {
+    let text = source.get(..offset).unwrap_or(source);
+    let line = text.matches('\n').count() + 1;
+    let column = text.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+
+    (line, column)
+}
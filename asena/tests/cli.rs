@@ -0,0 +1,131 @@
+use std::{
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+/// A project created with `asena new` in a temporary directory, it's removed when dropped.
+struct Project(PathBuf);
+
+impl Project {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("asena-cli-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let output = asena(&["new".as_ref(), dir.as_os_str()]);
+        assert!(output.status.success(), "{output:?}");
+
+        Self(dir)
+    }
+
+    fn write(&self, path: &str, text: &str) {
+        std::fs::write(self.0.join(path), text).unwrap();
+    }
+}
+
+impl Drop for Project {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn asena(args: &[&std::ffi::OsStr]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_asena"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn check_succeeds_without_errors() {
+    let project = Project::new("check");
+
+    let output = asena(&["check".as_ref(), "--dir".as_ref(), project.0.as_os_str()]);
+
+    assert_eq!(output.status.code(), Some(0), "{output:?}");
+    assert!(stdout(&output).starts_with("Checked "), "{output:?}");
+}
+
+#[test]
+fn check_fails_with_the_errors_of_the_package() {
+    let project = Project::new("check-errors");
+    project.write("src/Main.ase", "Main {\n  IO.println undefinedName\n}\n");
+
+    let output = asena(&[
+        "check".as_ref(),
+        "--dir".as_ref(),
+        project.0.as_os_str(),
+        "--message-format".as_ref(),
+        "json".as_ref(),
+    ]);
+
+    assert_eq!(output.status.code(), Some(1), "{output:?}");
+    let diagnostics = stdout(&output)
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .collect::<Vec<_>>();
+    assert!(
+        diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic["type"] == "diagnostic"
+                && diagnostic["severity"] == "error"),
+        "{diagnostics:#?}"
+    );
+}
+
+/// The C compiler is replaced by a script, that only creates the file of its `-o` option, so
+/// the path of the artifact is checked without compiling it.
+#[cfg(unix)]
+#[test]
+fn build_prints_the_path_of_the_artifact_of_the_profile() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let project = Project::new("build");
+    let cc = project.0.join("cc");
+    project.write(
+        "cc",
+        "#!/bin/sh\n\
+         while [ $# -gt 0 ]; do\n\
+         \x20 if [ \"$1\" = \"-o\" ]; then touch \"$2\"; fi\n\
+         \x20 shift\n\
+         done\n",
+    );
+    std::fs::set_permissions(&cc, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let build = |release: bool| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_asena"));
+        command
+            .env("CC", &cc)
+            .arg("build")
+            .arg("--dir")
+            .arg(&project.0)
+            .arg("--runtime")
+            .arg(&project.0)
+            .args(["--message-format", "json"]);
+        if release {
+            command.arg("--release");
+        }
+
+        let output = command.output().unwrap();
+        assert_eq!(output.status.code(), Some(0), "{output:?}");
+
+        let artifact = stdout(&output)
+            .lines()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .find(|message| message["type"] == "artifact")
+            .unwrap_or_else(|| panic!("{output:?}"));
+        PathBuf::from(artifact["path"].as_str().unwrap())
+    };
+
+    let name = project.0.file_name().unwrap();
+    let debug = build(false);
+    assert_eq!(debug, project.0.join("target/debug").join(name));
+    assert!(debug.exists());
+
+    let release = build(true);
+    assert_eq!(release, project.0.join("target/release").join(name));
+    assert!(release.exists());
+}